@@ -0,0 +1,39 @@
+//! Benchmarks for writing compressed packets, comparing a new zlib compressor
+//! for every packet against one reused [golden_apple::netty::Compressor].
+//! 
+//! Run with `cargo bench --bench compression`.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use golden_apple::netty::configuration::ServerboundPacket;
+use golden_apple::netty::Compressor;
+use golden_apple::{Identifier, VarInt};
+
+/// Plugin messages just over the compression threshold, and larger ones like
+/// registry data.
+fn packets() -> Vec<(&'static str, ServerboundPacket)> {
+    [("300 bytes", 300), ("4 KiB", 4096), ("30 KiB", 30000)].into_iter().map(|(name, size)| {
+        let packet = ServerboundPacket::PluginMessage {
+            channel: Identifier::from_string(String::from("golden_apple:bench")).unwrap(),
+            data: (0..size).map(|i| (i % 251) as u8).collect()
+        };
+        (name, packet)
+    }).collect()
+}
+
+fn compression(c: &mut Criterion) {
+    let threshold = VarInt::from_value(256).unwrap();
+    let mut group = c.benchmark_group("to_bytes_com");
+    for (name, packet) in packets() {
+        group.bench_with_input(BenchmarkId::new("new compressor", name), &packet, |b, packet| {
+            b.iter(|| black_box(packet.to_bytes_com(threshold).unwrap()));
+        });
+        group.bench_with_input(BenchmarkId::new("reused compressor", name), &packet, |b, packet| {
+            let mut compressor = Compressor::new();
+            b.iter(|| black_box(packet.to_bytes_com_with(threshold, &mut compressor).unwrap()));
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, compression);
+criterion_main!(benches);
@@ -0,0 +1,104 @@
+//! Benchmarks for encoding and decoding VarInts and VarLongs, which happens
+//! several times for every packet.
+//! 
+//! Run with `cargo bench`.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use golden_apple::{VarInt, VarLong};
+
+/// Values that take 1, 2, 3, and 5 bytes, plus a mix like a typical packet
+/// stream full of IDs and short lengths.
+fn int_distributions() -> Vec<(&'static str, Vec<i32>)> {
+    vec![
+        ("1 byte", (0..1024).map(|i| i % 128).collect()),
+        ("2 bytes", (0..1024).map(|i| 128 + i * 15).collect()),
+        ("3 bytes", (0..1024).map(|i| 16384 + i * 2000).collect()),
+        ("5 bytes", (0..1024).map(|i| -1 - i).collect()),
+        ("mixed", (0..1024).map(|i| if i % 8 == 0 { i * 300 } else { i % 100 }).collect())
+    ]
+}
+
+fn long_distributions() -> Vec<(&'static str, Vec<i64>)> {
+    vec![
+        ("1 byte", (0..1024).map(|i| i % 128).collect()),
+        ("2 bytes", (0..1024).map(|i| 128 + i * 15).collect()),
+        ("10 bytes", (0..1024).map(|i| -1 - i).collect())
+    ]
+}
+
+fn varint(c: &mut Criterion) {
+    let mut encode = c.benchmark_group("VarInt::encode_into");
+    for (name, values) in int_distributions() {
+        let values: Vec<VarInt> = values.into_iter().map(|v| VarInt::from_value(v).unwrap()).collect();
+        encode.bench_with_input(BenchmarkId::from_parameter(name), &values, |b, values| {
+            let mut buf = Vec::with_capacity(values.len() * 5);
+            b.iter(|| {
+                buf.clear();
+                for value in values {
+                    value.encode_into(&mut buf);
+                }
+                black_box(&buf);
+            })
+        });
+    }
+    encode.finish();
+
+    let mut decode = c.benchmark_group("VarInt::from_bytes");
+    for (name, values) in int_distributions() {
+        let mut bytes = vec![];
+        for value in &values {
+            VarInt::from_value(*value).unwrap().encode_into(&mut bytes);
+        }
+        decode.bench_with_input(BenchmarkId::from_parameter(name), &bytes, |b, bytes| {
+            b.iter(|| {
+                let mut rest = bytes.as_slice();
+                while !rest.is_empty() {
+                    let (value, used) = VarInt::from_bytes(black_box(rest)).unwrap();
+                    black_box(value);
+                    rest = &rest[used..];
+                }
+            })
+        });
+    }
+    decode.finish();
+}
+
+fn varlong(c: &mut Criterion) {
+    let mut encode = c.benchmark_group("VarLong::encode_into");
+    for (name, values) in long_distributions() {
+        let values: Vec<VarLong> = values.into_iter().map(|v| VarLong::from_value(v).unwrap()).collect();
+        encode.bench_with_input(BenchmarkId::from_parameter(name), &values, |b, values| {
+            let mut buf = Vec::with_capacity(values.len() * 10);
+            b.iter(|| {
+                buf.clear();
+                for value in values {
+                    value.encode_into(&mut buf);
+                }
+                black_box(&buf);
+            })
+        });
+    }
+    encode.finish();
+
+    let mut decode = c.benchmark_group("VarLong::from_bytes");
+    for (name, values) in long_distributions() {
+        let mut bytes = vec![];
+        for value in &values {
+            VarLong::from_value(*value).unwrap().encode_into(&mut bytes);
+        }
+        decode.bench_with_input(BenchmarkId::from_parameter(name), &bytes, |b, bytes| {
+            b.iter(|| {
+                let mut rest = bytes.as_slice();
+                while !rest.is_empty() {
+                    let (value, used) = VarLong::from_bytes(black_box(rest)).unwrap();
+                    black_box(value);
+                    rest = &rest[used..];
+                }
+            })
+        });
+    }
+    decode.finish();
+}
+
+criterion_group!(benches, varint, varlong);
+criterion_main!(benches);
@@ -11,7 +11,7 @@ const AUTOGENERATED: &str =
 fn main() -> Result<(), serde_json::Error> {
     let registries_bytes = include_bytes!("mojang/registries.json");
     // TODO: decide what to do about blockstates
-    let blockstates_bytes = include_bytes!("mojang/blocks.json");
+    let _blockstates_bytes = include_bytes!("mojang/blocks.json");
     let registries: Registries = serde_json::from_slice(registries_bytes)?;
     generate_enum(
         registries.mob_effect.entries.as_object().unwrap(),
@@ -37,6 +37,10 @@ fn main() -> Result<(), serde_json::Error> {
         "Item",
         ""
     );
+    generate_block_items(
+        registries.block.entries.as_object().unwrap(),
+        registries.item.entries.as_object().unwrap()
+    );
     generate_enum(
         registries.particle_type.entries.as_object().unwrap(),
         "particles.rs",
@@ -59,6 +63,7 @@ fn main() -> Result<(), serde_json::Error> {
 }
 
 #[derive(Deserialize)]
+#[allow(dead_code)]
 struct Registries {
     #[serde(rename(deserialize = "minecraft:sound_event"))]
     sound_event: Registry,
@@ -83,6 +88,7 @@ struct Registries {
 }
 
 #[derive(Deserialize)]
+#[allow(dead_code)]
 struct Registry {
     protocol_id: u8,
     default: Option<String>,
@@ -110,7 +116,7 @@ fn convert_to_camel_case(snake_case: &str) -> String {
     let mut tmp_iter = final_out.chars();
     tmp_iter.next().unwrap();
     let final_final_out = format!("{}{}", final_out.chars().take(1).next().unwrap().to_ascii_uppercase(), tmp_iter.as_str());
-    return final_final_out;
+    final_final_out
 }
 
 // TODO: this is gross. I could do this so much better now, so I should. Maybe later!
@@ -118,7 +124,9 @@ fn generate_enum(data_bloq: &Map<String, Value>, save_loc: &str, enum_name: &str
     let mut constructed_blocks = String::from(AUTOGENERATED);
     constructed_blocks += doc_comment;
     constructed_blocks += "\n";
-    constructed_blocks += "#[derive(Clone, Copy, Eq, PartialEq, Debug)]\n";
+    // Variants are declared in order of their protocol IDs, so deriving `Ord`
+    // sorts them by ID.
+    constructed_blocks += "#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Debug)]\n";
     constructed_blocks += "pub enum ";
     constructed_blocks += enum_name;
     constructed_blocks += " {\n";
@@ -154,3 +162,104 @@ fn generate_enum(data_bloq: &Map<String, Value>, save_loc: &str, enum_name: &str
     let destination = std::path::Path::new(&valid_out).join(save_loc);
     std::fs::write(destination, constructed_blocks).unwrap();
 }
+
+/// Crops and other blocks that are placed by an item with a different name,
+/// as (block, item) pairs. The item places the block and the block gives the
+/// item back when picked.
+const PLACED_BY: &[(&str, &str)] = &[
+    ("wheat", "wheat_seeds"),
+    ("beetroots", "beetroot_seeds"),
+    ("carrots", "carrot"),
+    ("potatoes", "potato"),
+    ("cocoa", "cocoa_beans"),
+    ("sweet_berry_bush", "sweet_berries"),
+    ("tripwire", "string"),
+    ("redstone_wire", "redstone"),
+    ("cave_vines", "glow_berries"),
+    ("pitcher_crop", "pitcher_pod"),
+    ("torchflower_crop", "torchflower_seeds"),
+    ("melon_stem", "melon_seeds"),
+    ("pumpkin_stem", "pumpkin_seeds")
+];
+
+/// Blocks that only exist as part of another block, as (block, item) pairs.
+/// The item places a different block, so these only map one way.
+const PICKED_AS: &[(&str, &str)] = &[
+    ("cave_vines_plant", "glow_berries"),
+    ("attached_melon_stem", "melon_seeds"),
+    ("attached_pumpkin_stem", "pumpkin_seeds"),
+    ("bamboo_sapling", "bamboo"),
+    ("big_dripleaf_stem", "big_dripleaf"),
+    ("tall_seagrass", "seagrass"),
+    ("water_cauldron", "cauldron"),
+    ("lava_cauldron", "cauldron"),
+    ("powder_snow_cauldron", "cauldron")
+];
+
+/// Finds the item a block is placed with, if it has one.
+fn item_for_block<'a>(block: &'a str, items: &std::collections::HashSet<&'a str>) -> Option<String> {
+    if let Some((_, item)) = PLACED_BY.iter().chain(PICKED_AS).find(|(name, _)| *name == block) {
+        return Some(item.to_string());
+    }
+    // Torches, signs, banners, heads, and coral fans placed on walls are
+    // separate blocks from the ones placed on the ground.
+    let candidates = [
+        Some(block.to_string()),
+        block.strip_prefix("wall_").map(String::from),
+        block.contains("_wall_").then(|| block.replacen("_wall_", "_", 1)),
+        block.strip_suffix("_plant").map(String::from)
+    ];
+
+    candidates.into_iter().flatten().find(|name| items.contains(name.as_str()))
+}
+
+fn generate_block_items(blocks: &Map<String, Value>, items: &Map<String, Value>) {
+    let block_names: Vec<&str> = blocks.keys().map(|name| name.strip_prefix("minecraft:").unwrap()).collect();
+    let item_names: std::collections::HashSet<&str> =
+        items.keys().map(|name| name.strip_prefix("minecraft:").unwrap()).collect();
+    let mut block_to_item = vec![];
+    let mut item_to_block = vec![];
+    for block in &block_names {
+        if let Some(item) = item_for_block(block, &item_names) {
+            // Only the block an item actually places maps back from the item.
+            if item == *block || PLACED_BY.contains(&(*block, item.as_str())) {
+                item_to_block.push((item.clone(), block.to_string()));
+            }
+            block_to_item.push((block.to_string(), item));
+        }
+    }
+    block_to_item.sort();
+    item_to_block.sort();
+
+    let mut output = String::from(AUTOGENERATED);
+    output += "impl Block {\n";
+    output += "    /// Gives the item used to place this block, which is also what you get when picking it.\n";
+    output += "    /// Blocks like fire, fluids, and portals have no item. This isn't always reversible:\n";
+    output += "    /// blocks placed on walls, like `WallTorch`, give the item of the standing version, and\n";
+    output += "    /// parts of other blocks, like `KelpPlant`, give the item that places the whole thing.\n";
+    output += "    pub fn to_item(self) -> Option<Item> {\n";
+    output += "        match self {\n";
+    for (block, item) in &block_to_item {
+        output += &format!(
+            "            Self::{} => Some(Item::{}),\n",
+            convert_to_camel_case(block), convert_to_camel_case(item)
+        );
+    }
+    output += "            _ => None\n        }\n    }\n}\n\n";
+    output += "impl Item {\n";
+    output += "    /// Gives the block this item places, if it places one. Items like seeds are mapped to\n";
+    output += "    /// the crop they plant, like `WheatSeeds` to `Wheat`, so the `Wheat` item gives `None`.\n";
+    output += "    /// Items that place fluids, like `WaterBucket`, or entities also give `None`.\n";
+    output += "    pub fn to_block(self) -> Option<Block> {\n";
+    output += "        match self {\n";
+    for (item, block) in &item_to_block {
+        output += &format!(
+            "            Self::{} => Some(Block::{}),\n",
+            convert_to_camel_case(item), convert_to_camel_case(block)
+        );
+    }
+    output += "            _ => None\n        }\n    }\n}\n";
+    let valid_out = std::env::var_os("OUT_DIR").unwrap();
+    let destination = std::path::Path::new(&valid_out).join("block_items.rs");
+    std::fs::write(destination, output).unwrap();
+}
@@ -9,6 +9,7 @@ include!(concat!(env!("OUT_DIR"), "/items.rs"));
 include!(concat!(env!("OUT_DIR"), "/particles.rs"));
 include!(concat!(env!("OUT_DIR"), "/professions.rs"));
 include!(concat!(env!("OUT_DIR"), "/custom_stats.rs"));
+include!(concat!(env!("OUT_DIR"), "/block_items.rs"));
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 #[repr(i32)]
@@ -104,6 +105,122 @@ pub enum SkulkVibrationDestination {
     EntityID(crate::VarInt)
 }
 
+#[derive(Clone, Copy, PartialEq, Debug)]
+/// Represents the extra data that follows some particles in a particle packet.
+pub enum ParticleData {
+    /// This particle has no extra data.
+    None,
+    /// The blockstate ID this particle is made from. Used by `block`,
+    /// `block_marker`, `falling_dust`, `dust_pillar`, and `block_crumble`.
+    BlockState(crate::VarInt),
+    /// A colored dust particle.
+    Dust {
+        /// RGB color, encoded as `0xRRGGBB`.
+        color: i32,
+        /// The size of the particle, between 0.01 and 4.
+        scale: f32
+    },
+    /// A dust particle that fades from one color to another.
+    DustColorTransition {
+        /// Starting RGB color, encoded as `0xRRGGBB`.
+        from_color: i32,
+        /// Ending RGB color, encoded as `0xRRGGBB`.
+        to_color: i32,
+        /// The size of the particle, between 0.01 and 4.
+        scale: f32
+    },
+    /// ARGB color of an `entity_effect` particle, encoded as `0xAARRGGBB`.
+    EntityEffect(i32),
+    /// The item an `item` particle is made from.
+    Item(crate::Slot),
+    /// A vibration travelling to a block or entity.
+    Vibration {
+        /// Where this particle is headed.
+        destination: SkulkVibrationDestination,
+        /// The height of the target entity's eyes. Only present when the
+        /// destination is an entity.
+        entity_eye_height: Option<f32>,
+        /// How many ticks it takes for this particle to reach its destination.
+        ticks: crate::VarInt
+    },
+    /// A trail moving towards a target.
+    Trail {
+        /// The target's x coordinate.
+        x: f64,
+        /// The target's y coordinate.
+        y: f64,
+        /// The target's z coordinate.
+        z: f64,
+        /// RGB color, encoded as `0xRRGGBB`.
+        color: i32
+    },
+    /// How much a `sculk_charge` particle is rotated, in radians.
+    SculkCharge(f32),
+    /// How many ticks a `shriek` particle waits before appearing.
+    Shriek(crate::VarInt)
+}
+
+impl Particle {
+    /// Reads the extra data that follows this particle type in a particle
+    /// packet. Returns [ParticleData::None] without reading anything for
+    /// particles that have no extra data.
+    pub fn read_data<R: std::io::Read>(&self, reader: &mut R) -> Result<ParticleData, Error> {
+        use crate::generalized::{double_from_reader, float_from_reader, int_from_reader};
+        use crate::VarInt;
+        match self {
+            Self::Block | Self::BlockMarker | Self::FallingDust |
+            Self::DustPillar | Self::BlockCrumble => {
+                Ok(ParticleData::BlockState(VarInt::from_reader(reader)?))
+            }
+            Self::Dust => {
+                let color = int_from_reader(reader)?;
+                let scale = float_from_reader(reader)?;
+
+                Ok(ParticleData::Dust { color, scale })
+            }
+            Self::DustColorTransition => {
+                let from_color = int_from_reader(reader)?;
+                let to_color = int_from_reader(reader)?;
+                let scale = float_from_reader(reader)?;
+
+                Ok(ParticleData::DustColorTransition { from_color, to_color, scale })
+            }
+            Self::EntityEffect => Ok(ParticleData::EntityEffect(int_from_reader(reader)?)),
+            Self::Item => Ok(ParticleData::Item(crate::Slot::from_reader(reader)?)),
+            Self::Vibration => {
+                let source_type = VarInt::from_reader(reader)?;
+                let (destination, entity_eye_height) = match source_type.value() {
+                    0 => (
+                        SkulkVibrationDestination::BlockPosition(crate::Position::from_reader(reader)?),
+                        None
+                    ),
+                    1 => (
+                        SkulkVibrationDestination::EntityID(VarInt::from_reader(reader)?),
+                        Some(float_from_reader(reader)?)
+                    ),
+                    _ => {
+                        return Err(Error::EnumOutOfBound);
+                    }
+                };
+                let ticks = VarInt::from_reader(reader)?;
+
+                Ok(ParticleData::Vibration { destination, entity_eye_height, ticks })
+            }
+            Self::Trail => {
+                let x = double_from_reader(reader)?;
+                let y = double_from_reader(reader)?;
+                let z = double_from_reader(reader)?;
+                let color = int_from_reader(reader)?;
+
+                Ok(ParticleData::Trail { x, y, z, color })
+            }
+            Self::SculkCharge => Ok(ParticleData::SculkCharge(float_from_reader(reader)?)),
+            Self::Shriek => Ok(ParticleData::Shriek(VarInt::from_reader(reader)?)),
+            _ => Ok(ParticleData::None)
+        }
+    }
+}
+
 #[derive(Clone, Copy, PartialEq, Eq, Debug, FromPrimitive, ToPrimitive)]
 #[repr(u8)]
 /// Represents the direction a painting is facing.
@@ -123,13 +240,49 @@ impl TryFrom<u8> for PaintingDirection {
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+/// The type-specific "data" field of the Spawn Entity packet. What the field
+/// means depends on the type of entity being spawned.
 pub enum SpawnEntityData {
     None,
     HasVelocity(bool),
     MinecartFunctionality(MinecartFunctionality),
+    /// The direction an item frame is facing.
     Orientation(Orientation),
-    BlockType,
-    EntityID(i32)
+    /// The block state ID of a falling block.
+    BlockType(i32),
+    /// The entity ID of whoever owns a projectile or fishing bobber.
+    EntityID(i32),
+    /// Data for an entity type whose data isn't understood yet.
+    Other(i32)
+}
+
+impl SpawnEntityData {
+    /// Interprets the data field of a Spawn Entity packet for the given type
+    /// of entity.
+    pub fn from_data(entity_type: EntityType, data: i32) -> Result<SpawnEntityData, Error> {
+        use EntityType::*;
+        Ok(match entity_type {
+            ItemFrame | GlowItemFrame => SpawnEntityData::Orientation(Orientation::try_from(data)?),
+            FallingBlock => SpawnEntityData::BlockType(data),
+            _ if data == 0 => SpawnEntityData::None,
+            FishingBobber | Arrow | SpectralArrow | Trident | Fireball | SmallFireball |
+            DragonFireball | WitherSkull | ShulkerBullet | LlamaSpit | WindCharge |
+            BreezeWindCharge => SpawnEntityData::EntityID(data),
+            _ => SpawnEntityData::Other(data)
+        })
+    }
+    /// Gives the value sent in the data field of a Spawn Entity packet.
+    pub fn to_data(self) -> i32 {
+        match self {
+            SpawnEntityData::None => 0,
+            SpawnEntityData::HasVelocity(has_velocity) => has_velocity as i32,
+            SpawnEntityData::MinecartFunctionality(functionality) => functionality as i32,
+            SpawnEntityData::Orientation(orientation) => orientation as i32,
+            SpawnEntityData::BlockType(id) |
+            SpawnEntityData::EntityID(id) |
+            SpawnEntityData::Other(id) => id
+        }
+    }
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug, FromPrimitive, ToPrimitive)]
@@ -152,7 +305,8 @@ impl TryFrom<crate::VarInt> for MinecartFunctionality {
     }
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, FromPrimitive, ToPrimitive)]
+#[repr(i32)]
 pub enum Orientation {
     Down = 0,
     Up = 1,
@@ -162,6 +316,14 @@ pub enum Orientation {
     East = 5
 }
 
+impl TryFrom<i32> for Orientation {
+    type Error = Error;
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        num_traits::FromPrimitive::from_i32(value)
+            .ok_or(Error::EnumOutOfBound)
+    }
+}
+
 #[derive(Clone, Copy, PartialEq, Eq, Debug, FromPrimitive, ToPrimitive)]
 #[repr(u8)]
 /// Represents the type of chat message being sent.
@@ -183,3 +345,62 @@ impl TryFrom<u8> for MessageType {
             .ok_or(Error::EnumOutOfBound)
     }
 }
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+/// Represents an entry in the `minecraft:chat_type` registry, as referenced by
+/// the Player Chat and Disguised Chat packets. This replaces [MessageType] in
+/// modern versions, where system messages have their own packet.
+/// 
+/// Chat type IDs are assigned by the order the server sends registry entries
+/// in, so the constants here only apply to servers using the vanilla chat
+/// types in their vanilla order.
+pub struct ChatType(pub crate::VarInt);
+
+impl ChatType {
+    /// `minecraft:chat`, a regular chat message.
+    pub const CHAT: ChatType = ChatType::from_id(0);
+    /// `minecraft:emote_command`, sent with `/me`.
+    pub const EMOTE_COMMAND: ChatType = ChatType::from_id(1);
+    /// `minecraft:msg_command_incoming`, a private message received from `/msg`.
+    pub const MSG_COMMAND_INCOMING: ChatType = ChatType::from_id(2);
+    /// `minecraft:msg_command_outgoing`, a private message sent with `/msg`.
+    pub const MSG_COMMAND_OUTGOING: ChatType = ChatType::from_id(3);
+    /// `minecraft:say_command`, sent with `/say`.
+    pub const SAY_COMMAND: ChatType = ChatType::from_id(4);
+    /// `minecraft:team_msg_command_incoming`, a team message received from `/teammsg`.
+    pub const TEAM_MSG_COMMAND_INCOMING: ChatType = ChatType::from_id(5);
+    /// `minecraft:team_msg_command_outgoing`, a team message sent with `/teammsg`.
+    pub const TEAM_MSG_COMMAND_OUTGOING: ChatType = ChatType::from_id(6);
+
+    const VANILLA: [&'static str; 7] = [
+        "chat",
+        "emote_command",
+        "msg_command_incoming",
+        "msg_command_outgoing",
+        "say_command",
+        "team_msg_command_incoming",
+        "team_msg_command_outgoing"
+    ];
+
+    const fn from_id(id: i32) -> ChatType {
+        ChatType(crate::VarInt { value: id, read_size: None })
+    }
+    /// Gives the vanilla registry Identifier for this chat type, or `None` if
+    /// it's not one of the vanilla chat types.
+    pub fn identifier(self) -> Option<crate::Identifier> {
+        let selector = Self::VANILLA.get(usize::try_from(self.0.value()).ok()?)?;
+
+        crate::Identifier::from_string(format!("minecraft:{}", selector)).ok()
+    }
+    /// Finds the vanilla chat type with the given Identifier, or `None` if it's
+    /// not one of the vanilla chat types.
+    pub fn from_identifier(identifier: &crate::Identifier) -> Option<ChatType> {
+        if identifier.clone().get_namespace() != "minecraft" {
+            return None;
+        }
+        let selector = identifier.clone().get_selector();
+        let index = Self::VANILLA.iter().position(|name| *name == selector)?;
+
+        Some(ChatType::from_id(index as i32))
+    }
+}
@@ -14,6 +14,10 @@ pub enum Error {
     /// The datastream representing a VarInt or VarLong exceded the maximum
     /// acceptable size.
     VarIntTooLong,
+    /// A reader ran out of data partway through a VarInt or VarLong, after its
+    /// first byte but before its last. Running out before the first byte is
+    /// [Error::MissingData] instead.
+    VarIntTruncated,
     /// An error occured while using a `Read` type to parse.
     ReaderError(std::io::Error),
     /// An error occured while using a `Write` type to parse.
@@ -26,6 +30,14 @@ pub enum Error {
     InvalidNbtHeader,
     /// While reading NBT, the stream had an invalid data type ID.
     InvalidNbtType,
+    /// While reading NBT, a list or array had a negative length or one larger
+    /// than allowed.
+    InvalidNbtLength(i32),
+    /// While reading NBT or SNBT, lists and compounds were nested deeper than
+    /// allowed.
+    NbtTooDeep,
+    /// While reading NBT, there was more data than allowed.
+    NbtTooLarge,
     /// While writing NBT, the root tag was not Tag::Compound.
     InvalidRootTag,
     /// The given identifier had more than one `:`, rendering it invalid.
@@ -40,15 +52,55 @@ pub enum Error {
     InvalidJsonType,
     /// A UUID consited of characters other than 0-f.
     InvalidUuid(std::num::ParseIntError),
+    /// A UUID string wasn't 32 hexadecimal digits, with or without hyphens in the standard places.
+    InvalidUuidString(String),
     /// A Java UTF-8 string was unable to be converted to "normal" UTF-8.
     InvalidJavaUtf8(cesu8::Cesu8DecodingError),
     /// A Netty packet had an invalid packet ID.
     InvalidPacketId(VarInt),
+    /// A Netty packet had a valid packet ID, but reading that packet isn't
+    /// supported yet.
+    UnsupportedPacket(VarInt),
+    /// A packet was read in a protocol state it can't appear in, like a
+    /// compressed packet during the "status" stage.
+    InvalidProtocolState(netty::ProtocolState),
     /// A generic IO error was thrown.
     IoError(std::io::Error),
     /// An attempt was made to read or parse a packet destined for the client
     /// during the "handshake" phase of networking, which shouldn't be possible.
-    NoClientboundHandshake
+    NoClientboundHandshake,
+    /// A Netty packet declared a negative length, or one longer than
+    /// [netty::MAX_PACKET_LENGTH]. Also used for compressed packets that
+    /// claim to decompress to more than [netty::MAX_DATA_LENGTH].
+    InvalidPacketLength(VarInt),
+    /// A Slot contained structured data components, which can't be read yet.
+    UnsupportedSlotComponents,
+    /// The server ended the connection, giving this reason.
+    Disconnected(String),
+    /// The server asked to enable encryption, which isn't supported yet.
+    EncryptionUnsupported,
+    /// The connection was closed before a whole packet could be read. Packet
+    /// readers in [netty] return this instead of an end-of-file
+    /// [Error::ReaderError], so a normal disconnect can be told apart from a
+    /// malformed packet.
+    ConnectionClosed,
+    /// A string declared a negative length.
+    InvalidStringLength(VarInt),
+    /// An array declared a negative number of elements.
+    InvalidArrayLength(VarInt),
+    /// A string that should have been normal UTF-8 wasn't.
+    InvalidUtf8(std::str::Utf8Error),
+    /// A client's locale wasn't in the `ll_cc` form, like `en_us`.
+    InvalidLocale(String),
+    /// SNBT text couldn't be parsed. Holds the byte offset the problem was
+    /// found at.
+    InvalidSnbt(usize),
+    /// An error occured while making a request to Mojang's API.
+    #[cfg(feature = "mojang-api")]
+    HttpError(reqwest::Error),
+    /// Mojang's API had no player with the given username or UUID.
+    #[cfg(feature = "mojang-api")]
+    PlayerNotFound
 }
 
 impl std::fmt::Display for Error {
@@ -69,12 +121,31 @@ impl From<std::num::ParseIntError> for Error {
     }
 }
 
+impl From<std::str::Utf8Error> for Error {
+    fn from(e: std::str::Utf8Error) -> Error {
+        Error::InvalidUtf8(e)
+    }
+}
+
+impl From<std::string::FromUtf8Error> for Error {
+    fn from(e: std::string::FromUtf8Error) -> Error {
+        Error::InvalidUtf8(e.utf8_error())
+    }
+}
+
 impl From<cesu8::Cesu8DecodingError> for Error {
     fn from(e: cesu8::Cesu8DecodingError) -> Error {
         Error::InvalidJavaUtf8(e)
     }
 }
 
+#[cfg(feature = "mojang-api")]
+impl From<reqwest::Error> for Error {
+    fn from(e: reqwest::Error) -> Error {
+        Error::HttpError(e)
+    }
+}
+
 impl From<std::io::Error> for Error {
     fn from(e: std::io::Error) -> Error {
         Error::IoError(e)
@@ -84,7 +155,9 @@ impl From<std::io::Error> for Error {
 impl std::error::Error for Error {}
 
 /// Represents a Unique User ID. Used to track players and entities.
-#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+/// 
+/// UUIDs are ordered by their numerical value.
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Ord, PartialOrd)]
 pub struct UUID {
     /// The value of this UUID
     value: u128
@@ -109,12 +182,79 @@ impl UUID {
     pub fn from_value(value: u128) -> Result<UUID, Error> {
         Ok(UUID { value })
     }
+    /// Parses a UUID from text, either in the hyphenated form most sources use, like
+    /// `069a79f4-44e9-4726-a5be-fca90e38aaf5`, or as the 32 digits alone, like Mojang's API
+    /// returns. Either case of hexadecimal digit is accepted.
+    pub fn from_hyphenated(text: &str) -> Result<UUID, Error> {
+        let digits: String = match text.len() {
+            32 => text.to_string(),
+            36 if [8, 13, 18, 23].iter().all(|&index| text.as_bytes()[index] == b'-') => {
+                text.split('-').collect()
+            }
+            _ => {
+                return Err(Error::InvalidUuidString(text.to_string()));
+            }
+        };
+        // from_str_radix allows a leading sign, so the digits are checked by hand.
+        if digits.len() != 32 || !digits.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+            return Err(Error::InvalidUuidString(text.to_string()));
+        }
+
+        Self::from_value(u128::from_str_radix(&digits, 16)?)
+    }
     /// Generates a UUID from a username. This function uses Mojang's API, and may be subject to
-    /// rate limiting. Cache your results.
+    /// rate limiting. Cache your results, for example with [UuidCache].
+    #[cfg(feature = "mojang-api")]
     pub fn from_username(username: String) -> Result<UUID, Error> {
-        use reqwest::blocking::get;
-        let raw_response = get(format!("https://api.mojang.com/users/profiles/minecraft/{}", username)).unwrap().text().unwrap();
-        let json_response: serde_json::Value = serde_json::from_str(&raw_response)?;
+        let raw_response = Self::get_profile(format!("https://api.mojang.com/users/profiles/minecraft/{}", username))?;
+
+        Self::id_from_profile(&raw_response)
+    }
+    /// The same as [UUID::from_username], but doesn't block. Use this inside async runtimes like
+    /// Tokio, where the blocking version panics.
+    #[cfg(feature = "async")]
+    pub async fn from_username_async(username: &str) -> Result<UUID, Error> {
+        let raw_response = Self::get_profile_async(format!("https://api.mojang.com/users/profiles/minecraft/{}", username)).await?;
+
+        Self::id_from_profile(&raw_response)
+    }
+    /// Fetches a single profile from one of Mojang's APIs. Unknown players are reported with
+    /// either a `204 No Content` or a `404 Not Found`, depending on the endpoint.
+    #[cfg(feature = "mojang-api")]
+    fn get_profile(url: String) -> Result<String, Error> {
+        let response = reqwest::blocking::get(url)?;
+        match response.status() {
+            reqwest::StatusCode::NO_CONTENT | reqwest::StatusCode::NOT_FOUND => {
+                Err(Error::PlayerNotFound)
+            }
+            _ => Ok(response.error_for_status()?.text()?)
+        }
+    }
+    /// The same as [UUID::get_profile], using the non-blocking client.
+    #[cfg(feature = "async")]
+    async fn get_profile_async(url: String) -> Result<String, Error> {
+        let response = reqwest::get(url).await?;
+        match response.status() {
+            reqwest::StatusCode::NO_CONTENT | reqwest::StatusCode::NOT_FOUND => {
+                Err(Error::PlayerNotFound)
+            }
+            _ => Ok(response.error_for_status()?.text().await?)
+        }
+    }
+    /// Parses the JSON object returned by Mojang's single profile APIs. An empty body means the
+    /// player doesn't exist.
+    #[cfg(feature = "mojang-api")]
+    fn parse_profile_response(raw_response: &str) -> Result<serde_json::Value, Error> {
+        if raw_response.trim().is_empty() {
+            return Err(Error::PlayerNotFound);
+        }
+
+        Ok(serde_json::from_str(raw_response)?)
+    }
+    /// Reads the UUID out of a profile returned by Mojang's API.
+    #[cfg(feature = "mojang-api")]
+    fn id_from_profile(raw_response: &str) -> Result<UUID, Error> {
+        let json_response = Self::parse_profile_response(raw_response)?;
 
         Self::from_value(
             u128::from_str_radix(
@@ -123,6 +263,49 @@ impl UUID {
             )?
         )
     }
+    /// Reads the username out of a profile returned by Mojang's API.
+    #[cfg(feature = "mojang-api")]
+    fn name_from_profile(raw_response: &str) -> Result<String, Error> {
+        let json_response = Self::parse_profile_response(raw_response)?;
+        let name = json_response["name"].as_str().ok_or(Error::InvalidJsonType)?;
+
+        Ok(name.to_string())
+    }
+    /// Generates UUIDs for many usernames at once. This function uses Mojang's bulk API, sending
+    /// up to 10 names per request, so it is far less likely to be rate limited than calling
+    /// [UUID::from_username] in a loop. Cache your results.
+    /// 
+    /// Returns pairs of usernames (as Mojang capitalizes them) and UUIDs. Usernames that don't
+    /// belong to any player are left out of the result.
+    #[cfg(feature = "mojang-api")]
+    pub fn from_usernames(names: &[String]) -> Result<Vec<(String, UUID)>, Error> {
+        let client = reqwest::blocking::Client::new();
+        let mut players = vec![];
+        for batch in names.chunks(10) {
+            let raw_response = client.post("https://api.mojang.com/profiles/minecraft")
+                .header("Content-Type", "application/json")
+                .body(serde_json::to_string(batch)?)
+                .send()?
+                .error_for_status()?
+                .text()?;
+            players.append(&mut Self::parse_bulk_response(&raw_response)?);
+        }
+
+        Ok(players)
+    }
+    /// Parses the JSON array returned by Mojang's bulk username API.
+    #[cfg(feature = "mojang-api")]
+    fn parse_bulk_response(raw_response: &str) -> Result<Vec<(String, UUID)>, Error> {
+        let json_response: serde_json::Value = serde_json::from_str(raw_response)?;
+        let mut players = vec![];
+        for profile in json_response.as_array().ok_or(Error::InvalidJsonRoot)? {
+            let name = profile["name"].as_str().ok_or(Error::InvalidJsonType)?;
+            let id = profile["id"].as_str().ok_or(Error::InvalidJsonType)?;
+            players.push((name.to_string(), Self::from_value(u128::from_str_radix(id, 16)?)?));
+        }
+
+        Ok(players)
+    }
     /// Writes this UUID to a Write type.
     pub fn to_writer<W: std::io::Write>(self, writer: &mut W) -> Result<(), Error> {
         match writer.write_all(&self.value.to_be_bytes()) {
@@ -138,68 +321,243 @@ impl UUID {
     pub fn to_bytes(self) -> Result<Vec<u8>, Error> {
         Ok(self.value.to_be_bytes().to_vec())
     }
+    /// Appends this UUID to the end of an existing buffer, avoiding the extra allocation of
+    /// [UUID::to_bytes].
+    pub fn encode_into(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&self.value.to_be_bytes());
+    }
+    /// Formats this UUID in its canonical hyphenated form, like
+    /// `069a79f4-44e9-4726-a5be-fca90e38aaf5`. The same as its [std::fmt::Display] output.
+    pub fn to_hyphenated(self) -> String {
+        self.to_string()
+    }
     /// Gives the underlying value of this UUID.
+    pub fn value(&self) -> u128 {
+        self.value
+    }
+    /// Generates a random version 4 UUID, like the ones the Notchian server gives to entities.
+    /// 
+    /// # Panics
+    /// Panics if the operating system's random number generator is unavailable.
+    #[cfg(feature = "random")]
+    pub fn new_v4() -> UUID {
+        let mut bytes = [0; 16];
+        getrandom::getrandom(&mut bytes).expect("the system random number generator failed");
+        let value = u128::from_be_bytes(bytes);
+
+        UUID { value: (value & !(0xF << 76) & !(0b11 << 62)) | (0x4 << 76) | (0b10 << 62) }
+    }
+    /// Gives the version of this UUID, stored in bits 48 to 51. Offline mode players have
+    /// version 3 UUIDs, and online mode players have random version 4 UUIDs.
+    pub fn version(self) -> u8 {
+        ((self.value >> 76) & 0xF) as u8
+    }
+    /// Gives the variant of this UUID, stored in the top bits of its ninth byte. Unused low bits
+    /// are left as zero, so this is `0b10` for the standard RFC 4122 variant, `0b0` for the
+    /// legacy NCS variant, `0b110` for Microsoft's legacy variant, and `0b111` for the reserved
+    /// variant.
+    pub fn variant(self) -> u8 {
+        let bits = ((self.value >> 61) & 0b111) as u8;
+        match bits {
+            0b000..=0b011 => 0b0,
+            0b100 | 0b101 => 0b10,
+            _ => bits
+        }
+    }
+    /// Gives the underlying value of this UUID. This can never fail, so prefer [UUID::value].
+    #[deprecated(note = "this can never fail, use `UUID::value` instead")]
     pub fn to_value(self) -> Result<u128, Error> {
         Ok(self.value)
     }
     /// Gives the username associated with this UUID. This function uses Mojang's API, and may be
-    /// subject to rate limiting. Cache your results.
+    /// subject to rate limiting. Cache your results, for example with [UuidCache].
+    #[cfg(feature = "mojang-api")]
     pub fn to_username(self) -> Result<String, Error> {
-        use reqwest::blocking::get;
-        let mut insertable = format!("{:x}", self.value);
-        insertable = insertable.split('x').next_back().unwrap().to_string();
-        while insertable.len() < 32 {
-            insertable = String::from("0") + &insertable;
-        }
-        let raw_response = get(format!("https://sessionserver.mojang.com/session/minecraft/profile/{}", insertable)).unwrap().text().unwrap();
-        let json_response: serde_json::Value = serde_json::from_str(&raw_response)?;
-        let name = json_response["name"].as_str().ok_or(Error::InvalidJsonType)?;
+        let raw_response = Self::get_profile(format!("https://sessionserver.mojang.com/session/minecraft/profile/{:032x}", self.value))?;
 
-        Ok(name.to_string())
+        Self::name_from_profile(&raw_response)
+    }
+    /// The same as [UUID::to_username], but doesn't block. Use this inside async runtimes like
+    /// Tokio, where the blocking version panics.
+    #[cfg(feature = "async")]
+    pub async fn to_username_async(self) -> Result<String, Error> {
+        let raw_response = Self::get_profile_async(format!("https://sessionserver.mojang.com/session/minecraft/profile/{:032x}", self.value)).await?;
+
+        Self::name_from_profile(&raw_response)
+    }
+}
+
+impl TryFrom<&[u8]> for UUID {
+    type Error = Error;
+    /// Equivalent to [UUID::from_bytes], without the number of bytes used.
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Ok(UUID::from_bytes(bytes)?.0)
+    }
+}
+
+impl std::fmt::Display for UUID {
+    /// Formats this UUID in its canonical hyphenated form, like
+    /// `069a79f4-44e9-4726-a5be-fca90e38aaf5`.
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let hex = format!("{:032x}", self.value);
+        write!(f, "{}-{}-{}-{}-{}", &hex[..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..])
+    }
+}
+
+impl std::str::FromStr for UUID {
+    type Err = Error;
+    /// Equivalent to [UUID::from_hyphenated].
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        UUID::from_hyphenated(text)
+    }
+}
+
+impl std::fmt::Debug for UUID {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "UUID({})", self)
+    }
+}
+
+/// Caches the results of Mojang API lookups, so repeated lookups of the same player don't count
+/// towards the rate limit. Entries are fetched again once they are older than the cache's TTL.
+/// 
+/// Usernames are matched case-insensitively, the same way Mojang's API does.
+#[cfg(feature = "mojang-api")]
+#[derive(Clone, Debug)]
+pub struct UuidCache {
+    ttl: std::time::Duration,
+    uuids: std::collections::HashMap<String, (UUID, std::time::Instant)>,
+    names: std::collections::HashMap<UUID, (String, std::time::Instant)>
+}
+
+#[cfg(feature = "mojang-api")]
+impl UuidCache {
+    /// Creates an empty cache that keeps results for `ttl`.
+    pub fn new(ttl: std::time::Duration) -> Self {
+        UuidCache {
+            ttl,
+            uuids: std::collections::HashMap::new(),
+            names: std::collections::HashMap::new()
+        }
+    }
+    /// Gives the UUID for a username, only calling [UUID::from_username] if it isn't cached or
+    /// has expired.
+    pub fn get_or_fetch_uuid(&mut self, username: &str) -> Result<UUID, Error> {
+        self.uuid_or_else(username, std::time::Instant::now(), |name| UUID::from_username(name.to_string()))
+    }
+    /// Gives the username for a UUID, only calling [UUID::to_username] if it isn't cached or has
+    /// expired.
+    pub fn get_or_fetch_name(&mut self, uuid: UUID) -> Result<String, Error> {
+        self.name_or_else(uuid, std::time::Instant::now(), UUID::to_username)
+    }
+    /// Removes every cached result.
+    pub fn clear(&mut self) {
+        self.uuids.clear();
+        self.names.clear();
+    }
+    /// Looks up a username, using `fetch` when it isn't cached or has expired.
+    fn uuid_or_else<F>(&mut self, username: &str, now: std::time::Instant, fetch: F) -> Result<UUID, Error>
+    where F: FnOnce(&str) -> Result<UUID, Error> {
+        let key = username.to_lowercase();
+        if let Some((uuid, fetched)) = self.uuids.get(&key) {
+            if now.saturating_duration_since(*fetched) < self.ttl {
+                return Ok(*uuid);
+            }
+        }
+        let uuid = fetch(username)?;
+        self.uuids.insert(key, (uuid, now));
+
+        Ok(uuid)
+    }
+    /// Looks up a UUID, using `fetch` when it isn't cached or has expired.
+    fn name_or_else<F>(&mut self, uuid: UUID, now: std::time::Instant, fetch: F) -> Result<String, Error>
+    where F: FnOnce(UUID) -> Result<String, Error> {
+        if let Some((name, fetched)) = self.names.get(&uuid) {
+            if now.saturating_duration_since(*fetched) < self.ttl {
+                return Ok(name.clone());
+            }
+        }
+        let name = fetch(uuid)?;
+        self.names.insert(uuid, (name.clone(), now));
+
+        Ok(name)
     }
 }
 
 use serde::{Serialize, Deserialize};
 
-#[derive(Debug, Clone, Eq, PartialEq)]
+#[derive(Debug, Clone, Eq, PartialEq, Default)]
 /// Represents a chat message or other form of rich text.
 pub struct Chat {
     component: ChatComponent
 }
 
-#[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+/// How a [Chat] is sent over the network. Protocol versions before 765 (1.20.3) send chat as a
+/// JSON string, and 765 onward, including this crate's [PROTOCOL_VERSION], send it as nameless
+/// NBT.
+pub enum ChatFormat {
+    /// A length prefixed JSON string, used before protocol 765.
+    Json,
+    /// A nameless NBT tag, used from protocol 765 onward.
+    Nbt
+}
+
+impl ChatFormat {
+    /// Picks the format used by the given protocol version.
+    pub fn for_protocol(protocol: i32) -> ChatFormat {
+        if protocol >= 765 { ChatFormat::Nbt } else { ChatFormat::Json }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Default, Deserialize, Serialize)]
 #[allow(non_snake_case)]
 /// Represents one component of a Chat object.
 pub struct ChatComponent {
     /// Text to be used.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub text: Option<String>,
     /// Translation key to be used.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub translate: Option<String>,
     /// Key to use the translated keybind for.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub keybind: Option<String>,
     /// Scoreboard to use.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub score: Option<ChatScore>,
     /// Selector to use with `score`.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub selector: Option<String>,
     /// Declares if the text is bold.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub bold: Option<bool>,
     /// Declares if the text is italic.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub italic: Option<bool>,
     /// Declares if the text is underlined.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub underlined: Option<bool>,
     /// Declares if the text has a strikethrough applied to it.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub strikethrough: Option<bool>,
     /// Declares if the text is obfuscated.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub obfuscated: Option<bool>,
     /// Declares the color of the text.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub color: Option<String>,
     /// Declares text to insert into the client's chat when clicked.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub insertion: Option<String>,
     /// Defines an event when this text is clicked.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub clickEvent: Option<ClickEvent>,
     /// Defines an event when a client is hovering over this text.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub hoverEvent: Option<HoverEvent>,
     /// Declares extra components to add aftr this one.
+    #[serde(default, skip_serializing_if = "Option::is_none", deserialize_with = "deserialize_extra")]
     pub extra: Option<Vec<ChatComponent>>
 }
 
@@ -211,6 +569,7 @@ pub struct ChatScore {
     /// Objective of the given scoreboard.
     pub objective: String,
     /// Value to assign to the given scoreboard.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub value: Option<String>
 }
 
@@ -223,7 +582,144 @@ pub struct ClickEvent {
 #[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize)]
 pub struct HoverEvent {
     pub action: String,
-    pub value: String
+    /// The legacy format for hover event data. Modern versions use `contents`
+    /// instead. See [HoverEvent::parse_contents] for a typed view of either.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    /// The modern format for hover event data.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contents: Option<serde_json::Value>
+}
+
+impl HoverEvent {
+    /// Creates a HoverEvent in the modern `contents` format.
+    pub fn from_contents(contents: HoverContents) -> Result<HoverEvent, Error> {
+        let mut fields = serde_json::Map::new();
+        let action = match contents {
+            HoverContents::ShowText(text) => {
+                return Ok(HoverEvent {
+                    action: String::from("show_text"),
+                    value: None,
+                    contents: Some(serde_json::to_value(&text.component)?)
+                });
+            }
+            HoverContents::ShowItem { id, count, components } => {
+                fields.insert(String::from("id"), id.as_string().into());
+                fields.insert(String::from("count"), count.into());
+                if let Some(components) = components {
+                    fields.insert(String::from("components"), components);
+                }
+                "show_item"
+            }
+            HoverContents::ShowEntity { entity_type, id, name } => {
+                fields.insert(String::from("type"), entity_type.as_string().into());
+                fields.insert(String::from("id"), id.to_string().into());
+                if let Some(name) = name {
+                    fields.insert(String::from("name"), serde_json::to_value(&name.component)?);
+                }
+                "show_entity"
+            }
+        };
+
+        Ok(HoverEvent {
+            action: String::from(action),
+            value: None,
+            contents: Some(serde_json::Value::Object(fields))
+        })
+    }
+    /// Reads the data of this HoverEvent into a [HoverContents]. Supports the modern `contents`
+    /// format, and the legacy `value` format for `show_text`.
+    pub fn parse_contents(&self) -> Result<HoverContents, Error> {
+        let contents = match (&self.contents, &self.value) {
+            (Some(contents), _) => contents.clone(),
+            (None, Some(value)) if self.action == "show_text" => {
+                return Ok(HoverContents::ShowText(Chat::from_string(value.clone())?));
+            }
+            _ => {
+                return Err(Error::InvalidJsonRoot);
+            }
+        };
+        match self.action.as_str() {
+            "show_text" => {
+                Ok(HoverContents::ShowText(Chat::from_string(serde_json::to_string(&contents)?)?))
+            }
+            "show_item" => {
+                // A bare string is shorthand for a single item with no components.
+                if let Some(id) = contents.as_str() {
+                    return Ok(HoverContents::ShowItem {
+                        id: Identifier::from_string(id.to_string())?,
+                        count: 1,
+                        components: None
+                    });
+                }
+                let id = contents["id"].as_str().ok_or(Error::InvalidJsonType)?;
+                let count = match contents.get("count") {
+                    Some(count) => count.as_i64().ok_or(Error::InvalidJsonType)? as i32,
+                    None => 1
+                };
+
+                Ok(HoverContents::ShowItem {
+                    id: Identifier::from_string(id.to_string())?,
+                    count,
+                    components: contents.get("components").cloned()
+                })
+            }
+            "show_entity" => {
+                let entity_type = contents["type"].as_str().ok_or(Error::InvalidJsonType)?;
+                let id = match &contents["id"] {
+                    serde_json::Value::String(id) => UUID::from_hyphenated(id)?,
+                    // UUIDs can also be written as four Ints, most significant first.
+                    serde_json::Value::Array(parts) if parts.len() == 4 => {
+                        let mut value = 0;
+                        for part in parts {
+                            let part = part.as_i64().ok_or(Error::InvalidJsonType)? as u32;
+                            value = (value << 32) | part as u128;
+                        }
+                        UUID::from_value(value)?
+                    }
+                    _ => {
+                        return Err(Error::InvalidJsonType);
+                    }
+                };
+                let name = match contents.get("name") {
+                    Some(name) => Some(Chat::from_string(serde_json::to_string(name)?)?),
+                    None => None
+                };
+
+                Ok(HoverContents::ShowEntity {
+                    entity_type: Identifier::from_string(entity_type.to_string())?,
+                    id,
+                    name
+                })
+            }
+            _ => Err(Error::InvalidJsonType)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+/// The typed data shown by a [HoverEvent].
+pub enum HoverContents {
+    /// Shows some text.
+    ShowText(Chat),
+    /// Shows an item's tooltip.
+    ShowItem {
+        /// The item's ID, like `minecraft:diamond_sword`.
+        id: Identifier,
+        /// How many items are in the stack.
+        count: i32,
+        /// The item's data components, as sent by the server.
+        components: Option<serde_json::Value>
+    },
+    /// Shows information about an entity.
+    ShowEntity {
+        /// The entity's type, like `minecraft:pig`.
+        entity_type: Identifier,
+        /// The entity's UUID.
+        id: UUID,
+        /// The entity's custom name, if it has one.
+        name: Option<Chat>
+    }
 }
 
 impl Chat {
@@ -237,56 +733,8 @@ impl Chat {
     }
     pub fn from_string(data: String) -> Result<Chat, Error> {
         let structure: serde_json::Value = serde_json::from_str(&data)?;
-        if structure.is_object() {
-            Ok(Chat {
-                component: serde_json::from_str(&data)?
-            })
-        }
-        else if structure.is_array() {
-            Ok(Chat {
-                component: ChatComponent {
-                    text: None,
-                    translate: None,
-                    keybind: None,
-                    score: None,
-                    selector: None,
-                    bold: None,
-                    italic: None,
-                    underlined: None,
-                    strikethrough: None,
-                    obfuscated: None,
-                    color: None,
-                    insertion: None,
-                    clickEvent: None,
-                    hoverEvent: None,
-                    extra: serde_json::from_str(&data)?
-                }
-            })
-        }
-        else if structure.is_string() {
-            Ok(Chat {
-                component: ChatComponent {
-                    text: serde_json::from_str(&data)?,
-                    translate: None,
-                    keybind: None,
-                    score: None,
-                    selector: None,
-                    bold: None,
-                    italic: None,
-                    underlined: None,
-                    strikethrough: None,
-                    obfuscated: None,
-                    color: None,
-                    insertion: None,
-                    clickEvent: None,
-                    hoverEvent: None,
-                    extra: None
-                }
-            })
-        }
-        else {
-            Err(Error::InvalidJsonRoot)
-        }
+
+        Ok(Chat { component: component_from_value(structure)? })
     }
     pub fn to_bytes(self) -> Result<Vec<u8>, Error> {
         generalized::string_to_bytes(serde_json::to_string(&self.component)?)
@@ -299,6 +747,256 @@ impl Chat {
     pub fn to_string(self) -> Result<String, Error> {
         Ok(serde_json::to_string(&self.component)?)
     }
+    /// Converts this Chat to the NBT form sent over the network since 1.20.3. Components with
+    /// nothing but text become a plain string tag, like the vanilla game sends them.
+    pub fn to_nbt(&self) -> Result<nbt::Tag, Error> {
+        let plain = ChatComponent { text: self.component.text.clone(), ..Default::default() };
+        if self.component == plain {
+            return Ok(nbt::Tag::String(self.component.text.clone().unwrap_or_default()));
+        }
+
+        Ok(json_to_nbt(serde_json::to_value(&self.component)?).unwrap_or(nbt::Tag::End))
+    }
+    /// Writes this Chat in the given format. Packet code that knows which protocol version it's
+    /// talking to can use [ChatFormat::for_protocol] to pick the format.
+    pub fn to_writer_versioned<W: std::io::Write>(
+        self, writer: &mut W, format: ChatFormat
+    ) -> Result<(), Error> {
+        match format {
+            ChatFormat::Json => self.to_writer(writer),
+            ChatFormat::Nbt => nbt::to_network_writer(writer, &self.to_nbt()?)
+        }
+    }
+    /// Creates a Chat with some unformatted text.
+    pub fn plain(text: &str) -> Chat {
+        Chat {
+            component: ChatComponent {
+                text: Some(text.to_string()),
+                ..Default::default()
+            }
+        }
+    }
+    /// Creates a Chat with some text in a single color. The color can be a named color, like
+    /// `gold`, or a hex code, like `#FFAA00`.
+    pub fn colored(text: &str, color: &str) -> Chat {
+        Chat {
+            component: ChatComponent {
+                text: Some(text.to_string()),
+                color: Some(color.to_string()),
+                ..Default::default()
+            }
+        }
+    }
+    /// Splits this Chat into a flat list of runs, one per component, in reading order. Each run
+    /// has no `extra` components and carries the full styling it inherits from its parents.
+    pub fn flatten(&self) -> Vec<ChatComponent> {
+        let mut runs = vec![];
+        flatten_component(&self.component, &ChatComponent::default(), &mut runs);
+
+        runs
+    }
+    /// Gives the visible text of this Chat without any formatting. Components that depend on
+    /// the client, like translations and keybinds, are shown as their raw keys.
+    pub fn to_plain_text(&self) -> String {
+        self.flatten().iter().map(visible_text).collect()
+    }
+    /// Checks if the visible text of this Chat, as given by [Chat::to_plain_text], contains
+    /// `needle`. Text split across several components still matches.
+    pub fn contains(&self, needle: &str) -> bool {
+        self.to_plain_text().contains(needle)
+    }
+    /// Like [Chat::contains], but ignores differences in case.
+    pub fn contains_ignore_case(&self, needle: &str) -> bool {
+        self.to_plain_text().to_lowercase().contains(&needle.to_lowercase())
+    }
+    /// Checks if `pattern` matches anywhere in the visible text of this Chat, as given by
+    /// [Chat::to_plain_text]. Requires the `regex` feature.
+    #[cfg(feature = "regex")]
+    pub fn matches(&self, pattern: &regex::Regex) -> bool {
+        pattern.is_match(&self.to_plain_text())
+    }
+    /// Shortens this Chat to at most `max_chars` visible characters, followed by an ellipsis if
+    /// anything was cut. Characters are counted as Unicode scalar values, so multi-byte
+    /// characters count as one. Each run that's kept keeps its styling.
+    pub fn truncate(&self, max_chars: usize) -> Chat {
+        let runs = self.flatten();
+        let total: usize = runs.iter().map(|run| visible_text(run).chars().count()).sum();
+        if total <= max_chars {
+            return self.clone();
+        }
+
+        let mut remaining = max_chars;
+        let mut kept = vec![];
+        for mut run in runs {
+            if remaining == 0 {
+                break;
+            }
+            let text = visible_text(&run);
+            let length = text.chars().count();
+            if length == 0 {
+                continue;
+            }
+            if length > remaining {
+                let cut: String = text.chars().take(remaining).collect();
+                run.text = Some(cut);
+                run.translate = None;
+                run.keybind = None;
+                run.score = None;
+                run.selector = None;
+                remaining = 0;
+            }
+            else {
+                remaining -= length;
+            }
+            kept.push(run);
+        }
+        // The ellipsis continues the style of the text it follows.
+        let mut ellipsis = match kept.last() {
+            Some(last) => last.clone(),
+            None => ChatComponent::default()
+        };
+        ellipsis.text = Some(String::from("\u{2026}"));
+        ellipsis.translate = None;
+        ellipsis.keybind = None;
+        ellipsis.score = None;
+        ellipsis.selector = None;
+        kept.push(ellipsis);
+
+        Chat {
+            component: ChatComponent {
+                text: Some(String::new()),
+                extra: Some(kept),
+                ..Default::default()
+            }
+        }
+    }
+}
+
+impl TryFrom<&[u8]> for Chat {
+    type Error = Error;
+    /// Equivalent to [Chat::from_bytes], without the number of bytes used.
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Ok(Chat::from_bytes(bytes)?.0)
+    }
+}
+
+/// Builds a component from any of the JSON shapes text can take: an object, a plain string, or an
+/// array. Like the vanilla game, the first element of an array is the parent of the rest, which
+/// inherit its style.
+fn component_from_value(value: serde_json::Value) -> Result<ChatComponent, Error> {
+    match value {
+        serde_json::Value::Object(_) => Ok(serde_json::from_value(value)?),
+        serde_json::Value::String(text) => Ok(ChatComponent { text: Some(text), ..Default::default() }),
+        serde_json::Value::Array(elements) => {
+            let mut elements = elements.into_iter();
+            let mut parent = component_from_value(elements.next().ok_or(Error::InvalidJsonRoot)?)?;
+            let children = elements.map(component_from_value).collect::<Result<Vec<_>, Error>>()?;
+            if !children.is_empty() {
+                parent.extra.get_or_insert_with(Vec::new).extend(children);
+            }
+
+            Ok(parent)
+        }
+        _ => Err(Error::InvalidJsonRoot)
+    }
+}
+
+/// Reads `extra` components, which may be plain strings or arrays as well as objects.
+fn deserialize_extra<'de, D: serde::Deserializer<'de>>(
+    deserializer: D
+) -> Result<Option<Vec<ChatComponent>>, D::Error> {
+    let Some(elements) = Option::<Vec<serde_json::Value>>::deserialize(deserializer)? else {
+        return Ok(None);
+    };
+    elements
+        .into_iter()
+        .map(|element| component_from_value(element).map_err(serde::de::Error::custom))
+        .collect::<Result<Vec<_>, D::Error>>()
+        .map(Some)
+}
+
+/// Converts serialized chat JSON to NBT. Booleans become bytes, and lists that mix types have
+/// each element wrapped in a compound, as NBT lists can only hold one type. Returns `None` for
+/// `null`, which NBT can't represent.
+fn json_to_nbt(value: serde_json::Value) -> Option<nbt::Tag> {
+    match value {
+        serde_json::Value::Null => None,
+        serde_json::Value::Bool(value) => Some(nbt::Tag::Byte(value as i8)),
+        serde_json::Value::Number(number) => {
+            if let Some(value) = number.as_i64() {
+                match i32::try_from(value) {
+                    Ok(value) => Some(nbt::Tag::Int(value)),
+                    Err(_) => Some(nbt::Tag::Long(value))
+                }
+            }
+            else {
+                Some(nbt::Tag::Double(number.as_f64().unwrap_or_default()))
+            }
+        }
+        serde_json::Value::String(value) => Some(nbt::Tag::String(value)),
+        serde_json::Value::Array(elements) => {
+            let mut elements: Vec<nbt::Tag> = elements.into_iter().filter_map(json_to_nbt).collect();
+            let mixed = elements.windows(2).any(|pair| {
+                std::mem::discriminant(&pair[0]) != std::mem::discriminant(&pair[1])
+            });
+            if mixed {
+                elements = elements.into_iter().map(|element| match element {
+                    nbt::Tag::Compound(_) => element,
+                    nbt::Tag::String(_) => nbt::Tag::Compound(vec![nbt::NamedTag::new("text", element)]),
+                    _ => nbt::Tag::Compound(vec![nbt::NamedTag::new("", element)])
+                }).collect();
+            }
+
+            Some(nbt::Tag::List(elements))
+        }
+        serde_json::Value::Object(fields) => {
+            Some(nbt::Tag::Compound(fields.into_iter().filter_map(|(name, value)| {
+                json_to_nbt(value).map(|tag| nbt::NamedTag::new(name, tag))
+            }).collect()))
+        }
+    }
+}
+
+/// Gives the text a single component shows, ignoring its `extra` components.
+fn visible_text(component: &ChatComponent) -> String {
+    if let Some(text) = &component.text {
+        text.clone()
+    }
+    else if let Some(translate) = &component.translate {
+        translate.clone()
+    }
+    else if let Some(keybind) = &component.keybind {
+        keybind.clone()
+    }
+    else if let Some(score) = &component.score {
+        score.value.clone().unwrap_or_default()
+    }
+    else {
+        component.selector.clone().unwrap_or_default()
+    }
+}
+
+fn flatten_component(component: &ChatComponent, parent: &ChatComponent, runs: &mut Vec<ChatComponent>) {
+    let mut run = component.clone();
+    run.extra = None;
+    run.bold = run.bold.or(parent.bold);
+    run.italic = run.italic.or(parent.italic);
+    run.underlined = run.underlined.or(parent.underlined);
+    run.strikethrough = run.strikethrough.or(parent.strikethrough);
+    run.obfuscated = run.obfuscated.or(parent.obfuscated);
+    run.color = run.color.or_else(|| parent.color.clone());
+    run.insertion = run.insertion.or_else(|| parent.insertion.clone());
+    run.clickEvent = run.clickEvent.or_else(|| parent.clickEvent.clone());
+    run.hoverEvent = run.hoverEvent.or_else(|| parent.hoverEvent.clone());
+    if let Some(extra) = &component.extra {
+        runs.push(run.clone());
+        for child in extra {
+            flatten_component(child, &run, runs);
+        }
+    }
+    else {
+        runs.push(run);
+    }
 }
 
 
@@ -312,6 +1010,10 @@ pub mod enums;
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 /// Represents a namespaced selector.
+/// 
+/// Identifiers without a namespace, like `stone` or `:stone`, are given the
+/// `minecraft` namespace as soon as they're created. Because of this,
+/// `stone` and `minecraft:stone` are always equal.
 pub struct Identifier {
     namespace: String,
     selector: String
@@ -343,8 +1045,14 @@ impl Identifier {
                 })
             }
             2 => {
+                // Like the vanilla game, `:stone` is treated the same as `stone`.
+                let namespace = match whole_chunks[0] {
+                    "" => "minecraft",
+                    namespace => namespace
+                };
+
                 Ok(Identifier {
-                    namespace: String::from(whole_chunks[0]),
+                    namespace: String::from(namespace),
                     selector: String::from(whole_chunks[1])
                 })
             }
@@ -353,23 +1061,34 @@ impl Identifier {
     }
     /// Writes this Identifier to a series of bytes.
     pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
-        generalized::string_to_bytes_no_cesu8(self.to_string()?)
+        generalized::string_to_bytes_no_cesu8(self.as_string())
     }
     /// Writes this Identifier to a Write type.
     pub fn to_writer<W: std::io::Write>(self, writer: &mut W) -> Result<(), Error> {
-        generalized::string_to_writer(writer, self.to_string()?)?;
+        generalized::string_to_writer(writer, self.as_string())?;
 
         Ok(())
     }
     /// Writes this Identifier to a String. Always writes in the extended format
     /// for selectors under the `minecraft` namespace.
-    pub fn to_string(&self) -> Result<String, Error> {
+    pub fn as_string(&self) -> String {
         let mut full_string = String::new();
         full_string += &self.namespace;
         full_string += ":";
         full_string += &self.selector;
 
-        Ok(full_string)
+        full_string
+    }
+    /// Writes this Identifier to a String. This can never fail, so prefer
+    /// [Identifier::as_string].
+    #[deprecated(note = "this can never fail, use `Identifier::as_string` instead")]
+    pub fn to_string(&self) -> Result<String, Error> {
+        Ok(self.as_string())
+    }
+    /// Returns `true` if this Identifier is in the `minecraft` namespace. This
+    /// includes Identifiers that were created without a namespace.
+    pub fn is_vanilla(&self) -> bool {
+        self.namespace == "minecraft"
     }
     /// Get the namespace of this Identifier. This is the part before the colon.
     pub fn get_namespace(self) -> String {
@@ -381,6 +1100,48 @@ impl Identifier {
     }
 }
 
+impl TryFrom<&[u8]> for Identifier {
+    type Error = Error;
+    /// Equivalent to [Identifier::from_bytes], without the number of bytes used.
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Ok(Identifier::from_bytes(bytes)?.0)
+    }
+}
+
+impl PartialEq<str> for Identifier {
+    /// Compares against the `namespace:selector` form of an Identifier. Like
+    /// [Identifier::from_string], a missing namespace means `minecraft`, so
+    /// `minecraft:stone` is equal to `"stone"`. Strings that aren't valid
+    /// Identifiers are never equal.
+    fn eq(&self, other: &str) -> bool {
+        let (namespace, selector) = match other.split_once(':') {
+            Some(("", selector)) => ("minecraft", selector),
+            Some(parts) => parts,
+            None => ("minecraft", other)
+        };
+
+        !selector.contains(':') && self.namespace == namespace && self.selector == selector
+    }
+}
+
+impl PartialEq<&str> for Identifier {
+    fn eq(&self, other: &&str) -> bool {
+        *self == **other
+    }
+}
+
+impl PartialEq<Identifier> for str {
+    fn eq(&self, other: &Identifier) -> bool {
+        *other == *self
+    }
+}
+
+impl PartialEq<Identifier> for &str {
+    fn eq(&self, other: &Identifier) -> bool {
+        *other == **self
+    }
+}
+
 use std::f64::consts::PI;
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
 /// Represents an angle. Cannot be greater than one full rotation, does not have negative values.
@@ -398,33 +1159,29 @@ impl Angle {
 
         Ok((Angle { value: bytes[0] }, 1))
     }
+    /// Creates a new `Angle` from a Read type.
+    pub fn from_reader<R: std::io::Read>(reader: &mut R) -> Result<Angle, Error> {
+        Ok(Angle { value: read_byte(reader)? })
+    }
     /// Creates a new `Angle` that is the given amount of degrees. Absoulte value is taken for
-    /// negative values. Values over a full turn have the amount of turns discarded. Some
-    /// significant precision is lost switching to Minecraft's format.
+    /// negative values. Values of a full turn or more have the amount of turns discarded. Some
+    /// significant precision is lost switching to Minecraft's format: the result is rounded down
+    /// to the nearest 256th of a turn.
     pub fn from_degrees(degrees: f64) -> Angle {
-        let mut workable = degrees;
-        if workable < 0.0 {
-            workable *= -1.0;
-        }
-        while workable > 360.0 {
-            workable -= 360.0;
-        }
+        // A full turn wraps back around to zero.
+        let workable = degrees.abs() % 360.0;
 
         Angle {
             value: ((workable / 360.0) * 256.0) as u8
         }
     }
     /// Creates a new `Angle` that is the given amount of radians. Absoulte value is taken for
-    /// negative values. Values over a full turn have the amount of turns discarded. Some
-    /// significant precision is lost switching to Minecraft's format.
+    /// negative values. Values of a full turn or more have the amount of turns discarded. Some
+    /// significant precision is lost switching to Minecraft's format: the result is rounded down
+    /// to the nearest 256th of a turn.
     pub fn from_radians(radians: f64) -> Angle {
-        let mut workable = radians;
-        if workable < 0.0 {
-            workable *= -1.0;
-        }
-        while workable > 2.0 * PI {
-            workable -= 2.0 * PI;
-        }
+        // A full turn wraps back around to zero.
+        let workable = radians.abs() % (2.0 * PI);
 
         Angle {
             value: ((workable / (2.0 * PI)) * 256.0) as u8
@@ -476,6 +1233,12 @@ impl VarInt {
     /// Creates a VarInt from a series of bytes. Returns the value and the amount of bytes used if
     /// creation is successful.
     pub fn from_bytes(data: &[u8]) -> Result<(VarInt, usize), Error> {
+        // Fast path: most VarInts are small IDs and lengths that fit in a single byte.
+        if let Some(&first) = data.first() {
+            if first & 0b10000000 == 0 {
+                return Ok((VarInt { value: first as i32, read_size: Some(1) }, 1));
+            }
+        }
         let mut iterator = data.iter();
         let mut result = 0;
 
@@ -490,13 +1253,14 @@ impl VarInt {
 
             result |= ((read & mask) as i32) << (7 * i);
 
-            // The 5th byte is only allowed to have the 4 smallest bits set
+            // The 5th byte only holds the top 4 bits of the i32, so only the 4 smallest bits may be
+            // set. This also rules out a continuation bit, so any 6th byte is an error.
             if i == 4 && (read & 0xf0 != 0) {
                 return Err(Error::VarIntTooLong);
             }
 
             if (read & msb) == 0 {
-                return Ok((VarInt { value: result, read_size: Some(i) }, i as usize));
+                return Ok((VarInt { value: result, read_size: Some(i + 1) }, i as usize + 1));
             }
         }
         // This will never occur.
@@ -510,17 +1274,18 @@ impl VarInt {
         let mask: u8 = !msb;
     
         for i in 0..5 {
-            let read = read_byte(reader)?;
+            let read = read_var_byte(reader, i == 0)?;
     
             result |= ((read & mask) as i32) << (7 * i);
     
-            // The 5th byte is only allowed to have the 4 smallest bits set
+            // The 5th byte only holds the top 4 bits of the i32, so only the 4 smallest bits may be
+            // set. This also rules out a continuation bit, so any 6th byte is an error.
             if i == 4 && (read & 0xf0 != 0) {
                 return Err(Error::VarIntTooLong);
             }
     
             if (read & msb) == 0 {
-                return Ok(VarInt { value: result, read_size: Some(i) });
+                return Ok(VarInt { value: result, read_size: Some(i + 1) });
             }
         }
         // This will never occur.
@@ -560,43 +1325,166 @@ impl VarInt {
     /// Converts a VarInt to a series of bytes.
     pub fn to_bytes(self) -> Result<Vec<u8>, Error> {
         let mut bytes = vec![];
+        self.encode_into(&mut bytes);
+
+        Ok(bytes)
+    }
+    /// Appends this VarInt to the end of an existing buffer, avoiding the extra allocation of
+    /// [VarInt::to_bytes].
+    pub fn encode_into(&self, buf: &mut Vec<u8>) {
         let msb: u8 = 0b10000000;
         let mask: i32 = 0b01111111;
-        let mut val = self.value;
 
+        // Fast path for the one and two byte values that make up most packets.
+        let unsigned = self.value as u32;
+        if unsigned < 0x80 {
+            buf.push(unsigned as u8);
+            return;
+        }
+        if unsigned < 0x4000 {
+            buf.extend_from_slice(&[(unsigned as u8) | msb, (unsigned >> 7) as u8]);
+            return;
+        }
+
+        let mut val = self.value;
         for _ in 0..5 {
             let tmp = (val & mask) as u8;
             val &= !mask;
             val = val.rotate_right(7);
 
             if val != 0 {
-                bytes.push(tmp | msb);
+                buf.push(tmp | msb);
             } else {
-                bytes.push(tmp);
-                return Ok(bytes);
+                buf.push(tmp);
+                return;
             }
         }
         // This will never occur.
-        unreachable!("VarInt::to_bytes reached end of function, which should not be possible");
+        unreachable!("VarInt::encode_into reached end of function, which should not be possible");
     }
     /// Creates a VarInt from a given value.
-    pub fn from_value(value: i32) -> Result<VarInt, Error> {
-        Ok(VarInt { value, read_size: None })
+    pub const fn new(value: i32) -> VarInt {
+        VarInt { value, read_size: None }
     }
+    /// Creates a VarInt from a given value. This can't fail; [VarInt::new] does the same without
+    /// the `Result`.
+    pub fn from_value(value: i32) -> Result<VarInt, Error> {
+        Ok(VarInt::new(value))
+    }
+    /// Creates a VarInt from a given value using zig-zag encoding, where small negative numbers
+    /// take as few bytes as small positive ones (-1 is encoded as 1, 1 as 2, and so on). Only use
+    /// this for formats that expect it; the Minecraft protocol itself doesn't.
+    pub fn from_value_zigzag(value: i32) -> Result<VarInt, Error> {
+        Self::from_value((value << 1) ^ (value >> 31))
+    }
+    /// Returns the value of a VarInt that was zig-zag encoded, like one made with
+    /// [VarInt::from_value_zigzag].
+    pub fn zigzag_value(self) -> i32 {
+        let unsigned = self.value as u32;
+        ((unsigned >> 1) as i32) ^ -((unsigned & 1) as i32)
+    }
+    /// Gives how many bytes this was read from. Returns `None` if it wasn't
+    /// read from bytes and the size hasn't been calculated.
     pub fn read_size(&self) -> Option<u8> {
         self.read_size
     }
     /// Calculates the size of this [VarInt] when encoded in bytes. Stores the
     /// value in this type so that you can use [VarInt::read_size].
     pub fn calculate_read_size(&mut self) {
-        // TODO: this is gross. do better.
-        let bytes = self.to_bytes().unwrap();
-        let val = VarInt::from_bytes(&bytes).unwrap().0;
-        self.read_size = val.read_size;
+        self.read_size = Some(self.encoded_len());
+    }
+    /// Gives how many bytes this VarInt takes up when encoded, from 1 to 5, without encoding it.
+    /// Negative values always take 5.
+    pub const fn encoded_len(&self) -> u8 {
+        let bits = 32 - (self.value as u32).leading_zeros();
+        if bits == 0 {
+            1
+        }
+        else {
+            bits.div_ceil(7) as u8
+        }
+    }
+}
+
+impl TryFrom<&[u8]> for VarInt {
+    type Error = Error;
+    /// Equivalent to [VarInt::from_bytes], without the number of bytes used.
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Ok(VarInt::from_bytes(bytes)?.0)
+    }
+}
+
+impl From<i32> for VarInt {
+    /// Equivalent to [VarInt::new].
+    fn from(value: i32) -> Self {
+        VarInt::new(value)
+    }
+}
+
+impl From<VarInt> for i32 {
+    fn from(value: VarInt) -> Self {
+        value.value
+    }
+}
+
+impl std::ops::Add for VarInt {
+    type Output = VarInt;
+    /// Adds the values of two VarInts. Overflows the same way adding two `i32`s does.
+    fn add(self, other: VarInt) -> VarInt {
+        VarInt::from(self.value + other.value)
+    }
+}
+
+impl std::ops::Sub for VarInt {
+    type Output = VarInt;
+    /// Subtracts the values of two VarInts. Overflows the same way subtracting two `i32`s does.
+    fn sub(self, other: VarInt) -> VarInt {
+        VarInt::from(self.value - other.value)
     }
 }
 
 
+/// Decodes a [VarInt] one byte at a time, for when bytes arrive in pieces,
+/// like from a non-blocking socket. Once a whole VarInt has been pushed it's
+/// returned and the decoder starts over, ready for the next one.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct VarIntDecoder {
+    value: i32,
+    read: u8
+}
+
+impl VarIntDecoder {
+    /// Creates a decoder that hasn't been given any bytes yet.
+    pub fn new() -> VarIntDecoder {
+        VarIntDecoder::default()
+    }
+    /// Adds the next byte of the VarInt. Returns the VarInt if this byte
+    /// finished it, or `None` if more bytes are needed. A fifth byte with
+    /// more than the top four bits of an i32 in it fails with
+    /// [Error::VarIntTooLong], and starts the decoder over.
+    pub fn push(&mut self, byte: u8) -> Result<Option<VarInt>, Error> {
+        self.value |= ((byte & 0b01111111) as i32) << (7 * self.read);
+        self.read += 1;
+        // The 5th byte only holds the top 4 bits of the i32, so only the 4 smallest bits may be
+        // set. This also rules out a continuation bit, so any 6th byte is an error.
+        if self.read == 5 && (byte & 0xf0 != 0) {
+            *self = VarIntDecoder::new();
+            return Err(Error::VarIntTooLong);
+        }
+        if byte & 0b10000000 != 0 {
+            return Ok(None);
+        }
+
+        let varint = VarInt { value: self.value, read_size: Some(self.read) };
+        *self = VarIntDecoder::new();
+        Ok(Some(varint))
+    }
+    /// Checks if this decoder is partway through a VarInt.
+    pub fn in_progress(&self) -> bool {
+        self.read != 0
+    }
+}
+
 /// Represents a Java Long (i64) using between 1-10 bytes.
 #[derive(Eq, Clone, Copy, Debug)]
 pub struct VarLong {
@@ -624,6 +1512,12 @@ impl VarLong {
     /// Creates a VarLong from a series of bytes. Returns the value and the amount of bytes used if
     /// creation is successful.
     pub fn from_bytes(data: &[u8]) -> Result<(VarLong, usize), Error> {
+        // Fast path: most VarLongs are small IDs and lengths that fit in a single byte.
+        if let Some(&first) = data.first() {
+            if first & 0b10000000 == 0 {
+                return Ok((VarLong { value: first as i64, read_size: Some(1) }, 1));
+            }
+        }
         let mut iterator = data.iter();
         let mut result = 0;
 
@@ -638,13 +1532,13 @@ impl VarLong {
 
             result |= ((read & mask) as i64) << (7 * i);
 
-            // The 10th byte is only allowed to have the 4 smallest bits set
-            if i == 9 && (read & 0xf0 != 0) {
+            // The 10th byte only holds the 64th bit, so only the smallest bit may be set
+            if i == 9 && (read & 0xfe != 0) {
                 return Err(Error::VarIntTooLong);
             }
 
             if (read & msb) == 0 {
-                return Ok((VarLong { value: result, read_size: Some(i) }, i as usize));
+                return Ok((VarLong { value: result, read_size: Some(i + 1) }, i as usize + 1));
             }
         }
         // This will never occur.
@@ -658,17 +1552,17 @@ impl VarLong {
         let mask: u8 = !msb;
     
         for i in 0..10 {
-            let read = read_byte(reader)?;
+            let read = read_var_byte(reader, i == 0)?;
     
             result |= ((read & mask) as i64) << (7 * i);
     
-            // The 10th byte is only allowed to have the 4 smallest bits set
-            if i == 9 && (read & 0xf0 != 0) {
+            // The 10th byte only holds the 64th bit, so only the smallest bit may be set
+            if i == 9 && (read & 0xfe != 0) {
                 return Err(Error::VarIntTooLong);
             }
     
             if (read & msb) == 0 {
-                return Ok(VarLong { value: result, read_size: Some(i) });
+                return Ok(VarLong { value: result, read_size: Some(i + 1) });
             }
         }
         // This will never occur.
@@ -680,7 +1574,7 @@ impl VarLong {
         let mask: i64 = 0b01111111;
         let mut val = self.value;
 
-        for _ in 0..5 {
+        for _ in 0..10 {
             let tmp = (val & mask) as u8;
             val &= !mask;
             val = val.rotate_right(7);
@@ -708,32 +1602,117 @@ impl VarLong {
     /// Converts a VarLong to a series of bytes.
     pub fn to_bytes(self) -> Result<Vec<u8>, Error> {
         let mut bytes = vec![];
+        self.encode_into(&mut bytes);
+
+        Ok(bytes)
+    }
+    /// Appends this VarLong to the end of an existing buffer, avoiding the extra allocation of
+    /// [VarLong::to_bytes].
+    pub fn encode_into(&self, buf: &mut Vec<u8>) {
         let msb: u8 = 0b10000000;
         let mask: i64 = 0b01111111;
-        let mut val = self.value;
 
+        // Fast path for the one and two byte values that make up most packets.
+        let unsigned = self.value as u64;
+        if unsigned < 0x80 {
+            buf.push(unsigned as u8);
+            return;
+        }
+        if unsigned < 0x4000 {
+            buf.extend_from_slice(&[(unsigned as u8) | msb, (unsigned >> 7) as u8]);
+            return;
+        }
+
+        let mut val = self.value;
         for _ in 0..10 {
             let tmp = (val & mask) as u8;
             val &= !mask;
             val = val.rotate_right(7);
 
             if val != 0 {
-                bytes.push(tmp | msb);
+                buf.push(tmp | msb);
             } else {
-                bytes.push(tmp);
-                return Ok(bytes);
+                buf.push(tmp);
+                return;
             }
         }
         // This will never occur.
-        unreachable!("VarLong::to_bytes reached end of function, which should not be possible");
+        unreachable!("VarLong::encode_into reached end of function, which should not be possible");
     }
     /// Creates a VarLong from a given value.
-    pub fn from_value(value: i64) -> Result<VarLong, Error> {
-        Ok(VarLong { value, read_size: None })
+    pub const fn new(value: i64) -> VarLong {
+        VarLong { value, read_size: None }
     }
+    /// Creates a VarLong from a given value. This can't fail; [VarLong::new] does the same without
+    /// the `Result`.
+    pub fn from_value(value: i64) -> Result<VarLong, Error> {
+        Ok(VarLong::new(value))
+    }
+    /// Creates a VarLong from a given value using zig-zag encoding, where small negative numbers
+    /// take as few bytes as small positive ones (-1 is encoded as 1, 1 as 2, and so on). Only use
+    /// this for formats that expect it; the Minecraft protocol itself doesn't.
+    pub fn from_value_zigzag(value: i64) -> Result<VarLong, Error> {
+        Self::from_value((value << 1) ^ (value >> 63))
+    }
+    /// Returns the value of a VarLong that was zig-zag encoded, like one made with
+    /// [VarLong::from_value_zigzag].
+    pub fn zigzag_value(self) -> i64 {
+        let unsigned = self.value as u64;
+        ((unsigned >> 1) as i64) ^ -((unsigned & 1) as i64)
+    }
+    /// Gives how many bytes this was read from. Returns `None` if it wasn't
+    /// read from bytes and the size hasn't been calculated.
     pub fn read_size(&self) -> Option<u8> {
         self.read_size
     }
+    /// Gives how many bytes this VarLong takes up when encoded, from 1 to 10, without encoding
+    /// it. Negative values always take 10.
+    pub const fn encoded_len(&self) -> u8 {
+        let bits = 64 - (self.value as u64).leading_zeros();
+        if bits == 0 {
+            1
+        }
+        else {
+            bits.div_ceil(7) as u8
+        }
+    }
+}
+
+impl TryFrom<&[u8]> for VarLong {
+    type Error = Error;
+    /// Equivalent to [VarLong::from_bytes], without the number of bytes used.
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Ok(VarLong::from_bytes(bytes)?.0)
+    }
+}
+
+impl From<i64> for VarLong {
+    /// Equivalent to [VarLong::new].
+    fn from(value: i64) -> Self {
+        VarLong::new(value)
+    }
+}
+
+impl From<VarLong> for i64 {
+    fn from(value: VarLong) -> Self {
+        value.value
+    }
+}
+
+impl std::ops::Add for VarLong {
+    type Output = VarLong;
+    /// Adds the values of two VarLongs. Overflows the same way adding two `i64`s does.
+    fn add(self, other: VarLong) -> VarLong {
+        VarLong::from(self.value + other.value)
+    }
+}
+
+impl std::ops::Sub for VarLong {
+    type Output = VarLong;
+    /// Subtracts the values of two VarLongs. Overflows the same way subtracting two `i64`s does.
+    fn sub(self, other: VarLong) -> VarLong {
+        VarLong::from(self.value - other.value)
+    }
 }
 
 #[derive(Clone, Copy, Eq, PartialEq, Debug)]
@@ -754,6 +1733,18 @@ impl std::fmt::Display for Position {
 }
 
 impl Position {
+    /// How many bits of the x coordinate are sent over the network. Enough for -33,554,432 to
+    /// 33,554,431.
+    pub const X_BITS: u32 = 26;
+    /// How many bits of the z coordinate are sent over the network. Enough for -33,554,432 to
+    /// 33,554,431.
+    pub const Z_BITS: u32 = 26;
+    /// How many bits of the y coordinate are sent over the network. Enough for -2048 to 2047.
+    pub const Y_BITS: u32 = 12;
+    /// Where the x coordinate starts in the packed value. It takes up the highest bits.
+    pub const X_SHIFT: u32 = Self::Z_BITS + Self::Y_BITS;
+    /// Where the z coordinate starts in the packed value. It sits between x and y.
+    pub const Z_SHIFT: u32 = Self::Y_BITS;
     /// Returns the x coordinate of this Position.
     pub fn get_x(self) -> i32 {
         self.x
@@ -779,51 +1770,14 @@ impl Position {
 
         toconvert.copy_from_slice(&indexable_data[..8]);
 
-        // convert to one big u64
-        let u64val = u64::from_be_bytes(toconvert);
-
-        // strip out values with bitmasks
-        let mut x = (u64val >> 38) as i32;
-        let mut y = (u64val & 0xfff) as i16;
-        let mut z = (u64val << 26 >> 38) as i32;
-
-        // convert to negative if appropriate
-        if x >= 2^25 {
-            x -= 2^26;
-        }
-        if y >= 2^11 {
-            y -= 2^12;
-        }
-        if z >= 2^25 {
-            z -= 2^26
-        }
-
-        Ok((Position { x, y, z }, 8))
+        Ok((Position::decode(u64::from_be_bytes(toconvert)), 8))
     }
     /// Creates a Position from a Read type.
     pub fn from_reader<R: std::io::Read>(reader: &mut R) -> Result<Position, Error> {
         let mut toconvert = [0; 8];
         reader.read_exact(&mut toconvert)?;
 
-        let u64val = u64::from_be_bytes(toconvert);
-
-        // strip out values with bitmasks
-        let mut x = (u64val >> 38) as i32;
-        let mut y = (u64val & 0xfff) as i16;
-        let mut z = (u64val << 26 >> 38) as i32;
-
-        // convert to negative if appropriate
-        if x >= 2^25 {
-            x -= 2^26;
-        }
-        if y >= 2^11 {
-            y -= 2^12;
-        }
-        if z >= 2^25 {
-            z -= 2^26
-        }
-
-        Ok(Position { x, y, z })
+        Ok(Position::decode(u64::from_be_bytes(toconvert)))
     }
     /// Creates a Position from coordinate values.
     pub fn from_values(x: i32, y: i16, z: i32) -> Position {
@@ -831,37 +1785,97 @@ impl Position {
             x, y, z
         }
     }
+    /// Creates a Position from world coordinates, giving the block that contains that point.
+    /// Values are floored, so `-0.5` is in the block at `-1`, not `0`.
+    pub fn from_f64_floored(x: f64, y: f64, z: f64) -> Position {
+        Position {
+            x: x.floor() as i32,
+            y: y.floor() as i16,
+            z: z.floor() as i32
+        }
+    }
+    /// Converts a Position into world coordinates. This is the block's lowest corner, the one
+    /// closest to negative infinity on every axis.
+    pub fn to_f64(self) -> (f64, f64, f64) {
+        (self.x as f64, self.y as f64, self.z as f64)
+    }
+    /// Gives the world coordinates of the very center of this Position's block.
+    pub fn center(self) -> (f64, f64, f64) {
+        (self.x as f64 + 0.5, self.y as f64 + 0.5, self.z as f64 + 0.5)
+    }
+    /// Moves this Position by the given number of blocks on each axis. Coordinates wrap around
+    /// on overflow instead of panicking, though positions that far out can't be sent over the
+    /// network anyway.
+    pub fn offset(self, dx: i32, dy: i16, dz: i32) -> Position {
+        Position {
+            x: self.x.wrapping_add(dx),
+            y: self.y.wrapping_add(dy),
+            z: self.z.wrapping_add(dz)
+        }
+    }
+    /// Gives the Position of the block next to this one in the given direction. North is
+    /// towards negative z and west is towards negative x. Wraps like [Position::offset].
+    pub fn neighbor(self, direction: enums::Orientation) -> Position {
+        use enums::Orientation;
+        match direction {
+            Orientation::Down => self.offset(0, -1, 0),
+            Orientation::Up => self.offset(0, 1, 0),
+            Orientation::North => self.offset(0, 0, -1),
+            Orientation::South => self.offset(0, 0, 1),
+            Orientation::West => self.offset(-1, 0, 0),
+            Orientation::East => self.offset(1, 0, 0)
+        }
+    }
+    /// Gives the x coordinate of the chunk this Position is in. Rounds towards negative
+    /// infinity, so x = -1 is in chunk -1, not 0.
+    pub fn chunk_x(self) -> i32 {
+        self.x >> 4
+    }
+    /// Gives the z coordinate of the chunk this Position is in. Rounds towards negative
+    /// infinity, so z = -1 is in chunk -1, not 0.
+    pub fn chunk_z(self) -> i32 {
+        self.z >> 4
+    }
+    /// Gives where this Position is within its chunk: x and z from 0 to 15, and y unchanged,
+    /// since chunks span the whole height of the world.
+    pub fn chunk_relative(self) -> (u8, i16, u8) {
+        ((self.x & 15) as u8, self.y, (self.z & 15) as u8)
+    }
+    /// Gives the square of the straight line distance between two Positions, in blocks. Worked
+    /// out with i64s, so it can't overflow for any Position that can be sent over the network.
+    pub fn distance_squared(self, other: Position) -> i64 {
+        let dx = self.x as i64 - other.x as i64;
+        let dy = self.y as i64 - other.y as i64;
+        let dz = self.z as i64 - other.z as i64;
+
+        dx * dx + dy * dy + dz * dz
+    }
+    /// Gives the Manhattan (taxicab) distance between two Positions: the number of blocks
+    /// between them when only moving along one axis at a time.
+    pub fn manhattan(self, other: Position) -> i64 {
+        (self.x as i64 - other.x as i64).abs()
+            + (self.y as i64 - other.y as i64).abs()
+            + (self.z as i64 - other.z as i64).abs()
+    }
+    /// Checks if two Positions are no more than `radius` blocks apart, in a straight line.
+    pub fn within(self, other: Position, radius: i32) -> bool {
+        self.distance_squared(other) <= radius as i64 * radius as i64
+    }
     /// Converts a Position into a series of bytes.
     pub fn to_bytes(self) -> Result<Vec<u8>, Error> {
-        let xval = if self.x < 0 {
-            (self.x + (2^26)) as u64
-        }
-        else {
-            self.x as u64
-        };
-        let zval = if self.z < 0 {
-            (self.x + (2^26)) as u64
-        }
-        else {
-            self.z as u64
-        };
-        let yval = if self.y < 0 {
-            (self.y + (2^12)) as u64
-        }
-        else {
-            self.y as u64
-        };
-
-        let u64val: u64 = ((xval & 0x3FFFFFF) << 38) | ((zval & 0x3FFFFFF) << 12) | (yval & 0xFFF);
-        let u64bytes = u64val.to_be_bytes();
+        let mut bytes = vec![];
+        self.encode_into(&mut bytes);
 
-        Ok(u64bytes.to_vec())
+        Ok(bytes)
+    }
+    /// Appends this Position to the end of an existing buffer, avoiding the extra allocation of
+    /// [Position::to_bytes].
+    pub fn encode_into(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&self.encode().to_be_bytes());
     }
     /// Writes a Position to a Write type.
     pub fn to_writer<W: std::io::Write>(self, writer: &mut W) -> Result<(), Error> {
-        let u64val: u64 = ((self.x as u64 & 0x3FFFFFF) << 38) | ((self.z as u64 & 0x3FFFFFF) << 12) | (self.y as u64 & 0xFFF);
-        let u64bytes = u64val.to_be_bytes();
-        match writer.write_all(&u64bytes) {
+        match writer.write_all(&self.encode().to_be_bytes()) {
             Ok(_) => {
                 Ok(())
             }
@@ -870,27 +1884,238 @@ impl Position {
             }
         }
     }
+    /// Packs this Position into the 64 bits sent over the network. Negative values are stored
+    /// in two's complement, so masking off the low bits of each coordinate is all that's needed.
+    fn encode(&self) -> u64 {
+        let x = self.x as u64 & ((1 << Self::X_BITS) - 1);
+        let z = self.z as u64 & ((1 << Self::Z_BITS) - 1);
+        let y = self.y as u64 & ((1 << Self::Y_BITS) - 1);
+
+        (x << Self::X_SHIFT) | (z << Self::Z_SHIFT) | y
+    }
+    /// Unpacks a Position from the 64 bits sent over the network. Each coordinate is shifted to
+    /// the top of a signed integer and back down, so the arithmetic shift extends its sign.
+    fn decode(value: u64) -> Position {
+        let value = value as i64;
+        let x = value >> Self::X_SHIFT;
+        let z = (value << (64 - Self::X_SHIFT)) >> (64 - Self::Z_BITS);
+        let y = (value << (64 - Self::Y_BITS)) >> (64 - Self::Y_BITS);
+
+        Position { x: x as i32, y: y as i16, z: z as i32 }
+    }
+}
+
+impl std::ops::Add<(i32, i16, i32)> for Position {
+    type Output = Position;
+    /// Equivalent to [Position::offset].
+    fn add(self, (dx, dy, dz): (i32, i16, i32)) -> Position {
+        self.offset(dx, dy, dz)
+    }
+}
+
+impl TryFrom<&[u8]> for Position {
+    type Error = Error;
+    /// Equivalent to [Position::from_bytes], without the number of bytes used.
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Ok(Position::from_bytes(bytes)?.0)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+/// The exact position of an entity, as opposed to the block [Position]. Sent
+/// over the network as three doubles.
+pub struct EntityPosition {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64
+}
+
+impl EntityPosition {
+    /// Creates an EntityPosition from a Read type.
+    pub fn from_reader<R: std::io::Read>(reader: &mut R) -> Result<EntityPosition, Error> {
+        Ok(EntityPosition {
+            x: generalized::double_from_reader(reader)?,
+            y: generalized::double_from_reader(reader)?,
+            z: generalized::double_from_reader(reader)?
+        })
+    }
+    /// Converts an EntityPosition into a series of bytes.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        let mut bytes = Vec::with_capacity(24);
+        bytes.extend_from_slice(&self.x.to_be_bytes());
+        bytes.extend_from_slice(&self.y.to_be_bytes());
+        bytes.extend_from_slice(&self.z.to_be_bytes());
+
+        Ok(bytes)
+    }
+    /// Returns the block this position is inside of.
+    pub fn block(&self) -> Position {
+        Position::from_values(self.x.floor() as i32, self.y.floor() as i16, self.z.floor() as i32)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+/// Represents a slot in an inventory, or an item anywhere else it may be sent
+/// over the network.
+/// 
+/// Structured data components are not yet supported. Slots that add or remove
+/// components will fail to read with [Error::UnsupportedSlotComponents].
+pub struct Slot {
+    /// How many items are in this slot. Zero means the slot is empty.
+    pub item_count: i32,
+    /// The item in this slot, if any.
+    pub item: Option<enums::Item>
+}
+
+impl Slot {
+    /// Creates a Slot with no item in it.
+    pub fn empty() -> Slot {
+        Slot { item_count: 0, item: None }
+    }
+    /// Creates a Slot from a Read type.
+    pub fn from_reader<R: std::io::Read>(reader: &mut R) -> Result<Slot, Error> {
+        let item_count = VarInt::from_reader(reader)?.value();
+        if item_count <= 0 {
+            return Ok(Slot::empty());
+        }
+        let item_id = VarInt::from_reader(reader)?.value();
+        let item = enums::Item::try_from(item_id as u32)?;
+        let components_to_add = VarInt::from_reader(reader)?.value();
+        let components_to_remove = VarInt::from_reader(reader)?.value();
+        if components_to_add != 0 || components_to_remove != 0 {
+            return Err(Error::UnsupportedSlotComponents);
+        }
+
+        Ok(Slot { item_count, item: Some(item) })
+    }
+    /// Converts a Slot into a series of bytes.
+    pub fn to_bytes(self) -> Result<Vec<u8>, Error> {
+        let mut bytes = vec![];
+        match self.item {
+            Some(item) if self.item_count > 0 => {
+                VarInt::new(self.item_count).encode_into(&mut bytes);
+                VarInt::new(item as i32).encode_into(&mut bytes);
+                // Components to add and remove
+                VarInt::new(0).encode_into(&mut bytes);
+                VarInt::new(0).encode_into(&mut bytes);
+            }
+            _ => {
+                VarInt::new(0).encode_into(&mut bytes);
+            }
+        }
+
+        Ok(bytes)
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+/// A fixed-size collection of [Slot]s, like a chest or a player's inventory.
+/// Sent over the network as a VarInt count followed by each slot.
+pub struct Inventory {
+    slots: Vec<Slot>
+}
+
+impl Inventory {
+    /// Creates an Inventory with the given number of empty slots.
+    pub fn new(size: usize) -> Inventory {
+        Inventory { slots: vec![Slot::empty(); size] }
+    }
+    /// Creates an Inventory from a Read type.
+    pub fn from_reader<R: std::io::Read>(reader: &mut R) -> Result<Inventory, Error> {
+        let count = VarInt::from_reader(reader)?;
+        if count.value() < 0 {
+            return Err(Error::InvalidArrayLength(count));
+        }
+        let mut slots = vec![];
+        for _ in 0..count.value() {
+            slots.push(Slot::from_reader(reader)?);
+        }
+
+        Ok(Inventory { slots })
+    }
+    /// Converts an Inventory into a series of bytes.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        let mut bytes = VarInt::from_value(self.slots.len() as i32)?.to_bytes()?;
+        for slot in &self.slots {
+            bytes.append(&mut slot.to_bytes()?);
+        }
+
+        Ok(bytes)
+    }
+    /// Returns the slot at the given index, or `None` if it's out of bounds.
+    pub fn get(&self, index: usize) -> Option<&Slot> {
+        self.slots.get(index)
+    }
+    /// Replaces the slot at the given index, returning what was there before.
+    /// Returns `None` and changes nothing if the index is out of bounds.
+    pub fn set(&mut self, index: usize, slot: Slot) -> Option<Slot> {
+        let existing = self.slots.get_mut(index)?;
+
+        Some(std::mem::replace(existing, slot))
+    }
+    /// Returns the index of the first empty slot, if there is one.
+    pub fn first_empty(&self) -> Option<usize> {
+        self.slots.iter().position(|slot| slot.item.is_none() || slot.item_count <= 0)
+    }
+    /// Returns how many slots this Inventory has.
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+    /// Returns `true` if this Inventory has no slots at all. An Inventory full
+    /// of empty slots is not empty.
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+    /// Returns all of the slots in order.
+    pub fn slots(&self) -> &[Slot] {
+        &self.slots
+    }
 }
 
 /// `generalized` contains many repetetive and unnecisary functions for reading and writing data.
 /// For sake of completion and inclusiveness, all standard types that may be written over the
 /// stream, no matter how easy to parse, are included here.
+/// 
+/// String length prefixes always count bytes after encoding, which can differ from the number of
+/// characters: every character outside the Basic Multilingual Plane (like most emoji) takes 4
+/// bytes of normal UTF-8, or 6 bytes of Java's "Modified UTF-8." The maximum lengths listed in the
+/// protocol, like 16 for usernames or 32767 for most strings, instead count characters (UTF-16
+/// code units). None of the string functions here enforce a maximum length.
 pub mod generalized {
     use super::Error;
     use super::{read_byte, read_bytes};
     use super::VarInt;
 
+    /// Turns a string's VarInt length prefix into a usable length.
+    fn string_length(length: VarInt) -> Result<usize, Error> {
+        if length.value() < 0 {
+            return Err(Error::InvalidStringLength(length));
+        }
+
+        Ok(length.value() as usize)
+    }
+
+    /// Reads the bytes of a string with the given length prefix. The length comes straight from the
+    /// other side of a connection, so the buffer grows as data actually arrives instead of being
+    /// allocated up front.
+    fn read_string_bytes<R: std::io::Read>(reader: &mut R, length: VarInt) -> Result<Vec<u8>, Error> {
+        let length = string_length(length)?;
+        let mut text = Vec::with_capacity(length.min(4096));
+        let mut limited = std::io::Read::take(&mut *reader, length as u64);
+        match std::io::Read::read_to_end(&mut limited, &mut text) {
+            Ok(read) if read < length => {
+                Err(Error::ReaderError(std::io::ErrorKind::UnexpectedEof.into()))
+            }
+            Ok(_) => Ok(text),
+            Err(e) => Err(Error::ReaderError(e))
+        }
+    }
+
     /// Reads a `String` from a type implimenting `Read`. This function returns the string without the
     /// VarInt length prefix. The text is converted from Java's "Modified UTF-8" into normal UTF-8.
     pub fn string_from_reader<R: std::io::Read>(reader: &mut R) -> Result<String, Error> {
-        let string_len = VarInt::from_reader(reader)?.value();
-        let mut text: Vec<u8> = vec![0; string_len as usize];
-        match reader.read_exact(&mut text) {
-            Ok(_) => {},
-            Err(e) => {
-                return Err(Error::ReaderError(e));
-            }
-        }
+        let string_len = VarInt::from_reader(reader)?;
+        let text = read_string_bytes(reader, string_len)?;
         // This is required because Mojang uses Java's modified UTF-8 which isn't
         // good or compatible with standard UTF-8.
         let string = cesu8::from_java_cesu8(&text)?;
@@ -900,50 +2125,66 @@ pub mod generalized {
     /// Reads a `String` from a type implimenting `Read`. This function returns the string without the
     /// VarInt length prefix. The text is not converted from Java's "Modified UTF-8."
     pub fn string_from_reader_no_cesu8<R: std::io::Read>(reader: &mut R) -> Result<String, Error> {
-        let string_len = VarInt::from_reader(reader)?.value();
-        let mut text: Vec<u8> = vec![0; string_len as usize];
-        match reader.read_exact(&mut text) {
-            Ok(_) => {},
-            Err(e) => {
-                return Err(Error::ReaderError(e));
-            }
-        }
+        let string_len = VarInt::from_reader(reader)?;
+        let text = read_string_bytes(reader, string_len)?;
+
+        Ok(String::from_utf8(text)?)
+    }
+    /// Reads a `String` from a type implimenting `Read`, like [string_from_reader], but also returns
+    /// the number of bytes read. This includes the size of the VarInt length prefix, matching
+    /// [string_from_bytes]. The text is converted from Java's "Modified UTF-8" into normal UTF-8.
+    pub fn string_from_reader_counted<R: std::io::Read>(reader: &mut R) -> Result<(String, usize), Error> {
+        let string_len = VarInt::from_reader(reader)?;
+        // The prefix may have been sent in more bytes than it needed.
+        let prefix_len = string_len.read_size().unwrap_or(string_len.encoded_len()) as usize;
+        let text = read_string_bytes(reader, string_len)?;
+        // This is required because Mojang uses Java's modified UTF-8 which isn't
+        // good or compatible with standard UTF-8.
+        let string = cesu8::from_java_cesu8(&text)?;
 
-        // TODO: proper error!
-        Ok(std::str::from_utf8(&text).expect("Invalid UTF-8!").to_string())
+        Ok((string.to_string(), prefix_len + text.len()))
+    }
+    /// Reads a `String` from a type implimenting `Read`, like [string_from_reader_no_cesu8], but also
+    /// returns the number of bytes read. This includes the size of the VarInt length prefix, matching
+    /// [string_from_bytes_no_cesu8]. The text is not converted from Java's "Modified UTF-8."
+    pub fn string_from_reader_counted_no_cesu8<R: std::io::Read>(
+        reader: &mut R
+    ) -> Result<(String, usize), Error> {
+        let string_len = VarInt::from_reader(reader)?;
+        // The prefix may have been sent in more bytes than it needed.
+        let prefix_len = string_len.read_size().unwrap_or(string_len.encoded_len()) as usize;
+        let text = read_string_bytes(reader, string_len)?;
+        let text_len = text.len();
+
+        Ok((String::from_utf8(text)?, prefix_len + text_len))
+    }
+    /// Reads a `VarInt` from a type implimenting `Read`. Equivalent to [VarInt::from_reader], provided
+    /// so that packet parsers can pull every primitive from this module.
+    pub fn var_int_from_reader<R: std::io::Read>(reader: &mut R) -> Result<VarInt, Error> {
+        VarInt::from_reader(reader)
     }
     /// Reads a `String` from a series of bytes. This function returns the string without the VarInt
     /// length prefix, but does include the size of that VarInt in the final size calculation. The text
     /// is converted from Java's "Modified UTF-8" into normal UTF-8.
     pub fn string_from_bytes(bytes: &[u8]) -> Result<(String, usize), Error> {
-        let string_len = VarInt::from_bytes(bytes)?;
-        let mut text: Vec<u8> = vec![0; string_len.0.value() as usize];
-        let finbytes = bytes.split_at(string_len.1).1;
-        for i in 0..text.len() {
-            text[i] = finbytes[i];
-        }
+        let (string_len, prefix_len) = VarInt::from_bytes(bytes)?;
+        let string_len = string_length(string_len)?;
+        let text = bytes[prefix_len..].get(..string_len).ok_or(Error::MissingData)?;
 
         // This is required because Mojang uses Java's modified UTF-8 which isn't
         // good or compatible with standard UTF-8.
-        let string = cesu8::from_java_cesu8(&text)?;
-        Ok((string.to_string(), string_len.0.value() as usize + string_len.1))
+        let string = cesu8::from_java_cesu8(text)?;
+        Ok((string.to_string(), prefix_len + string_len))
     }
     /// Reads a `String` from a series of bytes. This function returns the string without the VarInt
     /// length prefix, but does include the size of that VarInt in the final size calculation. The text
     /// is not converted to Java's "Modified UTF-8."
     pub fn string_from_bytes_no_cesu8(bytes: &[u8]) -> Result<(String, usize), Error> {
-        let string_len = VarInt::from_bytes(bytes)?;
-        let mut text: Vec<u8> = vec![0; string_len.0.value() as usize];
-        let finbytes = bytes.split_at(string_len.1).1;
-        for i in 0..text.len() {
-            text[i] = finbytes[i];
-        }
-        
-        // TODO: proper error!
-        Ok((
-            std::str::from_utf8(&text).expect("Invalid UTF-8!").to_string(),
-            string_len.0.value() as usize + string_len.1
-        ))
+        let (string_len, prefix_len) = VarInt::from_bytes(bytes)?;
+        let string_len = string_length(string_len)?;
+        let text = bytes[prefix_len..].get(..string_len).ok_or(Error::MissingData)?;
+
+        Ok((std::str::from_utf8(text)?.to_string(), prefix_len + string_len))
     }
     /// Writes a `String` to a Write interface. Converts into Java's modified
     /// UTF-8 format.
@@ -988,22 +2229,54 @@ pub mod generalized {
     /// Converts a `String` to a VarInt length prefixed series of bytes. Converts
     /// from Java's modified UTF-8 to standard UTF-8.
     pub fn string_to_bytes(data: String) -> Result<Vec<u8>, Error> {
-        let as_bytes = cesu8::to_java_cesu8(&data);
-        let len = VarInt::from_value(as_bytes.len() as i32)?;
-        let mut len_as_bytes = len.to_bytes()?;
-        len_as_bytes.append(&mut as_bytes.to_vec());
+        let mut bytes = vec![];
+        string_encode_into(&data, &mut bytes);
 
-        Ok(len_as_bytes)
+        Ok(bytes)
     }
     /// Converts a `String` to a VarInt length prefixed series of bytes. Does not
     /// preform modified UTF-8 conversion, unlike [string_to_bytes].
     pub fn string_to_bytes_no_cesu8(data: String) -> Result<Vec<u8>, Error> {
-        let as_bytes = data.as_bytes();
-        let len = VarInt::from_value(as_bytes.len() as i32)?;
-        let mut len_as_bytes = len.to_bytes()?;
-        len_as_bytes.append(&mut as_bytes.to_vec());
+        let mut bytes = vec![];
+        string_encode_into_no_cesu8(&data, &mut bytes);
 
-        Ok(len_as_bytes)
+        Ok(bytes)
+    }
+    /// Appends a `String` with its VarInt length prefix to the end of an existing buffer. Converts
+    /// from Java's modified UTF-8 to standard UTF-8.
+    pub fn string_encode_into(data: &str, buf: &mut Vec<u8>) {
+        let as_bytes = cesu8::to_java_cesu8(data);
+        VarInt { value: as_bytes.len() as i32, read_size: None }.encode_into(buf);
+        buf.extend_from_slice(&as_bytes);
+    }
+    /// Appends a `String` with its VarInt length prefix to the end of an existing buffer. Does not
+    /// preform modified UTF-8 conversion, unlike [string_encode_into].
+    pub fn string_encode_into_no_cesu8(data: &str, buf: &mut Vec<u8>) {
+        VarInt { value: data.len() as i32, read_size: None }.encode_into(buf);
+        buf.extend_from_slice(data.as_bytes());
+    }
+    /// Runs `f` to write some data into a new buffer, then puts the data's length in front of it
+    /// as a VarInt. This is the "write the body, then prefix its length" pattern used for whole
+    /// packets and for length prefixed structures inside them.
+    pub fn with_length_prefix(f: impl FnOnce(&mut Vec<u8>)) -> Vec<u8> {
+        let mut body = vec![];
+        f(&mut body);
+        let length = VarInt::new(body.len() as i32);
+        let mut result = Vec::with_capacity(body.len() + length.encoded_len() as usize);
+        length.encode_into(&mut result);
+        result.append(&mut body);
+
+        result
+    }
+    /// Reads everything left in a Read type. This reads until the end of the stream, so it's only
+    /// useful on a reader limited to a single packet's body, such as [crate::netty::PacketBody], for the
+    /// byte arrays some packets end with.
+    pub fn read_remaining<R: std::io::Read>(reader: &mut R) -> Result<Vec<u8>, Error> {
+        let mut data = vec![];
+        match reader.read_to_end(&mut data) {
+            Ok(_) => Ok(data),
+            Err(e) => Err(Error::ReaderError(e))
+        }
     }
     pub fn boolean_from_reader<R: std::io::Read>(reader: &mut R) -> Result<bool, Error> {
         let byte = read_byte(reader)?;
@@ -1258,6 +2531,89 @@ pub mod generalized {
     }
 }
 
+/// Extends every [std::io::Read] type with methods for reading this crate's types, so that
+/// `reader.read_varint()?` can be used in place of `VarInt::from_reader(reader)?`.
+pub trait McRead: std::io::Read + Sized {
+    /// Reads a [VarInt]. See [VarInt::from_reader].
+    fn read_varint(&mut self) -> Result<VarInt, Error> {
+        VarInt::from_reader(self)
+    }
+    /// Reads a [VarLong]. See [VarLong::from_reader].
+    fn read_varlong(&mut self) -> Result<VarLong, Error> {
+        VarLong::from_reader(self)
+    }
+    /// Reads a length prefixed string in Java's "Modified UTF-8." See
+    /// [generalized::string_from_reader].
+    fn read_string(&mut self) -> Result<String, Error> {
+        generalized::string_from_reader(self)
+    }
+    /// Reads a [UUID]. See [UUID::from_reader].
+    fn read_uuid(&mut self) -> Result<UUID, Error> {
+        UUID::from_reader(self)
+    }
+    /// Reads a [Position]. See [Position::from_reader].
+    fn read_position(&mut self) -> Result<Position, Error> {
+        Position::from_reader(self)
+    }
+    /// Reads a boolean. See [generalized::boolean_from_reader].
+    fn read_bool(&mut self) -> Result<bool, Error> {
+        generalized::boolean_from_reader(self)
+    }
+}
+
+impl<R: std::io::Read> McRead for R {}
+
+/// Extends every [std::io::Write] type with methods for writing this crate's types, matching
+/// [McRead].
+pub trait McWrite: std::io::Write + Sized {
+    /// Writes a [VarInt]. See [VarInt::to_writer].
+    fn write_varint(&mut self, value: VarInt) -> Result<(), Error> {
+        let mut value = value;
+        value.to_writer(self)
+    }
+    /// Writes a [VarLong]. See [VarLong::to_writer].
+    fn write_varlong(&mut self, value: VarLong) -> Result<(), Error> {
+        let mut value = value;
+        value.to_writer(self)
+    }
+    /// Writes a length prefixed string in Java's "Modified UTF-8." See
+    /// [generalized::string_to_writer].
+    fn write_string(&mut self, value: &str) -> Result<(), Error> {
+        generalized::string_to_writer(self, value.to_string())
+    }
+    /// Writes a [UUID]. See [UUID::to_writer].
+    fn write_uuid(&mut self, value: UUID) -> Result<(), Error> {
+        value.to_writer(self)
+    }
+    /// Writes a [Position]. See [Position::to_writer].
+    fn write_position(&mut self, value: Position) -> Result<(), Error> {
+        value.to_writer(self)
+    }
+    /// Writes a boolean. See [generalized::boolean_to_writer].
+    fn write_bool(&mut self, value: bool) -> Result<(), Error> {
+        generalized::boolean_to_writer(self, value)
+    }
+}
+
+impl<W: std::io::Write> McWrite for W {}
+
+/// Reads one byte of a VarInt or VarLong, reporting the reader running out of
+/// data as [Error::MissingData] for the first byte or [Error::VarIntTruncated]
+/// for any after it.
+fn read_var_byte<R: std::io::Read>(reader: &mut R, first: bool) -> Result<u8, Error> {
+    match read_byte(reader) {
+        Err(Error::ReaderError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
+            if first {
+                Err(Error::MissingData)
+            }
+            else {
+                Err(Error::VarIntTruncated)
+            }
+        }
+        result => result
+    }
+}
+
 fn read_byte<R: std::io::Read>(reader: &mut R) -> Result<u8, Error> {
     let mut read: [u8; 1] = [0x00];
     match reader.read_exact(&mut read) {
@@ -1268,12 +2624,10 @@ fn read_byte<R: std::io::Read>(reader: &mut R) -> Result<u8, Error> {
 
 fn read_bytes<R: std::io::Read, const N: usize>(reader: &mut R) -> Result<[u8; N], Error> {
     let mut buf: [u8; N] = [0; N];
-
-    for i in buf.iter_mut() {
-        *i = read_byte(reader)?;
+    match reader.read_exact(&mut buf) {
+        Ok(_) => Ok(buf),
+        Err(e) => Err(Error::ReaderError(e))
     }
-
-    Ok(buf)
 }
 
 /// Provides tools for reading, writing, and managing NBT types.
@@ -1282,4 +2636,5 @@ pub mod nbt;
 pub mod netty;
 /// Unit testing module.
 #[cfg(test)]
+#[allow(clippy::needless_return, clippy::redundant_static_lifetimes)]
 mod test;
@@ -1,14 +1,139 @@
 use super::{Error, read_byte, read_bytes};
+use std::collections::HashMap;
+
+mod snbt;
+
+/// Limits on what the NBT reader will accept, for reading data that may come
+/// from an untrusted source.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ReadLimits {
+    /// The largest number of elements allowed in any one list or array tag.
+    /// Longer lists or arrays fail to read with [Error::InvalidNbtLength].
+    pub max_length: usize,
+    /// The deepest that lists and compounds may be nested, counting the root
+    /// tag. Deeper data fails to read with [Error::NbtTooDeep] instead of
+    /// overflowing the stack.
+    pub max_depth: usize,
+    /// The most bytes of NBT that will be read in total, counting the header.
+    /// Longer data fails to read with [Error::NbtTooLarge], and no more than
+    /// this many bytes are read from the reader before that happens. Every tag
+    /// read takes up at least one byte, so this also caps how many tags are
+    /// held in memory, although each one takes more memory than it did bytes.
+    pub max_bytes: usize
+}
+
+impl Default for ReadLimits {
+    /// Allows up to 2,097,152 elements per list or array. Every element takes
+    /// up at least one byte, and Minecraft won't accept more than 2MiB of NBT
+    /// in a single packet, so this never rejects data a vanilla client or
+    /// server would send. Nesting is allowed up to 512 levels deep, the same
+    /// limit Minecraft uses.
+    /// 
+    /// The total size isn't limited, since files like chunks can hold more
+    /// NBT than a packet. Set `max_bytes` to 2,097,152 to match what
+    /// Minecraft accepts over the network.
+    fn default() -> ReadLimits {
+        ReadLimits { max_length: 2_097_152, max_depth: 512, max_bytes: usize::MAX }
+    }
+}
+
+/// Wraps a reader so that no more than a set number of bytes can be read
+/// through it. Reading past the end fails, and marks the budget as exceeded
+/// so that the error can be reported as [Error::NbtTooLarge].
+struct Budget<'a, R> {
+    reader: &'a mut R,
+    remaining: usize,
+    exceeded: bool
+}
+
+impl<R: std::io::Read> std::io::Read for Budget<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.remaining == 0 && !buf.is_empty() {
+            self.exceeded = true;
+            return Err(std::io::Error::other("NBT is larger than the read limit"));
+        }
+        let allowed = buf.len().min(self.remaining);
+        let read = self.reader.read(&mut buf[..allowed])?;
+        self.remaining -= read;
+
+        Ok(read)
+    }
+}
 
 /// Reads an entire NBT compound from a Read type.
 pub fn from_reader<R: std::io::Read>(reader: &mut R) -> Result<NamedTag, Error> {
+    from_reader_with_limits(reader, &ReadLimits::default())
+}
+
+/// Reads an entire gzip compressed NBT compound from a Read type, like the
+/// `level.dat` and player data files Minecraft saves.
+pub fn from_reader_gzip<R: std::io::Read>(reader: &mut R) -> Result<NamedTag, Error> {
+    from_reader(&mut flate2::read::GzDecoder::new(reader))
+}
+
+/// Reads an entire zlib compressed NBT compound from a Read type, like the
+/// chunks stored in region files.
+pub fn from_reader_zlib<R: std::io::Read>(reader: &mut R) -> Result<NamedTag, Error> {
+    from_reader(&mut flate2::read::ZlibDecoder::new(reader))
+}
+
+/// Reads an entire NBT compound from a Read type, working out from the first
+/// byte whether it's gzip compressed (0x1f), zlib compressed (0x78), or not
+/// compressed at all.
+pub fn from_reader_auto<R: std::io::Read>(reader: &mut R) -> Result<NamedTag, Error> {
+    use std::io::Read;
+    let first = [read_byte(reader)?];
+    let mut whole = first.chain(reader);
+    match first[0] {
+        0x1f => from_reader_gzip(&mut whole),
+        0x78 => from_reader_zlib(&mut whole),
+        _ => from_reader(&mut whole)
+    }
+}
+
+/// Reads an entire NBT compound from a byte slice. Returns the compound and how
+/// many bytes it took up, from the 0x0a header through the final End tag.
+pub fn from_bytes(data: &[u8]) -> Result<(NamedTag, usize), Error> {
+    let mut cursor = data;
+    let root = from_reader(&mut cursor)?;
+
+    Ok((root, data.len() - cursor.len()))
+}
+
+/// Reads an entire NBT compound from a Read type, rejecting lists and
+/// compounds nested more than `max_depth` levels deep. Use this with a lower
+/// limit than the default of 512 for untrusted data read on a small stack.
+pub fn from_reader_limited<R: std::io::Read>(
+    reader: &mut R, max_depth: usize
+) -> Result<NamedTag, Error> {
+    from_reader_with_limits(reader, &ReadLimits { max_depth, ..ReadLimits::default() })
+}
+
+/// Reads an entire NBT compound from a Read type, rejecting anything that
+/// goes over the given limits.
+pub fn from_reader_with_limits<R: std::io::Read>(
+    reader: &mut R, limits: &ReadLimits
+) -> Result<NamedTag, Error> {
+    let mut budget = Budget { reader, remaining: limits.max_bytes, exceeded: false };
+    let root = read_root(&mut budget, limits);
+    if budget.exceeded {
+        return Err(Error::NbtTooLarge);
+    }
+
+    root
+}
+
+fn read_root<R: std::io::Read>(reader: &mut R, limits: &ReadLimits) -> Result<NamedTag, Error> {
     if read_byte(reader)? != 0x0a {
         return Err(Error::InvalidNbtHeader);
     }
+    if limits.max_depth == 0 {
+        return Err(Error::NbtTooDeep);
+    }
     let root_name = read_string_tag(reader)?;
     let mut elements = vec![];
     loop {
-        let next_tag = read_named_tag(reader)?;
+        let next_tag = read_named_tag_limited(reader, limits, 1)?;
         match next_tag.tag {
             Tag::End => {
                 break;
@@ -23,41 +148,78 @@ pub fn from_reader<R: std::io::Read>(reader: &mut R) -> Result<NamedTag, Error>
 }
 
 /// Converts an entire NBT compound into an array of bytes. This must be a full NBT compound.
-pub fn to_bytes(root_tag: NamedTag) -> Result<Vec<u8>, Error> {
+pub fn to_bytes(root_tag: &NamedTag) -> Result<Vec<u8>, Error> {
     let mut final_bytes = vec![];
+    to_writer(root_tag, &mut final_bytes)?;
+
+    Ok(final_bytes)
+}
+
+/// Writes an entire NBT compound to a Write type as it goes, without building
+/// the whole thing in memory first. This must be a full NBT compound.
+pub fn to_writer<W: std::io::Write>(root_tag: &NamedTag, writer: &mut W) -> Result<(), Error> {
+    let Tag::Compound(_) = root_tag.tag else {
+        return Err(Error::InvalidRootTag);
+    };
     // Add start tag
-    final_bytes.push(0x0a);
+    write_all(writer, &[0x0a])?;
     // Add root tag name
-    for byte in root_tag.name.as_bytes() {
-        final_bytes.push(*byte);
-    }
-    // Add root tag components
-    if let Tag::Compound(cmptag) = root_tag.tag {
-        for tag in cmptag {
-            let prefix = tag.tag.clone().tag_prefix();
-            final_bytes.push(prefix);
-            if prefix == 0 {
-                break;
-            }
-            let name = tag.name.as_bytes();
-            for byte in &(name.len() as u16).to_be_bytes() {
-                final_bytes.push(*byte);
-            }
-            for byte in name {
-                final_bytes.push(*byte);
-            }
-            for byte in tag.tag.write_to_bytes()? {
-                final_bytes.push(byte);
-            }
-        }
-    }
-    else {
-        return Err(Error::InvalidRootTag);
+    write_string_tag(writer, &root_tag.name)?;
+    // Add root tag components, then the end tag
+    root_tag.tag.write_to(writer)
+}
+
+/// Converts an entire NBT compound into gzip compressed bytes, the format
+/// Minecraft saves `.dat` files in.
+pub fn to_bytes_gzip(root_tag: &NamedTag) -> Result<Vec<u8>, Error> {
+    let mut encoder = flate2::write::GzEncoder::new(vec![], flate2::Compression::default());
+    to_writer(root_tag, &mut encoder)?;
+
+    Ok(encoder.finish()?)
+}
+
+/// Writes a tag to a Write type in the nameless format used over the network
+/// since 1.20.2, where the root tag has a type ID but no name. Takes the tag by
+/// reference and writes it directly, so it can be embedded in the middle of a
+/// packet without cloning or allocating.
+pub fn to_network_writer<W: std::io::Write>(writer: &mut W, tag: &Tag) -> Result<(), Error> {
+    write_all(writer, &[tag.tag_prefix()])?;
+    tag.write_to(writer)
+}
+
+/// Reads NBT in the nameless format used over the network since 1.20.2, where
+/// the root tag has a type ID but no name. The returned tag's name is empty.
+/// Use [from_reader] for NBT from files, which still name the root tag.
+pub fn from_reader_network<R: std::io::Read>(reader: &mut R) -> Result<NamedTag, Error> {
+    let tag_type = read_byte(reader)?;
+    let tag = read_tag_by_type(reader, tag_type)?;
+
+    Ok(NamedTag { name: String::new(), tag })
+}
+
+/// Converts a tag into bytes in the nameless format used over the network
+/// since 1.20.2. The root tag's name is left out. Use [to_bytes] for NBT
+/// written to files.
+pub fn to_bytes_network(root_tag: &NamedTag) -> Result<Vec<u8>, Error> {
+    let mut bytes = vec![];
+    to_network_writer(&mut bytes, &root_tag.tag)?;
+
+    Ok(bytes)
+}
+
+fn write_all<W: std::io::Write>(writer: &mut W, bytes: &[u8]) -> Result<(), Error> {
+    match writer.write_all(bytes) {
+        Ok(_) => Ok(()),
+        Err(e) => Err(Error::WriterError(e))
     }
-    // Add end tag
-    final_bytes.push(0x00);
+}
 
-    Ok(final_bytes)
+fn write_string_tag<W: std::io::Write>(writer: &mut W, string: &str) -> Result<(), Error> {
+    // This is required because Mojang uses Java's modified UTF-8 which isn't
+    // good or compatible with standard UTF-8.
+    let bytes = cesu8::to_java_cesu8(string);
+    write_all(writer, &(bytes.len() as u16).to_be_bytes())?;
+    write_all(writer, &bytes)
 }
 
 fn read_string_tag<R: std::io::Read>(reader: &mut R) -> Result<String, Error> {
@@ -74,6 +236,13 @@ fn read_string_tag<R: std::io::Read>(reader: &mut R) -> Result<String, Error> {
 }
 
 pub fn read_named_tag<R: std::io::Read>(reader: &mut R) -> Result<NamedTag, Error> {
+    read_named_tag_limited(reader, &ReadLimits::default(), 0)
+}
+
+/// `depth` is how many lists and compounds the tag is nested inside.
+fn read_named_tag_limited<R: std::io::Read>(
+    reader: &mut R, limits: &ReadLimits, depth: usize
+) -> Result<NamedTag, Error> {
     let tag_type = read_byte(reader)?;
     let tag_name = if tag_type == 0x00 {
         String::from("N/A")
@@ -81,11 +250,35 @@ pub fn read_named_tag<R: std::io::Read>(reader: &mut R) -> Result<NamedTag, Erro
         read_string_tag(reader)?
     };
     
-    let tag_val = read_tag_by_type(reader, tag_type)?;
+    let tag_val = read_tag_by_type_limited(reader, tag_type, limits, depth)?;
 
     Ok(NamedTag { name: tag_name, tag: tag_val })
 }
 
+/// Reads the length of a list or array tag, making sure it's within limits.
+fn read_length<R: std::io::Read>(reader: &mut R, limits: &ReadLimits) -> Result<usize, Error> {
+    let length = i32::from_be_bytes(read_bytes(reader)?);
+    if length < 0 || length as usize > limits.max_length {
+        return Err(Error::InvalidNbtLength(length));
+    }
+
+    Ok(length as usize)
+}
+
+/// Reads the contents of an array tag in one go. The buffer grows as data
+/// arrives instead of being allocated up front, and a stream that ends before
+/// `len` bytes fails with [Error::MissingData].
+fn read_array_bytes<R: std::io::Read>(reader: &mut R, len: usize) -> Result<Vec<u8>, Error> {
+    use std::io::Read;
+    let mut bytes = vec![];
+    reader.take(len as u64).read_to_end(&mut bytes)?;
+    if bytes.len() != len {
+        return Err(Error::MissingData);
+    }
+
+    Ok(bytes)
+}
+
 /// Reads a Tag from a [std::io::Read] type R, given that the first byte in the
 /// reader indicates the tag's type.
 pub fn read_tag_with_type<R: std::io::Read>(reader: &mut R) -> Result<Tag, Error> {
@@ -98,33 +291,31 @@ pub fn read_tag_with_type<R: std::io::Read>(reader: &mut R) -> Result<Tag, Error
 /// Functionally similar to [read_tag_with_type], but the tag type must be
 /// specified instead of read from the reader.
 pub fn read_tag_by_type<R: std::io::Read>(reader: &mut R, type_id: u8) -> Result<Tag, Error> {
-    match type_id {
-        0x00 => Ok(Tag::End),
-        0x01 => Ok(Tag::Byte(i8::from_be_bytes([read_byte(reader)?]))),
-        0x02 => Ok(Tag::Short(i16::from_be_bytes(read_bytes(reader)?))),
-        0x03 => Ok(Tag::Int(i32::from_be_bytes(read_bytes(reader)?))),
-        0x04 => Ok(Tag::Long(i64::from_be_bytes(read_bytes(reader)?))),
-        0x05 => Ok(Tag::Float(f32::from_be_bytes(read_bytes(reader)?))),
-        0x06 => Ok(Tag::Double(f64::from_be_bytes(read_bytes(reader)?))),
-        0x07 => {
-            let array_len = i32::from_be_bytes(read_bytes(reader)?);
-            let mut array = vec![];
-            for _ in 0..array_len {
-                array.push(i8::from_be_bytes([read_byte(reader)?]));
-            }
+    read_tag_by_type_limited(reader, type_id, &ReadLimits::default(), 0)
+}
 
-            Ok(Tag::ByteArray(array))
-        }
-        0x08 => Ok(Tag::String(read_string_tag(reader)?)),
+// Nothing is allocated up front from a declared length, so a length that's
+// within limits but larger than the actual data only costs as much memory as
+// the data that was really there. Lists of End tags are rejected unless
+// they're empty, since their elements take up no bytes at all. `depth` is how
+// many lists and compounds the tag is nested inside.
+fn read_tag_by_type_limited<R: std::io::Read>(
+    reader: &mut R, type_id: u8, limits: &ReadLimits, depth: usize
+) -> Result<Tag, Error> {
+    match type_id {
+        0x09 | 0x0A if depth >= limits.max_depth => Err(Error::NbtTooDeep),
         0x09 => {
             let list_type = read_byte(reader)?;
-            let list_len = i32::from_be_bytes(read_bytes(reader)?);
-            if list_len < 1 {
-                return Ok(Tag::List(vec![Tag::End]));
+            let list_len = read_length(reader, limits)?;
+            if list_len == 0 {
+                return Ok(Tag::EmptyList(list_type));
+            }
+            if list_type == 0x00 {
+                return Err(Error::InvalidNbtType);
             }
             let mut list_elements = vec![];
             for _ in 0..list_len {
-                list_elements.push(read_tag_by_type(reader, list_type)?);
+                list_elements.push(read_tag_by_type_limited(reader, list_type, limits, depth + 1)?);
             }
 
             Ok(Tag::List(list_elements))
@@ -132,7 +323,7 @@ pub fn read_tag_by_type<R: std::io::Read>(reader: &mut R, type_id: u8) -> Result
         0x0A => {
             let mut compound_elements = vec![];
             loop {
-                let tag = read_named_tag(reader)?;
+                let tag = read_named_tag_limited(reader, limits, depth + 1)?;
                 if tag.tag == Tag::End {
                     break;
                 }
@@ -143,21 +334,47 @@ pub fn read_tag_by_type<R: std::io::Read>(reader: &mut R, type_id: u8) -> Result
 
             Ok(Tag::Compound(compound_elements))
         }
+        _ => read_value_tag(reader, type_id, limits)
+    }
+}
+
+// Reads every tag type that can't hold other tags. Kept out of
+// read_tag_by_type_limited so the recursive path through lists and compounds
+// uses as little stack per level as possible.
+#[inline(never)]
+fn read_value_tag<R: std::io::Read>(
+    reader: &mut R, type_id: u8, limits: &ReadLimits
+) -> Result<Tag, Error> {
+    match type_id {
+        0x00 => Ok(Tag::End),
+        0x01 => Ok(Tag::Byte(i8::from_be_bytes([read_byte(reader)?]))),
+        0x02 => Ok(Tag::Short(i16::from_be_bytes(read_bytes(reader)?))),
+        0x03 => Ok(Tag::Int(i32::from_be_bytes(read_bytes(reader)?))),
+        0x04 => Ok(Tag::Long(i64::from_be_bytes(read_bytes(reader)?))),
+        0x05 => Ok(Tag::Float(f32::from_be_bytes(read_bytes(reader)?))),
+        0x06 => Ok(Tag::Double(f64::from_be_bytes(read_bytes(reader)?))),
+        0x07 => {
+            let array_len = read_length(reader, limits)?;
+            let bytes = read_array_bytes(reader, array_len)?;
+
+            Ok(Tag::ByteArray(bytes.into_iter().map(|byte| byte as i8).collect()))
+        }
+        0x08 => Ok(Tag::String(read_string_tag(reader)?)),
         0x0B => {
-            let array_len = i32::from_be_bytes(read_bytes(reader)?);
-            let mut array = vec![];
-            for _ in 0..array_len {
-                array.push(i32::from_be_bytes(read_bytes(reader)?));
-            }
+            let array_len = read_length(reader, limits)?;
+            let bytes = read_array_bytes(reader, array_len * 4)?;
+            let array = bytes.chunks_exact(4)
+                .map(|chunk| i32::from_be_bytes(chunk.try_into().unwrap()))
+                .collect();
 
             Ok(Tag::IntArray(array))
         }
         0x0C => {
-            let array_len = i32::from_be_bytes(read_bytes(reader)?);
-            let mut array = vec![];
-            for _ in 0..array_len {
-                array.push(i64::from_be_bytes(read_bytes(reader)?));
-            }
+            let array_len = read_length(reader, limits)?;
+            let bytes = read_array_bytes(reader, array_len * 8)?;
+            let array = bytes.chunks_exact(8)
+                .map(|chunk| i64::from_be_bytes(chunk.try_into().unwrap()))
+                .collect();
 
             Ok(Tag::LongArray(array))
         }
@@ -166,7 +383,13 @@ pub fn read_tag_by_type<R: std::io::Read>(reader: &mut R, type_id: u8) -> Result
 }
 
 #[derive(PartialEq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 /// Represents a value in a NBT structure.
+/// 
+/// With the `serde` feature, tags are serialized with their type as the key,
+/// like `{"Int": 3}` or `{"IntArray": [1, 2]}`, so that they can be turned
+/// back into the exact same NBT. Compounds become maps from their children's
+/// names to their tags, in order.
 pub enum Tag {
     /// A signed byte.
     Byte(i8),
@@ -183,11 +406,17 @@ pub enum Tag {
     /// An array of signed bytes.
     ByteArray(Vec<i8>),
     /// A Java modified UTF-8 string. Stored as a valid "normal" UTF-8 string,
-    /// converted when reading or writing to a buffer.
+    /// converted when reading or writing to a buffer. Strings that aren't
+    /// valid modified UTF-8 fail to read with [Error::InvalidJavaUtf8].
     String(String),
     /// A list type containing a list of tags without names. All tags will be of the same type.
     List(Vec<Tag>),
+    /// A list type with no elements, holding the type ID its elements would
+    /// have had. Reading an empty list always gives this rather than an empty
+    /// [Tag::List], so the declared type survives being written back out.
+    EmptyList(u8),
     /// A compound type containing a list of named tags.
+    #[cfg_attr(feature = "serde", serde(with = "compound_map"))]
     Compound(Vec<NamedTag>),
     /// An array of Java Ints.
     IntArray(Vec<i32>),
@@ -198,7 +427,7 @@ pub enum Tag {
 }
 
 impl Tag {
-    fn tag_prefix(self) -> u8 {
+    fn tag_prefix(&self) -> u8 {
         match self {
             Self::End => 0,
             Self::Byte(_) => 1,
@@ -210,14 +439,326 @@ impl Tag {
             Self::ByteArray(_) => 7,
             Self::String(_) => 8,
             Self::List(_) => 9,
+            Self::EmptyList(_) => 9,
             Self::Compound(_) => 10,
             Self::IntArray(_) => 11,
             Self::LongArray(_) => 12
         }
     }
+    /// Gives the number of elements in a list, compound, array, or string tag.
+    /// Strings are measured in bytes of normal UTF-8. Returns `None` for tags
+    /// that don't hold multiple elements, like numbers.
+    pub fn len(&self) -> Option<usize> {
+        match self {
+            Self::List(data) => Some(data.len()),
+            Self::EmptyList(_) => Some(0),
+            Self::Compound(data) => Some(data.len()),
+            Self::ByteArray(data) => Some(data.len()),
+            Self::IntArray(data) => Some(data.len()),
+            Self::LongArray(data) => Some(data.len()),
+            Self::String(data) => Some(data.len()),
+            _ => None
+        }
+    }
+    /// Checks if a list, compound, array, or string tag has no elements.
+    /// Always returns `false` for tags that don't hold multiple elements, like
+    /// numbers.
+    pub fn is_empty(&self) -> bool {
+        self.len() == Some(0)
+    }
+    /// Iterates over the elements of a list tag. Yields nothing if this isn't a
+    /// list tag.
+    pub fn iter_list(&self) -> impl Iterator<Item = &Tag> {
+        match self {
+            Self::List(elements) => elements.iter(),
+            _ => [].iter()
+        }
+    }
+    /// Iterates over the children of a compound tag. Yields nothing if this
+    /// isn't a compound tag.
+    pub fn iter_compound(&self) -> impl Iterator<Item = &NamedTag> {
+        match self {
+            Self::Compound(children) => children.iter(),
+            _ => [].iter()
+        }
+    }
+    /// Iterates over the children of a compound tag. Returns `None` if this
+    /// isn't a compound tag, unlike [Tag::iter_compound], which yields
+    /// nothing.
+    pub fn entries(&self) -> Option<std::slice::Iter<'_, NamedTag>> {
+        match self {
+            Self::Compound(children) => Some(children.iter()),
+            _ => None
+        }
+    }
+    /// Iterates over the children of each compound in a list of compounds, like the entities in a
+    /// chunk or the items in an inventory. Returns `None` if this isn't a list tag or if any of its
+    /// elements aren't compound tags. An empty list yields nothing.
+    pub fn compound_list(&self) -> Option<impl Iterator<Item = &[NamedTag]>> {
+        let elements: &[Tag] = match self {
+            Self::List(elements) => elements,
+            Self::EmptyList(_) => &[],
+            _ => {
+                return None;
+            }
+        };
+        if !elements.iter().all(|element| matches!(element, Self::Compound(_))) {
+            return None;
+        }
+
+        Some(elements.iter().map(|element| match element {
+            Self::Compound(children) => children.as_slice(),
+            // Every element was checked to be a compound above.
+            _ => unreachable!()
+        }))
+    }
+    /// Gives a borrowed map view of the children of a compound tag, for code
+    /// that does many lookups by name. Returns `None` if this isn't a compound
+    /// tag. The tag itself keeps its children in order, so writing it back out
+    /// is unaffected.
+    /// 
+    /// Vanilla never writes two children with the same name, but malformed
+    /// data might. If it does, the map holds the last one.
+    pub fn as_map(&self) -> Option<HashMap<&str, &Tag>> {
+        let Self::Compound(children) = self else {
+            return None;
+        };
+
+        Some(children.iter().map(|child| (child.name.as_str(), &child.tag)).collect())
+    }
+    /// Removes and returns the first child of a compound tag with the given
+    /// name. Returns `None` if there is no such child or if this isn't a
+    /// compound tag.
+    pub fn remove(&mut self, name: &str) -> Option<NamedTag> {
+        let Self::Compound(children) = self else {
+            return None;
+        };
+        let index = children.iter().position(|child| child.name == name)?;
+
+        Some(children.remove(index))
+    }
+    /// Keeps only the children of a compound tag for which `f` returns `true`,
+    /// in their original order. Does nothing if this isn't a compound tag.
+    pub fn retain(&mut self, f: impl FnMut(&NamedTag) -> bool) {
+        if let Self::Compound(children) = self {
+            children.retain(f);
+        }
+    }
+    /// Gets the first child of a compound tag with the given name. Returns
+    /// `None` if there is no such child or if this isn't a compound tag.
+    pub fn get(&self, name: &str) -> Option<&Tag> {
+        self.iter_compound().find(|child| child.name == name).map(|child| &child.tag)
+    }
+    /// Follows a `/` separated path down through compounds, by child name, and
+    /// lists, by index, like `"Inventory/0/id"`. Returns `None` if any step
+    /// is missing, out of range, or lands on a tag that can't be descended
+    /// into. An empty path gives this tag back.
+    pub fn path(&self, path: &str) -> Option<&Tag> {
+        if path.is_empty() {
+            return Some(self);
+        }
+        path.split('/').try_fold(self, |tag, step| match tag {
+            Self::Compound(_) => tag.get(step),
+            Self::List(elements) => elements.get(step.parse::<usize>().ok()?),
+            _ => None
+        })
+    }
+    /// Gets the value of a byte tag, or `None` if this is any other tag.
+    pub fn as_i8(&self) -> Option<i8> {
+        match self {
+            Self::Byte(data) => Some(*data),
+            _ => None
+        }
+    }
+    /// Gets the value of a short tag, or `None` if this is any other tag.
+    pub fn as_i16(&self) -> Option<i16> {
+        match self {
+            Self::Short(data) => Some(*data),
+            _ => None
+        }
+    }
+    /// Gets the value of an int tag, or `None` if this is any other tag.
+    pub fn as_i32(&self) -> Option<i32> {
+        match self {
+            Self::Int(data) => Some(*data),
+            _ => None
+        }
+    }
+    /// Gets the value of a long tag, or `None` if this is any other tag.
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Self::Long(data) => Some(*data),
+            _ => None
+        }
+    }
+    /// Gets the value of a float tag, or `None` if this is any other tag.
+    pub fn as_f32(&self) -> Option<f32> {
+        match self {
+            Self::Float(data) => Some(*data),
+            _ => None
+        }
+    }
+    /// Gets the value of a double tag, or `None` if this is any other tag.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Self::Double(data) => Some(*data),
+            _ => None
+        }
+    }
+    /// Gets the value of a string tag, or `None` if this is any other tag.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(data) => Some(data),
+            _ => None
+        }
+    }
+    /// Gets the elements of a byte array tag, or `None` if this is any other
+    /// tag.
+    pub fn as_byte_array(&self) -> Option<&[i8]> {
+        match self {
+            Self::ByteArray(data) => Some(data),
+            _ => None
+        }
+    }
+    /// Gets the elements of an int array tag, or `None` if this is any other
+    /// tag.
+    pub fn as_int_array(&self) -> Option<&[i32]> {
+        match self {
+            Self::IntArray(data) => Some(data),
+            _ => None
+        }
+    }
+    /// Gets the elements of a long array tag, or `None` if this is any other
+    /// tag.
+    pub fn as_long_array(&self) -> Option<&[i64]> {
+        match self {
+            Self::LongArray(data) => Some(data),
+            _ => None
+        }
+    }
+    /// Gets the elements of a list tag, or `None` if this is any other tag.
+    /// Empty lists give an empty slice no matter their element type.
+    pub fn as_list(&self) -> Option<&[Tag]> {
+        match self {
+            Self::List(data) => Some(data),
+            Self::EmptyList(_) => Some(&[]),
+            _ => None
+        }
+    }
+    /// Gets the children of a compound tag, or `None` if this is any other
+    /// tag.
+    pub fn as_compound(&self) -> Option<&[NamedTag]> {
+        match self {
+            Self::Compound(data) => Some(data),
+            _ => None
+        }
+    }
+    /// Compares two tags like `==`, except that float and double tags only
+    /// need to be within `epsilon` of each other, and NaN is equal to NaN.
+    /// Lists and compounds are compared element by element, in order.
+    pub fn approx_eq(&self, other: &Tag, epsilon: f64) -> bool {
+        fn close(a: f64, b: f64, epsilon: f64) -> bool {
+            a == b || (a.is_nan() && b.is_nan()) || (a - b).abs() <= epsilon
+        }
+
+        match (self, other) {
+            (Self::Float(a), Self::Float(b)) => close(*a as f64, *b as f64, epsilon),
+            (Self::Double(a), Self::Double(b)) => close(*a, *b, epsilon),
+            (Self::List(a), Self::List(b)) => {
+                a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.approx_eq(b, epsilon))
+            }
+            (Self::Compound(a), Self::Compound(b)) => {
+                a.len() == b.len() && a.iter().zip(b).all(|(a, b)| {
+                    a.name == b.name && a.tag.approx_eq(&b.tag, epsilon)
+                })
+            }
+            _ => self == other
+        }
+    }
+    /// Compares two tags like `==`, except that float and double tags are
+    /// compared by their raw bits. Unlike `==`, NaN is equal to an identical
+    /// NaN, but `0.0` and `-0.0` are different. Useful for checking that a
+    /// tag survives being written and read back exactly.
+    pub fn bit_eq(&self, other: &Tag) -> bool {
+        match (self, other) {
+            (Self::Float(a), Self::Float(b)) => a.to_bits() == b.to_bits(),
+            (Self::Double(a), Self::Double(b)) => a.to_bits() == b.to_bits(),
+            (Self::List(a), Self::List(b)) => {
+                a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.bit_eq(b))
+            }
+            (Self::Compound(a), Self::Compound(b)) => {
+                a.len() == b.len() && a.iter().zip(b).all(|(a, b)| {
+                    a.name == b.name && a.tag.bit_eq(&b.tag)
+                })
+            }
+            _ => self == other
+        }
+    }
+    /// Writes this tag directly to a Write type, without cloning it or building an intermediate
+    /// buffer. Like [Tag::write_to_bytes], this does not include the tag's type ID prefix.
+    pub fn write_to<W: std::io::Write>(&self, writer: &mut W) -> Result<(), Error> {
+        match self {
+            Self::End => Ok(()),
+            Self::Byte(data) => write_all(writer, &data.to_be_bytes()),
+            Self::Short(data) => write_all(writer, &data.to_be_bytes()),
+            Self::Int(data) => write_all(writer, &data.to_be_bytes()),
+            Self::Long(data) => write_all(writer, &data.to_be_bytes()),
+            Self::Float(data) => write_all(writer, &data.to_be_bytes()),
+            Self::Double(data) => write_all(writer, &data.to_be_bytes()),
+            Self::ByteArray(data) => {
+                write_all(writer, &(data.len() as i32).to_be_bytes())?;
+                for byte in data {
+                    write_all(writer, &byte.to_be_bytes())?;
+                }
+
+                Ok(())
+            }
+            Self::IntArray(data) => {
+                write_all(writer, &(data.len() as i32).to_be_bytes())?;
+                for int in data {
+                    write_all(writer, &int.to_be_bytes())?;
+                }
+
+                Ok(())
+            }
+            Self::LongArray(data) => {
+                write_all(writer, &(data.len() as i32).to_be_bytes())?;
+                for long in data {
+                    write_all(writer, &long.to_be_bytes())?;
+                }
+
+                Ok(())
+            }
+            Self::String(data) => write_string_tag(writer, data),
+            Self::List(data) => {
+                // Empty lists are written with the End type, same as Minecraft does.
+                let list_type = data.first().map(Tag::tag_prefix).unwrap_or(0);
+                write_all(writer, &[list_type])?;
+                write_all(writer, &(data.len() as i32).to_be_bytes())?;
+                for element in data {
+                    element.write_to(writer)?;
+                }
+
+                Ok(())
+            }
+            Self::EmptyList(list_type) => {
+                write_all(writer, &[*list_type])?;
+                write_all(writer, &0i32.to_be_bytes())
+            }
+            Self::Compound(data) => {
+                for named_tag in data {
+                    write_all(writer, &[named_tag.tag.tag_prefix()])?;
+                    write_string_tag(writer, &named_tag.name)?;
+                    named_tag.tag.write_to(writer)?;
+                }
+
+                write_all(writer, &[0x00])
+            }
+        }
+    }
     /// Writes this tag to a series of bytes. Does not include the tag's type ID prefix. Does
-    /// include list and compound tag's ending byte.
-    pub fn write_to_bytes(self) -> Result<Vec<u8>, Error> {
+    /// include compound tag's ending byte.
+    pub fn write_to_bytes(&self) -> Result<Vec<u8>, Error> {
         match self {
             // The end tag has no data.
             Self::End => Ok(vec![]),
@@ -273,7 +814,7 @@ impl Tag {
                 let mut final_data = vec![];
                 // This is required because Mojang uses Java's modified UTF-8 which isn't
                 // good or compatible with standard UTF-8.
-                let strbytes = cesu8::to_java_cesu8(&data);
+                let strbytes = cesu8::to_java_cesu8(data);
                 for byte in &(strbytes.len() as u16).to_be_bytes() {
                     final_data.push(*byte);
                 }
@@ -285,30 +826,31 @@ impl Tag {
             },
             Self::List(data) => {
                 let mut final_data = vec![];
-                final_data.push(data[0].clone().tag_prefix());
+                // Empty lists are written with the End type, same as Minecraft does.
+                final_data.push(data.first().map(Tag::tag_prefix).unwrap_or(0));
                 for byte in &(data.len() as i32).to_be_bytes() {
                     final_data.push(*byte);
                 }
+                // Unlike compounds, lists have no End tag after their elements.
                 for element in data {
                     for byte in element.write_to_bytes()? {
                         final_data.push(byte);
                     }
                 }
-                final_data.push(0x00);
+
+                Ok(final_data)
+            },
+            Self::EmptyList(list_type) => {
+                let mut final_data = vec![*list_type];
+                final_data.extend_from_slice(&0i32.to_be_bytes());
 
                 Ok(final_data)
             },
             Self::Compound(data) => {
                 let mut final_data = vec![];
                 for named_tag in data {
-                    final_data.push(named_tag.tag.clone().tag_prefix());
-                    let name_bytes = named_tag.name.as_bytes();
-                    for byte in &(name_bytes.len() as u16).to_be_bytes() {
-                        final_data.push(*byte);
-                    }
-                    for byte in name_bytes {
-                        final_data.push(*byte);
-                    }
+                    final_data.push(named_tag.tag.tag_prefix());
+                    write_string_tag(&mut final_data, &named_tag.name)?;
                     for byte in named_tag.tag.write_to_bytes()? {
                         final_data.push(byte);
                     }
@@ -321,9 +863,112 @@ impl Tag {
     }
 }
 
+impl std::ops::Index<usize> for Tag {
+    type Output = Tag;
+    /// Gets an element of a list tag.
+    /// 
+    /// # Panics
+    /// Panics if this isn't a list tag, or if the index is out of bounds.
+    fn index(&self, index: usize) -> &Tag {
+        match self {
+            Self::List(elements) => &elements[index],
+            Self::EmptyList(_) => panic!("Tried to index into an empty NBT list!"),
+            _ => panic!("Tried to index into a non-list NBT tag!")
+        }
+    }
+}
+
+impl std::fmt::Display for Tag {
+    /// Writes this tag as indented text, one child per line, like
+    /// `Compound {` followed by its children. Numbers keep their SNBT type
+    /// suffix, so `3b` is a byte and `3` is an int. Meant for debugging, not
+    /// for parsing back in; use [Tag::to_snbt] for that.
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        display_tag(f, None, self, 0)
+    }
+}
+
+impl std::fmt::Display for NamedTag {
+    /// Writes this tag like [Tag]'s Display implementation, with the name
+    /// after the tag's type, like `Compound "root" {` or `Byte "x": 3b`.
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        display_tag(f, Some(&self.name), &self.tag, 0)
+    }
+}
+
+fn display_tag(
+    f: &mut std::fmt::Formatter, name: Option<&str>, tag: &Tag, indent: usize
+) -> std::fmt::Result {
+    fn write_array<T: std::fmt::Display>(
+        f: &mut std::fmt::Formatter, prefix: &str, suffix: &str, data: &[T]
+    ) -> std::fmt::Result {
+        write!(f, "[{};", prefix)?;
+        for (index, element) in data.iter().enumerate() {
+            let separator = if index == 0 { " " } else { ", " };
+            write!(f, "{}{}{}", separator, element, suffix)?;
+        }
+        write!(f, "]")
+    }
+
+    let type_name = match tag {
+        Tag::End => "End",
+        Tag::Byte(_) => "Byte",
+        Tag::Short(_) => "Short",
+        Tag::Int(_) => "Int",
+        Tag::Long(_) => "Long",
+        Tag::Float(_) => "Float",
+        Tag::Double(_) => "Double",
+        Tag::ByteArray(_) => "ByteArray",
+        Tag::String(_) => "String",
+        Tag::List(_) | Tag::EmptyList(_) => "List",
+        Tag::Compound(_) => "Compound",
+        Tag::IntArray(_) => "IntArray",
+        Tag::LongArray(_) => "LongArray"
+    };
+    write!(f, "{:indent$}{}", "", type_name, indent = indent * 4)?;
+    match (name, tag) {
+        (Some(name), Tag::Compound(_)) => write!(f, " {:?} ", name)?,
+        (Some(name), _) => write!(f, " {:?}: ", name)?,
+        (None, _) => write!(f, " ")?
+    }
+    match tag {
+        Tag::End => Ok(()),
+        Tag::Byte(data) => write!(f, "{}b", data),
+        Tag::Short(data) => write!(f, "{}s", data),
+        Tag::Int(data) => write!(f, "{}", data),
+        Tag::Long(data) => write!(f, "{}L", data),
+        Tag::Float(data) => write!(f, "{}f", data),
+        Tag::Double(data) => write!(f, "{}d", data),
+        Tag::String(data) => write!(f, "{:?}", data),
+        Tag::ByteArray(data) => write_array(f, "B", "b", data),
+        Tag::IntArray(data) => write_array(f, "I", "", data),
+        Tag::LongArray(data) => write_array(f, "L", "L", data),
+        Tag::EmptyList(_) => write!(f, "[]"),
+        Tag::List(elements) if elements.is_empty() => write!(f, "[]"),
+        Tag::Compound(children) if children.is_empty() => write!(f, "{{}}"),
+        Tag::List(elements) => {
+            writeln!(f, "[")?;
+            for (index, element) in elements.iter().enumerate() {
+                display_tag(f, None, element, indent + 1)?;
+                writeln!(f, "{}", if index + 1 == elements.len() { "" } else { "," })?;
+            }
+            write!(f, "{:indent$}]", "", indent = indent * 4)
+        }
+        Tag::Compound(children) => {
+            writeln!(f, "{{")?;
+            for (index, child) in children.iter().enumerate() {
+                display_tag(f, Some(&child.name), &child.tag, indent + 1)?;
+                writeln!(f, "{}", if index + 1 == children.len() { "" } else { "," })?;
+            }
+            write!(f, "{:indent$}}}", "", indent = indent * 4)
+        }
+    }
+}
+
 // TODO: an implementation of PartialEq and Eq that respects NBT ordering rules
 // would be great
- #[derive(PartialEq, Clone, Debug)]
+#[derive(PartialEq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 /// Represents a key-value pair in a NBT structure.
 pub struct NamedTag {
     /// Name of the given tag.
@@ -331,3 +976,237 @@ pub struct NamedTag {
     /// Tag of this pair.
     pub tag: Tag
 }
+
+impl NamedTag {
+    /// Creates a new `NamedTag` from a name and any tag.
+    pub fn new(name: impl Into<String>, tag: Tag) -> NamedTag {
+        NamedTag { name: name.into(), tag }
+    }
+    /// Creates a new `NamedTag` holding a compound tag made up of the given
+    /// children. Useful for building a root tag to pass to [to_bytes].
+    pub fn compound(name: impl Into<String>, children: Vec<NamedTag>) -> NamedTag {
+        NamedTag::new(name, Tag::Compound(children))
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Default)]
+/// Builds a [Tag::Compound] one child at a time, in order.
+/// 
+/// ```
+/// # use golden_apple::nbt::{CompoundBuilder, Tag};
+/// let item = CompoundBuilder::new()
+///     .byte("Count", 1)
+///     .string("id", "minecraft:stone")
+///     .compound("tag", CompoundBuilder::new().int("Damage", 0))
+///     .build();
+/// assert_eq!(item.get("id").and_then(Tag::as_str), Some("minecraft:stone"));
+/// ```
+pub struct CompoundBuilder {
+    children: Vec<NamedTag>
+}
+
+impl CompoundBuilder {
+    /// Starts building a compound with no children.
+    pub fn new() -> CompoundBuilder {
+        CompoundBuilder::default()
+    }
+    /// Adds a child holding any tag.
+    pub fn tag(mut self, name: impl Into<String>, tag: Tag) -> CompoundBuilder {
+        self.children.push(NamedTag::new(name, tag));
+        self
+    }
+    /// Adds a byte tag.
+    pub fn byte(self, name: impl Into<String>, value: i8) -> CompoundBuilder {
+        self.tag(name, Tag::Byte(value))
+    }
+    /// Adds a short tag.
+    pub fn short(self, name: impl Into<String>, value: i16) -> CompoundBuilder {
+        self.tag(name, Tag::Short(value))
+    }
+    /// Adds an int tag.
+    pub fn int(self, name: impl Into<String>, value: i32) -> CompoundBuilder {
+        self.tag(name, Tag::Int(value))
+    }
+    /// Adds a long tag.
+    pub fn long(self, name: impl Into<String>, value: i64) -> CompoundBuilder {
+        self.tag(name, Tag::Long(value))
+    }
+    /// Adds a float tag.
+    pub fn float(self, name: impl Into<String>, value: f32) -> CompoundBuilder {
+        self.tag(name, Tag::Float(value))
+    }
+    /// Adds a double tag.
+    pub fn double(self, name: impl Into<String>, value: f64) -> CompoundBuilder {
+        self.tag(name, Tag::Double(value))
+    }
+    /// Adds a string tag.
+    pub fn string(self, name: impl Into<String>, value: impl Into<String>) -> CompoundBuilder {
+        self.tag(name, Tag::String(value.into()))
+    }
+    /// Adds a byte array tag.
+    pub fn byte_array(self, name: impl Into<String>, value: Vec<i8>) -> CompoundBuilder {
+        self.tag(name, Tag::ByteArray(value))
+    }
+    /// Adds an int array tag.
+    pub fn int_array(self, name: impl Into<String>, value: Vec<i32>) -> CompoundBuilder {
+        self.tag(name, Tag::IntArray(value))
+    }
+    /// Adds a long array tag.
+    pub fn long_array(self, name: impl Into<String>, value: Vec<i64>) -> CompoundBuilder {
+        self.tag(name, Tag::LongArray(value))
+    }
+    /// Adds a list tag. Every element should be the same type of tag.
+    pub fn list(self, name: impl Into<String>, elements: Vec<Tag>) -> CompoundBuilder {
+        self.tag(name, Tag::List(elements))
+    }
+    /// Adds a nested compound tag, built by another builder.
+    pub fn compound(self, name: impl Into<String>, compound: CompoundBuilder) -> CompoundBuilder {
+        self.tag(name, compound.build())
+    }
+    /// Finishes building, giving a [Tag::Compound].
+    pub fn build(self) -> Tag {
+        Tag::Compound(self.children)
+    }
+    /// Finishes building, giving a named compound that can be passed straight
+    /// to [to_bytes] as a root tag.
+    pub fn build_named(self, name: impl Into<String>) -> NamedTag {
+        NamedTag::new(name, self.build())
+    }
+}
+
+#[derive(PartialEq, Clone, Debug)]
+/// A single difference between two NBT structures, as found by [diff].
+/// 
+/// Paths are made of compound names and list indices separated by `/`, like
+/// `Inventory/0/id`. A change to the tags passed to [diff] themselves has an
+/// empty path.
+pub enum NbtChange {
+    /// A tag exists in the new structure but not the old one.
+    Added {
+        /// Where the tag was added.
+        path: String,
+        /// The added tag.
+        value: Tag
+    },
+    /// A tag exists in the old structure but not the new one.
+    Removed {
+        /// Where the tag was removed from.
+        path: String,
+        /// The removed tag.
+        value: Tag
+    },
+    /// A tag exists in both structures, but has a different value.
+    Modified {
+        /// Where the tag was changed.
+        path: String,
+        /// The tag's old value.
+        old: Tag,
+        /// The tag's new value.
+        new: Tag
+    }
+}
+
+/// Compares two tags and lists every difference between them. Compounds are
+/// compared by name, so reordering a compound's children isn't a change. Lists
+/// are compared index by index. Any other tags, including arrays, are reported
+/// as a whole when they differ.
+pub fn diff(old: &Tag, new: &Tag) -> Vec<NbtChange> {
+    let mut changes = vec![];
+    diff_into(old, new, String::new(), &mut changes);
+
+    changes
+}
+
+fn diff_into(old: &Tag, new: &Tag, path: String, changes: &mut Vec<NbtChange>) {
+    let child_path = |name: &str| {
+        if path.is_empty() {
+            name.to_string()
+        }
+        else {
+            format!("{}/{}", path, name)
+        }
+    };
+    match (old, new) {
+        (Tag::Compound(old_children), Tag::Compound(new_children)) => {
+            for old_child in old_children {
+                match new_children.iter().find(|child| child.name == old_child.name) {
+                    Some(new_child) => {
+                        diff_into(&old_child.tag, &new_child.tag, child_path(&old_child.name), changes);
+                    }
+                    None => {
+                        changes.push(NbtChange::Removed {
+                            path: child_path(&old_child.name),
+                            value: old_child.tag.clone()
+                        });
+                    }
+                }
+            }
+            for new_child in new_children {
+                if !old_children.iter().any(|child| child.name == new_child.name) {
+                    changes.push(NbtChange::Added {
+                        path: child_path(&new_child.name),
+                        value: new_child.tag.clone()
+                    });
+                }
+            }
+        }
+        (Tag::List(old_elements), Tag::List(new_elements)) => {
+            for index in 0..old_elements.len().max(new_elements.len()) {
+                let path = child_path(&index.to_string());
+                match (old_elements.get(index), new_elements.get(index)) {
+                    (Some(old_element), Some(new_element)) => {
+                        diff_into(old_element, new_element, path, changes);
+                    }
+                    (Some(old_element), None) => {
+                        changes.push(NbtChange::Removed { path, value: old_element.clone() });
+                    }
+                    (None, Some(new_element)) => {
+                        changes.push(NbtChange::Added { path, value: new_element.clone() });
+                    }
+                    (None, None) => unreachable!("index is within the longer list")
+                }
+            }
+        }
+        _ => {
+            if old != new {
+                changes.push(NbtChange::Modified { path, old: old.clone(), new: new.clone() });
+            }
+        }
+    }
+}
+
+/// Serializes the children of a compound as a map, keeping them in order.
+#[cfg(feature = "serde")]
+mod compound_map {
+    use super::{NamedTag, Tag};
+    use serde::de::{Deserializer, MapAccess, Visitor};
+    use serde::ser::{SerializeMap, Serializer};
+
+    pub fn serialize<S: Serializer>(children: &[NamedTag], serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(children.len()))?;
+        for child in children {
+            map.serialize_entry(&child.name, &child.tag)?;
+        }
+        map.end()
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<NamedTag>, D::Error> {
+        struct CompoundVisitor;
+
+        impl<'de> Visitor<'de> for CompoundVisitor {
+            type Value = Vec<NamedTag>;
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                write!(f, "a map of names to NBT tags")
+            }
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Vec<NamedTag>, A::Error> {
+                let mut children = vec![];
+                while let Some((name, tag)) = map.next_entry::<String, Tag>()? {
+                    children.push(NamedTag { name, tag });
+                }
+                Ok(children)
+            }
+        }
+
+        deserializer.deserialize_map(CompoundVisitor)
+    }
+}
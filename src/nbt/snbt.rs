@@ -0,0 +1,347 @@
+//! Reading and writing SNBT, the "stringified" NBT used in commands and data
+//! packs, like `{Air:0s,Items:[{Slot:0b}]}`.
+
+use super::{NamedTag, ReadLimits, Tag};
+use crate::Error;
+
+impl Tag {
+    /// Parses a tag from SNBT. Numbers take their type from a suffix (`b`,
+    /// `s`, `l`, `f`, or `d`, in either case), and default to an Int if they're
+    /// whole or a Double if they aren't. `true` and `false` become Bytes, and
+    /// any other unquoted word is a String. Typed arrays are written as
+    /// `[B;1b,2b]`, `[I;1,2]`, and `[L;1L,2L]`.
+    /// 
+    /// Returns [Error::InvalidSnbt] with the byte offset of the first problem
+    /// if the text can't be parsed, or [Error::NbtTooDeep] if lists and
+    /// compounds are nested more than 512 levels deep.
+    pub fn from_snbt(snbt: &str) -> Result<Tag, Error> {
+        let mut parser = Parser { input: snbt, position: 0, depth: 0 };
+        let tag = parser.value()?;
+        parser.skip_whitespace();
+        if parser.position != snbt.len() {
+            return Err(Error::InvalidSnbt(parser.position));
+        }
+
+        Ok(tag)
+    }
+    /// Writes this tag as SNBT. Every number gets a type suffix except Ints,
+    /// so the result parses back into the same tag with [Tag::from_snbt].
+    /// 
+    /// There are a few exceptions. SNBT has no way to write NaN or infinite
+    /// numbers, so Floats and Doubles holding them are written as `NaNf`,
+    /// `inff`, `-infd`, and so on, which parse back as Strings. Empty lists
+    /// parse back as `Tag::EmptyList(0)` whatever their element type was.
+    pub fn to_snbt(&self) -> String {
+        let mut snbt = String::new();
+        write_snbt(self, &mut snbt);
+
+        snbt
+    }
+}
+
+fn write_snbt(tag: &Tag, snbt: &mut String) {
+    fn write_array<T: std::fmt::Display>(prefix: &str, suffix: &str, data: &[T], snbt: &mut String) {
+        snbt.push('[');
+        snbt.push_str(prefix);
+        for (index, element) in data.iter().enumerate() {
+            if index != 0 {
+                snbt.push(',');
+            }
+            snbt.push_str(&format!("{}{}", element, suffix));
+        }
+        snbt.push(']');
+    }
+
+    match tag {
+        Tag::End => {}
+        Tag::Byte(data) => snbt.push_str(&format!("{}b", data)),
+        Tag::Short(data) => snbt.push_str(&format!("{}s", data)),
+        Tag::Int(data) => snbt.push_str(&data.to_string()),
+        Tag::Long(data) => snbt.push_str(&format!("{}L", data)),
+        Tag::Float(data) => snbt.push_str(&format!("{}f", data)),
+        Tag::Double(data) => snbt.push_str(&format!("{}d", data)),
+        Tag::String(data) => write_quoted(data, snbt),
+        Tag::ByteArray(data) => write_array("B;", "b", data, snbt),
+        Tag::IntArray(data) => write_array("I;", "", data, snbt),
+        Tag::LongArray(data) => write_array("L;", "L", data, snbt),
+        Tag::List(data) => {
+            snbt.push('[');
+            // End tags have no SNBT form, so any in a list are left out.
+            for (index, element) in data.iter().filter(|tag| **tag != Tag::End).enumerate() {
+                if index != 0 {
+                    snbt.push(',');
+                }
+                write_snbt(element, snbt);
+            }
+            snbt.push(']');
+        }
+        Tag::EmptyList(_) => snbt.push_str("[]"),
+        Tag::Compound(data) => {
+            snbt.push('{');
+            for (index, NamedTag { name, tag }) in data.iter().enumerate() {
+                if index != 0 {
+                    snbt.push(',');
+                }
+                if !name.is_empty() && name.chars().all(is_unquoted_char) {
+                    snbt.push_str(name);
+                }
+                else {
+                    write_quoted(name, snbt);
+                }
+                snbt.push(':');
+                write_snbt(tag, snbt);
+            }
+            snbt.push('}');
+        }
+    }
+}
+
+fn write_quoted(string: &str, snbt: &mut String) {
+    snbt.push('"');
+    for character in string.chars() {
+        if character == '"' || character == '\\' {
+            snbt.push('\\');
+        }
+        snbt.push(character);
+    }
+    snbt.push('"');
+}
+
+/// Characters that can appear in a key or string without quotes.
+fn is_unquoted_char(character: char) -> bool {
+    character.is_ascii_alphanumeric() || matches!(character, '_' | '-' | '.' | '+')
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    position: usize,
+    /// How many lists and compounds the value being parsed is nested inside.
+    depth: usize
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<char> {
+        self.input[self.position..].chars().next()
+    }
+    fn skip_whitespace(&mut self) {
+        while let Some(character) = self.peek() {
+            if !character.is_whitespace() {
+                break;
+            }
+            self.position += character.len_utf8();
+        }
+    }
+    /// Skips whitespace, then consumes `expected` or fails.
+    fn expect(&mut self, expected: char) -> Result<(), Error> {
+        self.skip_whitespace();
+        if self.peek() != Some(expected) {
+            return Err(Error::InvalidSnbt(self.position));
+        }
+        self.position += expected.len_utf8();
+
+        Ok(())
+    }
+    /// Skips whitespace, then consumes `expected` if it's next.
+    fn accept(&mut self, expected: char) -> bool {
+        self.skip_whitespace();
+        let found = self.peek() == Some(expected);
+        if found {
+            self.position += expected.len_utf8();
+        }
+
+        found
+    }
+    fn value(&mut self) -> Result<Tag, Error> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('{') => self.nested(Self::compound),
+            Some('[') => self.nested(Self::list),
+            Some('"' | '\'') => Ok(Tag::String(self.quoted()?)),
+            _ => {
+                let start = self.position;
+                let word = self.unquoted()?;
+                parse_word(word).ok_or(Error::InvalidSnbt(start))
+            }
+        }
+    }
+    /// Parses a list or compound one level deeper, failing past the same
+    /// depth limit as binary NBT so that parsing can't overflow the stack.
+    fn nested(&mut self, parse: fn(&mut Self) -> Result<Tag, Error>) -> Result<Tag, Error> {
+        if self.depth >= ReadLimits::default().max_depth {
+            return Err(Error::NbtTooDeep);
+        }
+        self.depth += 1;
+        let tag = parse(self);
+        self.depth -= 1;
+
+        tag
+    }
+    fn compound(&mut self) -> Result<Tag, Error> {
+        self.expect('{')?;
+        let mut children = vec![];
+        if !self.accept('}') {
+            loop {
+                self.skip_whitespace();
+                let name = match self.peek() {
+                    Some('"' | '\'') => self.quoted()?,
+                    _ => self.unquoted()?.to_string()
+                };
+                self.expect(':')?;
+                children.push(NamedTag { name, tag: self.value()? });
+                if self.accept('}') {
+                    break;
+                }
+                self.expect(',')?;
+            }
+        }
+
+        Ok(Tag::Compound(children))
+    }
+    fn list(&mut self) -> Result<Tag, Error> {
+        self.expect('[')?;
+        let rest = &self.input[self.position..];
+        for (prefix, array_type) in [("B;", 0x07), ("I;", 0x0B), ("L;", 0x0C)] {
+            if rest.starts_with(prefix) {
+                self.position += prefix.len();
+                return self.array(array_type);
+            }
+        }
+
+        // An empty list has no element type, so it reads back the same way
+        // as one from binary NBT.
+        if self.accept(']') {
+            return Ok(Tag::EmptyList(0));
+        }
+        let mut elements: Vec<Tag> = vec![];
+        loop {
+            self.skip_whitespace();
+            let start = self.position;
+            let element = self.value()?;
+            // Every element of a list has to be the same type.
+            if elements.first().is_some_and(|first| first.tag_prefix() != element.tag_prefix()) {
+                return Err(Error::InvalidSnbt(start));
+            }
+            elements.push(element);
+            if self.accept(']') {
+                break;
+            }
+            self.expect(',')?;
+        }
+
+        Ok(Tag::List(elements))
+    }
+    /// Reads the elements of a typed array, after its `B;`, `I;`, or `L;`.
+    fn array(&mut self, array_type: u8) -> Result<Tag, Error> {
+        let mut elements = vec![];
+        if !self.accept(']') {
+            loop {
+                self.skip_whitespace();
+                let start = self.position;
+                let element = match parse_word(self.unquoted()?) {
+                    Some(Tag::Byte(value)) if array_type == 0x07 => value as i64,
+                    Some(Tag::Int(value)) if array_type != 0x0C => value as i64,
+                    Some(Tag::Long(value)) if array_type == 0x0C => value,
+                    _ => {
+                        return Err(Error::InvalidSnbt(start));
+                    }
+                };
+                elements.push((start, element));
+                if self.accept(']') {
+                    break;
+                }
+                self.expect(',')?;
+            }
+        }
+
+        let narrow = |(start, value): (usize, i64)| -> Result<i32, Error> {
+            i32::try_from(value).map_err(|_| Error::InvalidSnbt(start))
+        };
+        Ok(match array_type {
+            0x07 => Tag::ByteArray(elements.into_iter().map(|(start, value)| {
+                i8::try_from(value).map_err(|_| Error::InvalidSnbt(start))
+            }).collect::<Result<_, _>>()?),
+            0x0B => Tag::IntArray(elements.into_iter().map(narrow).collect::<Result<_, _>>()?),
+            _ => Tag::LongArray(elements.into_iter().map(|(_, value)| value).collect())
+        })
+    }
+    /// Reads a string in single or double quotes, handling backslash escapes.
+    fn quoted(&mut self) -> Result<String, Error> {
+        let start = self.position;
+        let Some(quote) = self.peek() else {
+            return Err(Error::InvalidSnbt(start));
+        };
+        self.position += 1;
+        let mut string = String::new();
+        let mut escaped = false;
+        while let Some(character) = self.peek() {
+            self.position += character.len_utf8();
+            if escaped {
+                string.push(character);
+                escaped = false;
+            }
+            else if character == '\\' {
+                escaped = true;
+            }
+            else if character == quote {
+                return Ok(string);
+            }
+            else {
+                string.push(character);
+            }
+        }
+
+        // The string was never closed.
+        Err(Error::InvalidSnbt(start))
+    }
+    fn unquoted(&mut self) -> Result<&str, Error> {
+        let start = self.position;
+        while self.peek().is_some_and(is_unquoted_char) {
+            self.position += 1;
+        }
+        if start == self.position {
+            return Err(Error::InvalidSnbt(start));
+        }
+
+        Ok(&self.input[start..self.position])
+    }
+}
+
+/// Works out what type an unquoted word is: a boolean, a number, or otherwise
+/// a string.
+fn parse_word(word: &str) -> Option<Tag> {
+    match word {
+        "true" => return Some(Tag::Byte(1)),
+        "false" => return Some(Tag::Byte(0)),
+        _ => {}
+    }
+    // Rust accepts words like `inf` and `NaN` as floats, but SNBT doesn't, so
+    // they're caught here and, with a sign in front, by checking for finite
+    // values below.
+    let numeric = word.starts_with(|c: char| c.is_ascii_digit() || matches!(c, '-' | '+' | '.'));
+    if !numeric {
+        return Some(Tag::String(word.to_string()));
+    }
+
+    let (number, suffix) = word.split_at(word.len() - 1);
+    let typed = match suffix {
+        "b" | "B" => number.parse().ok().map(Tag::Byte),
+        "s" | "S" => number.parse().ok().map(Tag::Short),
+        "l" | "L" => number.parse().ok().map(Tag::Long),
+        "f" | "F" => number.parse().ok().filter(|float: &f32| float.is_finite()).map(Tag::Float),
+        "d" | "D" => number.parse().ok().filter(|double: &f64| double.is_finite()).map(Tag::Double),
+        _ => None
+    };
+    let untyped = || {
+        if let Ok(int) = word.parse() {
+            Some(Tag::Int(int))
+        }
+        else if word.contains(['.', 'e', 'E']) {
+            word.parse().ok().filter(|double: &f64| double.is_finite()).map(Tag::Double)
+        }
+        else {
+            None
+        }
+    };
+
+    Some(typed.or_else(untyped).unwrap_or_else(|| Tag::String(word.to_string())))
+}
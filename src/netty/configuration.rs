@@ -2,9 +2,11 @@ use crate::nbt::NamedTag;
 use crate::{Error, Identifier, VarInt, UUID};
 use crate::generalized::{
     boolean_from_reader, byte_from_reader, byte_to_bytes, int_from_reader,
-    int_to_bytes, long_from_reader, long_to_bytes, string_from_reader_no_cesu8,
-    string_to_bytes_no_cesu8, unsigned_byte_from_reader
+    int_to_bytes, long_from_reader, long_to_bytes, read_remaining,
+    string_from_reader_no_cesu8, string_to_bytes_no_cesu8, unsigned_byte_from_reader,
+    with_length_prefix
 };
+use super::{Compressor, PacketBody, Tags};
 use std::io::Read;
 
 #[derive(Clone, PartialEq, Eq, Debug)]
@@ -96,7 +98,7 @@ pub enum ClientboundPacket {
         flags: Vec<Identifier>
     },
     UpdateTags {
-        // TODO
+        registries: Vec<Tags>
     },
     KnownPacks {
         packs: Vec<KnownPack>
@@ -109,7 +111,7 @@ pub enum ClientboundPacket {
     }
 }
 
-#[derive(Clone, PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
 pub struct Property {
     pub name: String,
     pub value: String,
@@ -122,12 +124,8 @@ impl ServerboundPacket {
     /// server using this protocol version.
     pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
         let mut packet_bytes = self.to_most_bytes()?;
-        // Calculate packet length, prepend, and send it!
-        let packet_length = packet_bytes.len();
-        let mut result = VarInt::from_value(packet_length as i32)?.to_bytes()?;
-        result.append(&mut packet_bytes);
-
-        Ok(result)
+        // Prepend the packet length and send it!
+        Ok(with_length_prefix(|buf| buf.append(&mut packet_bytes)))
     }
     /// Converts the packet to bytes in the proper format for networking with
     /// traditional Minecraft software *minus* the packet length being prepended.
@@ -140,7 +138,7 @@ impl ServerboundPacket {
                 text_filtering, allow_server_listings
             } => {
                 // Packet ID
-                bytes.append(&mut VarInt::from_value(0x00)?.to_bytes()?);
+                VarInt::new(0x00).encode_into(&mut bytes);
 
                 // Payload
                 assert!(locale.chars().count() <= 16);
@@ -156,7 +154,7 @@ impl ServerboundPacket {
             }
             Self::CookieResponse { key, payload } => {
                 // Packet ID
-                bytes.append(&mut VarInt::from_value(0x01)?.to_bytes()?);
+                VarInt::new(0x01).encode_into(&mut bytes);
 
                 // Payload
                 bytes.append(&mut key.to_bytes()?);
@@ -165,7 +163,7 @@ impl ServerboundPacket {
                     bytes.push(0x01);
 
                     assert!(payload.len() <= 5120);
-                    bytes.append(&mut VarInt::from_value(payload.len() as i32)?.to_bytes()?);
+                    VarInt::new(payload.len() as i32).encode_into(&mut bytes);
                     bytes.append(&mut payload.clone());
                 }
                 else {
@@ -174,7 +172,7 @@ impl ServerboundPacket {
             }
             Self::PluginMessage { channel, data } => {
                 // Packet ID
-                bytes.append(&mut VarInt::from_value(0x02)?.to_bytes()?);
+                VarInt::new(0x02).encode_into(&mut bytes);
 
                 // Payload
                 bytes.append(&mut channel.to_bytes()?);
@@ -184,25 +182,25 @@ impl ServerboundPacket {
             }
             Self::AcknowledgeFinishConfiguration => {
                 // Packet ID
-                bytes.append(&mut VarInt::from_value(0x03)?.to_bytes()?);
+                VarInt::new(0x03).encode_into(&mut bytes);
             }
             Self::KeepAlive { id } => {
                 // Packet ID
-                bytes.append(&mut VarInt::from_value(0x04)?.to_bytes()?);
+                VarInt::new(0x04).encode_into(&mut bytes);
 
                 // Payload
                 bytes.append(&mut long_to_bytes(*id)?);
             }
             Self::Pong { id } => {
                 // Packet ID
-                bytes.append(&mut VarInt::from_value(0x05)?.to_bytes()?);
+                VarInt::new(0x05).encode_into(&mut bytes);
 
                 // Payload
                 bytes.append(&mut int_to_bytes(*id)?);
             }
             Self::ResourcePackResponse { uuid, result } => {
                 // Packet ID
-                bytes.append(&mut VarInt::from_value(0x06)?.to_bytes()?);
+                VarInt::new(0x06).encode_into(&mut bytes);
                 
                 // Payload
                 bytes.append(&mut uuid.to_bytes()?);
@@ -210,10 +208,10 @@ impl ServerboundPacket {
             }
             Self::KnownPacks { packs } => {
                 // Packet ID
-                bytes.append(&mut VarInt::from_value(0x07)?.to_bytes()?);
+                VarInt::new(0x07).encode_into(&mut bytes);
 
                 // Payload
-                bytes.append(&mut VarInt::from_value(packs.len() as i32)?.to_bytes()?);
+                VarInt::new(packs.len() as i32).encode_into(&mut bytes);
 
                 for pack in packs {
                     bytes.append(&mut string_to_bytes_no_cesu8(pack.namespace.clone())?);
@@ -230,55 +228,16 @@ impl ServerboundPacket {
     /// Only use this method after recieving
     /// [crate::netty::login::ClientboundPacket::SetCompression]. Even if a
     /// packet isn't encrypted, the format is slightly different.
-    // TODO: test that this is compliant and works
     pub fn to_bytes_com(&self, threshold: VarInt) -> Result<Vec<u8>, Error> {
-        // Get packet data.
-        let mut packet_bytes = self.to_most_bytes()?;
-        // Calculate packet length.
-        let packet_length = packet_bytes.len();
-
-        // If it's below the packet compression threshold,
-        if packet_length < threshold.value() as usize {
-            // Prepend length and send it off!
-            // We add 1 to `packet_length` to account for the compression length.
-            // (which is zero, but encodes as one byte)
-            let mut result = VarInt::from_value(packet_length as i32 + 1)?.to_bytes()?;
-            // Insert the compression length (0)
-            result.push(0x00);
-            // Add the rest of the packet
-            result.append(&mut packet_bytes);
-
-            Ok(result)
-        }
-        else {
-            // Otherwise, we need to compress the packet.
-            use std::io::prelude::*;
-            use flate2::Compression;
-            use flate2::write::ZlibEncoder;
-            // TODO: allow the user to select the compression type.
-            let mut encoder = ZlibEncoder::new(Vec::new(), Compression::fast());
-            // TODO: be more specific with the errors coming off of these `?`s.
-            encoder.write_all(&packet_bytes)?;
-            let mut compressed_data = encoder.finish()?;
-
-            // Put the length of the compressed section of the packet into this VarInt
-            let mut compressed_data_length = VarInt::from_value(compressed_data.len() as i32)?;
-            compressed_data_length.calculate_read_size();
-
-            // Prepend the value of (compressed data length + compressed data
-            // length length).
-            // Safe unwrap, since we just did `.calculate_read_size()`.
-            let mut result = VarInt::from_value(
-                compressed_data_length.value() +
-                compressed_data_length.read_size().unwrap() as i32
-            )?.to_bytes()?;
-            // Prepend compressed data length
-            result.append(&mut compressed_data_length.to_bytes()?);
-            // Add the rest of the packet
-            result.append(&mut compressed_data);
-
-            Ok(result)
-        }
+        self.to_bytes_com_with(threshold, &mut Compressor::new())
+    }
+    /// Equivalent to `to_bytes_com`, but reuses the given [Compressor]
+    /// instead of setting up a new one, which is faster when sending many
+    /// packets.
+    pub fn to_bytes_com_with(
+        &self, threshold: VarInt, compressor: &mut Compressor
+    ) -> Result<Vec<u8>, Error> {
+        compressor.frame(self.to_most_bytes()?, threshold)
     }
     /// Not done! Please wait for this to be finished or open a PR!
     #[cfg(feature = "encryption")]
@@ -291,11 +250,9 @@ impl ServerboundPacket {
         todo!()
     }
     pub fn from_reader<R: Read>(reader: &mut R) -> Result<Self, Error> {
-        let packet_length = VarInt::from_reader(reader)?;
-        
-        Self::from_reader_internal(reader, packet_length)
+        PacketBody::new(reader)?.parse(Self::from_reader_internal)
     }
-    fn from_reader_internal<R: Read>(reader: &mut R, packet_length: VarInt) -> Result<Self, Error> {
+    fn from_reader_internal<R: Read>(reader: &mut R) -> Result<Self, Error> {
         let packet_id = VarInt::from_reader(reader)?;
         match packet_id.value() {
             0x00 => {
@@ -329,18 +286,7 @@ impl ServerboundPacket {
             }
             0x02 => {
                 let key = Identifier::from_reader(reader)?;
-                // It's okay to determine length lazily here unless a custom
-                // server/client sends something under the `minecraft`` namespace
-                // which is both improper and disallowed. In the future this
-                // should just do things the right way, so this is a TODO.
-                let key_len = key.to_bytes()?.len();
-                let data_len =
-                    packet_length.value() as usize -
-                    packet_id.read_size().unwrap() as usize -
-                    key_len;
-                
-                let mut data = vec![0; data_len];
-                reader.read_exact(&mut data).unwrap();
+                let data = read_remaining(reader)?;
 
                 Ok(Self::PluginMessage { channel: key, data })
             }
@@ -387,38 +333,8 @@ impl ServerboundPacket {
     /// this method after recieving
     /// [crate::netty::login::ClientboundPacket::SetCompression]. Even if a
     /// packet isn't encrypted, the format is slightly different.
-    // TODO: test that this is compliant and works. This is pretty gross, could
-    // use some cleanup too.
     pub fn from_reader_com<R: Read>(reader: &mut R) -> Result<Self, Error> {
-        let remaining_len = VarInt::from_reader(reader)?;
-        let compressed_len = VarInt::from_reader(reader)?;
-        if compressed_len.value() == 0 {
-            // Packet is not compressed.
-            Self::from_reader_internal(
-                reader,
-                VarInt::from_value(
-                    remaining_len.value() -
-                    compressed_len.read_size().unwrap() as i32
-                )?
-            )
-        }
-        else {
-            // Packet is compressed. Grab all data...
-            let mut packet_data = vec![0x00; remaining_len.value() as usize - compressed_len.read_size().unwrap() as usize];
-            reader.read_exact(&mut packet_data)?;
-            // Add a decoding wrapper...
-            let mut decoded =
-                flate2::bufread::ZlibDecoder::new(packet_data.as_ref());
-            
-            // And interpret the packet. Also return.
-            Self::from_reader_internal(
-                &mut decoded,
-                VarInt::from_value(
-                    remaining_len.value() -
-                    compressed_len.read_size().unwrap() as i32
-                )?
-            )
-        }
+        PacketBody::new_com(reader)?.parse(Self::from_reader_internal)
     }
     /// Not done! Please wait for this to be finished or open a PR!
     #[cfg(feature = "encryption")]
@@ -435,11 +351,21 @@ impl ClientboundPacket {
         match self {
             Self::CookieRequest { key } => {
                 // Packet ID
-                bytes.append(&mut VarInt::from_value(0x00)?.to_bytes()?);
+                VarInt::new(0x00).encode_into(&mut bytes);
 
                 // Payload
                 bytes.append(&mut key.to_bytes()?);
             }
+            Self::UpdateTags { registries } => {
+                // Packet ID
+                VarInt::new(0x0D).encode_into(&mut bytes);
+
+                // Payload
+                VarInt::new(registries.len() as i32).encode_into(&mut bytes);
+                for tags in registries {
+                    bytes.append(&mut tags.to_bytes()?);
+                }
+            }
             _ => todo!()
         }
 
@@ -449,67 +375,24 @@ impl ClientboundPacket {
     /// client using this protocol version.
     pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
         let mut packet_bytes = self.to_most_bytes()?;
-        // Calculate packet length, prepend, and send it!
-        let packet_length = packet_bytes.len();
-        let mut result = VarInt::from_value(packet_length as i32)?.to_bytes()?;
-        result.append(&mut packet_bytes);
-
-        Ok(result)
+        // Prepend the packet length and send it!
+        Ok(with_length_prefix(|buf| buf.append(&mut packet_bytes)))
     }
     /// Converts this packet into bytes that can be sent over the network to a
     /// client using this protocol version, once compression has been enabled.
     /// Only use this method after sending
     /// [crate::netty::login::ClientboundPacket::SetCompression]. Even if a
     /// packet isn't encrypted, the format is slightly different.
-    // TODO: test that this is compliant and works
     pub fn to_bytes_com(&self, threshold: VarInt) -> Result<Vec<u8>, Error> {
-        // Get packet data.
-        let mut packet_bytes = self.to_most_bytes()?;
-        // Calculate packet length.
-        let packet_length = packet_bytes.len();
-
-        // If it's below the packet compression threshold,
-        if packet_length < threshold.value() as usize {
-            // Prepend length and send it off!
-            // We add 1 to `packet_length` to account for the compression length.
-            // (which is zero, but encodes as one byte)
-            let mut result = VarInt::from_value(packet_length as i32 + 1)?.to_bytes()?;
-            // Insert the compression length (0)
-            result.push(0x00);
-            // Add the rest of the packet
-            result.append(&mut packet_bytes);
-
-            Ok(result)
-        }
-        else {
-            // Otherwise, we need to compress the packet.
-            use std::io::prelude::*;
-            use flate2::Compression;
-            use flate2::write::ZlibEncoder;
-            // TODO: allow the user to select the compression type.
-            let mut encoder = ZlibEncoder::new(Vec::new(), Compression::fast());
-            // TODO: be more specific with the errors coming off of these `?`s.
-            encoder.write_all(&packet_bytes)?;
-            let mut compressed_data = encoder.finish()?;
-
-            // Put the length of the compressed section of the packet into this VarInt
-            let mut compressed_data_length = VarInt::from_value(compressed_data.len() as i32)?;
-            compressed_data_length.calculate_read_size();
-
-            // Prepend the value of (compressed data length + compressed data
-            // length length).
-            // Safe unwrap, since we just did `.calculate_read_size()`.
-            let mut result = VarInt::from_value(
-                compressed_data_length.value() +
-                compressed_data_length.read_size().unwrap() as i32
-            )?.to_bytes()?;
-            // Prepend compressed data length
-            result.append(&mut compressed_data_length.to_bytes()?);
-            // Add the rest of the packet
-            result.append(&mut compressed_data);
-
-            Ok(result)
-        }
+        self.to_bytes_com_with(threshold, &mut Compressor::new())
+    }
+    /// Equivalent to `to_bytes_com`, but reuses the given [Compressor]
+    /// instead of setting up a new one, which is faster when sending many
+    /// packets.
+    pub fn to_bytes_com_with(
+        &self, threshold: VarInt, compressor: &mut Compressor
+    ) -> Result<Vec<u8>, Error> {
+        compressor.frame(self.to_most_bytes()?, threshold)
     }
     /// Not done! Please wait for this to be finished or open a PR!
     #[cfg(feature = "encryption")]
@@ -522,14 +405,24 @@ impl ClientboundPacket {
         todo!()
     }
     pub fn from_reader<R: Read>(reader: &mut R) -> Result<Self, Error> {
-        let packet_length = VarInt::from_reader(reader)?;
-        
-        Self::from_reader_internal(reader, packet_length)
+        PacketBody::new(reader)?.parse(Self::from_reader_internal)
     }
-    fn from_reader_internal<R: Read>(reader: &mut R, packet_length: VarInt) -> Result<Self, Error> {
+    fn from_reader_internal<R: Read>(reader: &mut R) -> Result<Self, Error> {
         let packet_id = VarInt::from_reader(reader)?;
         match packet_id.value() {
-            0x00..0x10 => todo!(),
+            0x0D => {
+                let registry_count = VarInt::from_reader(reader)?;
+                if registry_count.value() < 0 {
+                    return Err(Error::InvalidArrayLength(registry_count));
+                }
+                let mut registries = vec![];
+                for _ in 0..registry_count.value() {
+                    registries.push(Tags::from_reader(reader)?);
+                }
+
+                Ok(ClientboundPacket::UpdateTags { registries })
+            }
+            0x00..0x10 => Err(Error::UnsupportedPacket(packet_id)),
             _ => { Err(Error::InvalidPacketId(packet_id)) }
         }
     }
@@ -543,38 +436,8 @@ impl ClientboundPacket {
     /// this method after sending
     /// [crate::netty::login::ClientboundPacket::SetCompression]. Even if a
     /// packet isn't encrypted, the format is slightly different.
-    // TODO: test that this is compliant and works. This is pretty gross, could
-    // use some cleanup too.
     pub fn from_reader_com<R: Read>(reader: &mut R) -> Result<Self, Error> {
-        let remaining_len = VarInt::from_reader(reader)?;
-        let compressed_len = VarInt::from_reader(reader)?;
-        if compressed_len.value() == 0 {
-            // Packet is not compressed. Return whatever standard parsing gives.
-            Self::from_reader_internal(
-                reader,
-                VarInt::from_value(
-                    remaining_len.value() -
-                    compressed_len.read_size().unwrap() as i32
-                )?
-            )
-        }
-        else {
-            // Packet is compressed. Grab all data...
-            let mut packet_data = vec![0x00; remaining_len.value() as usize - compressed_len.read_size().unwrap() as usize];
-            reader.read_exact(&mut packet_data)?;
-            // Add a decoding wrapper...
-            let mut decoded =
-                flate2::bufread::ZlibDecoder::new(packet_data.as_ref());
-            
-            // And interpret the packet. Also return it.
-            Self::from_reader_internal(
-                &mut decoded,
-                VarInt::from_value(
-                    remaining_len.value() -
-                    compressed_len.read_size().unwrap() as i32
-                )?
-            )
-        }
+        PacketBody::new_com(reader)?.parse(Self::from_reader_internal)
     }
     /// Not done! Please wait for this to be finished or open a PR!
     #[cfg(feature = "encryption")]
@@ -583,6 +446,122 @@ impl ClientboundPacket {
     }
 }
 
+#[derive(Clone, PartialEq, Eq, Debug)]
+/// Builds a [ServerboundPacket::ClientInformation] packet, so that fields can
+/// be set by name. Starts from the same settings a new vanilla client uses.
+/// 
+/// ```
+/// # use golden_apple::netty::configuration::{ChatSettings, ClientInformation};
+/// let packet = ClientInformation::new()
+///     .locale("fr_fr")
+///     .view_distance(8)
+///     .chat_mode(ChatSettings::System)
+///     .build()?;
+/// # Ok::<(), golden_apple::Error>(())
+/// ```
+pub struct ClientInformation {
+    locale: String,
+    view_distance: i8,
+    chat_mode: ChatSettings,
+    chat_colors: bool,
+    skin_parts: SkinSettings,
+    main_hand: VarInt,
+    text_filtering: bool,
+    allow_server_listings: bool
+}
+
+impl Default for ClientInformation {
+    /// English (US), a view distance of 12, all chat shown in color, every
+    /// skin part shown, right handed, no text filtering, and listed on servers.
+    fn default() -> ClientInformation {
+        ClientInformation {
+            locale: String::from("en_us"),
+            view_distance: 12,
+            chat_mode: ChatSettings::Full,
+            chat_colors: true,
+            skin_parts: SkinSettings::all(),
+            // Right handed.
+            main_hand: VarInt::from(1),
+            text_filtering: false,
+            allow_server_listings: true
+        }
+    }
+}
+
+impl ClientInformation {
+    /// Equivalent to [ClientInformation::default].
+    pub fn new() -> ClientInformation {
+        ClientInformation::default()
+    }
+    /// Sets the client's language, like `en_us`. Checked by
+    /// [ClientInformation::build].
+    pub fn locale(mut self, locale: impl Into<String>) -> ClientInformation {
+        self.locale = locale.into();
+        self
+    }
+    /// Sets how many chunks away from the player the client renders.
+    pub fn view_distance(mut self, view_distance: i8) -> ClientInformation {
+        self.view_distance = view_distance;
+        self
+    }
+    /// Sets which chat messages the client wants to receive.
+    pub fn chat_mode(mut self, chat_mode: ChatSettings) -> ClientInformation {
+        self.chat_mode = chat_mode;
+        self
+    }
+    /// Sets whether the client shows colors in chat.
+    pub fn chat_colors(mut self, chat_colors: bool) -> ClientInformation {
+        self.chat_colors = chat_colors;
+        self
+    }
+    /// Sets which parts of the player's skin are shown.
+    pub fn skin_parts(mut self, skin_parts: SkinSettings) -> ClientInformation {
+        self.skin_parts = skin_parts;
+        self
+    }
+    /// Sets the player's main hand. 0 is left and 1 is right.
+    pub fn main_hand(mut self, main_hand: VarInt) -> ClientInformation {
+        self.main_hand = main_hand;
+        self
+    }
+    /// Sets whether the client wants messages on signs and books filtered.
+    pub fn text_filtering(mut self, text_filtering: bool) -> ClientInformation {
+        self.text_filtering = text_filtering;
+        self
+    }
+    /// Sets whether the player can be shown in server list player samples.
+    pub fn allow_server_listings(mut self, allow_server_listings: bool) -> ClientInformation {
+        self.allow_server_listings = allow_server_listings;
+        self
+    }
+    /// Creates the packet, returning [Error::InvalidLocale] if the locale
+    /// isn't two groups of letters joined by an underscore, like `en_us` or
+    /// `en_US`, or is longer than the 16 characters allowed.
+    pub fn build(self) -> Result<ServerboundPacket, Error> {
+        let valid_part = |part: &str| {
+            (2..=3).contains(&part.len()) && part.chars().all(|c| c.is_ascii_alphabetic())
+        };
+        let valid = self.locale.len() <= 16 && match self.locale.split_once('_') {
+            Some((language, country)) => valid_part(language) && valid_part(country),
+            None => false
+        };
+        if !valid {
+            return Err(Error::InvalidLocale(self.locale));
+        }
+
+        Ok(ServerboundPacket::ClientInformation {
+            locale: self.locale,
+            view_distance: self.view_distance,
+            chat_mode: self.chat_mode,
+            chat_colors: self.chat_colors,
+            skin_parts: self.skin_parts,
+            main_hand: self.main_hand,
+            text_filtering: self.text_filtering,
+            allow_server_listings: self.allow_server_listings
+        })
+    }
+}
+
 #[derive(Clone, Copy, PartialEq, Eq, Debug, FromPrimitive, ToPrimitive)]
 #[repr(u8)]
 /// Represents the level of chat messages a given client would like to receive.
@@ -615,8 +594,7 @@ impl TryFrom<VarInt> for ChatSettings {
 
 impl From<ChatSettings> for VarInt {
     fn from(val: ChatSettings) -> VarInt {
-        // This is a safe unwrap: no enum value exceeds safe VarInt limits.
-        VarInt::from_value(val as i32).unwrap()
+        VarInt::from(val as i32)
     }
 }
 
@@ -639,9 +617,26 @@ bitflags::bitflags! {
     }
 }
 
-#[derive(Clone, PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
 pub struct KnownPack {
     pub namespace: String,
     pub id: String,
     pub version: String
 }
+
+impl KnownPack {
+    /// Works out which of the server's packs the client also knows, producing
+    /// the packs to send back in [ServerboundPacket::KnownPacks]. Packs are
+    /// matched by namespace and id, and are only kept if both sides have the
+    /// same version. The server's ordering is preserved.
+    pub fn negotiate(server_packs: &[KnownPack], client_known: &[KnownPack]) -> Vec<KnownPack> {
+        server_packs.iter()
+            .filter(|pack| client_known.iter().any(|known| {
+                known.namespace == pack.namespace
+                    && known.id == pack.id
+                    && known.version == pack.version
+            }))
+            .cloned()
+            .collect()
+    }
+}
@@ -1,7 +1,8 @@
 use crate::{generalized::unsigned_short_to_bytes, Error, VarInt};
 use std::io::Read;
 use crate::generalized::{
-    string_from_reader, unsigned_short_from_reader, string_to_bytes_no_cesu8
+    string_from_reader, unsigned_short_from_reader, string_to_bytes_no_cesu8,
+    with_length_prefix
 };
 
 #[derive(Clone, PartialEq, Eq, Debug)]
@@ -30,7 +31,7 @@ impl ServerboundPacket {
                 server_port, next_state
             } => {
                 // Packet ID
-                bytes.append(&mut VarInt::from_value(0)?.to_bytes()?);
+                VarInt::new(0).encode_into(&mut bytes);
                 // Fields
                 bytes.append(&mut protocol_version.to_bytes()?);
                 bytes.append(&mut string_to_bytes_no_cesu8(server_address.clone())?);
@@ -39,11 +40,8 @@ impl ServerboundPacket {
                 bytes.append(&mut tryinto.to_bytes()?);
             }
         }
-        let packet_length = bytes.len();
-        let mut result = VarInt::from_value(packet_length as i32)?.to_bytes()?;
-        result.append(&mut bytes);
-
-        Ok(result)
+        // Prepend the packet length and send it!
+        Ok(with_length_prefix(|buf| buf.append(&mut bytes)))
     }
     /// Deserializes a packet from a [Read] type
     pub fn from_reader<R: Read>(reader: &mut R) -> Result<Self, Error> {
@@ -1,7 +1,9 @@
 use crate::{Error, Identifier, VarInt, UUID};
 use crate::generalized::{
-    boolean_from_reader, string_from_reader_no_cesu8, string_to_bytes_no_cesu8
+    boolean_from_reader, read_remaining, string_from_reader_no_cesu8,
+    string_to_bytes_no_cesu8, with_length_prefix
 };
+use super::{Compressor, PacketBody};
 use std::io::Read;
 
 #[derive(Clone, PartialEq, Eq, Debug)]
@@ -59,7 +61,7 @@ pub enum ClientboundPacket {
     }
 }
 
-#[derive(Clone, PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
 pub struct Property {
     pub name: String,
     pub value: String,
@@ -72,12 +74,8 @@ impl ServerboundPacket {
     /// server using this protocol version.
     pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
         let mut packet_bytes = self.to_most_bytes()?;
-        // Calculate packet length, prepend, and send it!
-        let packet_length = packet_bytes.len();
-        let mut result = VarInt::from_value(packet_length as i32)?.to_bytes()?;
-        result.append(&mut packet_bytes);
-
-        Ok(result)
+        // Prepend the packet length and send it!
+        Ok(with_length_prefix(|buf| buf.append(&mut packet_bytes)))
     }
     /// Converts the packet to bytes in the proper format for networking with
     /// traditional Minecraft software *minus* the packet length being prepended.
@@ -86,7 +84,7 @@ impl ServerboundPacket {
         match self {
             Self::LoginStart { name, uuid } => {
                 // Packet ID
-                bytes.append(&mut VarInt::from_value(0x00)?.to_bytes()?);
+                VarInt::new(0x00).encode_into(&mut bytes);
 
                 // Payload (username, UUID)
                 // Anything larger than 16 characters is invalid.
@@ -96,21 +94,21 @@ impl ServerboundPacket {
             }
             Self::EncryptionResponse { shared_secret, verify_token } => {
                 // Packet ID
-                bytes.append(&mut VarInt::from_value(0x01)?.to_bytes()?);
+                VarInt::new(0x01).encode_into(&mut bytes);
                 
                 // Payload
                 // Shared Secret Length
-                bytes.append(&mut VarInt::from_value(shared_secret.len() as i32)?.to_bytes()?);
+                VarInt::new(shared_secret.len() as i32).encode_into(&mut bytes);
                 // Shared Secret
                 bytes.append(&mut shared_secret.clone());
                 // Verify Token Length
-                bytes.append(&mut VarInt::from_value(verify_token.len() as i32)?.to_bytes()?);
+                VarInt::new(verify_token.len() as i32).encode_into(&mut bytes);
                 // Verify Token
                 bytes.append(&mut verify_token.clone());
             }
             Self::LoginPluginResponse { message_id, data: data_opt } => {
                 // Packet ID
-                bytes.append(&mut VarInt::from_value(0x02)?.to_bytes()?);
+                VarInt::new(0x02).encode_into(&mut bytes);
 
                 // Payload
                 // Message ID
@@ -128,11 +126,11 @@ impl ServerboundPacket {
             }
             Self::LoginAcknowledged => {
                 // Packet ID, that's it!
-                bytes.append(&mut VarInt::from_value(0x03)?.to_bytes()?);
+                VarInt::new(0x03).encode_into(&mut bytes);
             }
             Self::CookieResponse { key, payload } => {
                 // Packet ID
-                bytes.append(&mut VarInt::from_value(0x04)?.to_bytes()?);
+                VarInt::new(0x04).encode_into(&mut bytes);
 
                 // Payload
                 // Identifier
@@ -143,7 +141,7 @@ impl ServerboundPacket {
                     // Has payload
                     bytes.push(0x01);
                     // Payload len
-                    bytes.append(&mut VarInt::from_value(payload.len() as i32)?.to_bytes()?);
+                    VarInt::new(payload.len() as i32).encode_into(&mut bytes);
                     // Payload
                     bytes.append(&mut payload.clone())
                 }
@@ -161,55 +159,16 @@ impl ServerboundPacket {
     /// Only use this method after recieving
     /// [crate::netty::login::ClientboundPacket::SetCompression]. Even if a
     /// packet isn't encrypted, the format is slightly different.
-    // TODO: test that this is compliant and works
     pub fn to_bytes_com(&self, threshold: VarInt) -> Result<Vec<u8>, Error> {
-        // Get packet data.
-        let mut packet_bytes = self.to_most_bytes()?;
-        // Calculate packet length.
-        let packet_length = packet_bytes.len();
-
-        // If it's below the packet compression threshold,
-        if packet_length < threshold.value() as usize {
-            // Prepend length and send it off!
-            // We add 1 to `packet_length` to account for the compression length.
-            // (which is zero, but encodes as one byte)
-            let mut result = VarInt::from_value(packet_length as i32 + 1)?.to_bytes()?;
-            // Insert the compression length (0)
-            result.push(0x00);
-            // Add the rest of the packet
-            result.append(&mut packet_bytes);
-            
-            Ok(result)
-        }
-        else {
-            // Otherwise, we need to compress the packet.
-            use std::io::prelude::*;
-            use flate2::Compression;
-            use flate2::write::ZlibEncoder;
-            // TODO: allow the user to select the compression type.
-            let mut encoder = ZlibEncoder::new(Vec::new(), Compression::fast());
-            // TODO: be more specific with the errors coming off of these `?`s.
-            encoder.write_all(&packet_bytes)?;
-            let mut compressed_data = encoder.finish()?;
-
-            // Put the length of the compressed section of the packet into this VarInt
-            let mut compressed_data_length = VarInt::from_value(compressed_data.len() as i32)?;
-            compressed_data_length.calculate_read_size();
-
-            // Prepend the value of (compressed data length + compressed data
-            // length length).
-            // Safe unwrap, since we just did `.calculate_read_size()`.
-            let mut result = VarInt::from_value(
-                compressed_data_length.value() +
-                compressed_data_length.read_size().unwrap() as i32
-            )?.to_bytes()?;
-            // Prepend compressed data length
-            result.append(&mut compressed_data_length.to_bytes()?);
-            // Add the rest of the packet
-            result.append(&mut compressed_data);
-
-            Ok(result)
-        }
+        self.to_bytes_com_with(threshold, &mut Compressor::new())
+    }
+    /// Equivalent to `to_bytes_com`, but reuses the given [Compressor]
+    /// instead of setting up a new one, which is faster when sending many
+    /// packets.
+    pub fn to_bytes_com_with(
+        &self, threshold: VarInt, compressor: &mut Compressor
+    ) -> Result<Vec<u8>, Error> {
+        compressor.frame(self.to_most_bytes()?, threshold)
     }
     /// Not done! Please wait for this to be finished or open a PR!
     #[cfg(feature = "encryption")]
@@ -222,11 +181,9 @@ impl ServerboundPacket {
         todo!()
     }
     pub fn from_reader<R: Read>(reader: &mut R) -> Result<Self, Error> {
-        let packet_length = VarInt::from_reader(reader)?;
-        
-        Self::from_reader_internal(reader, packet_length)
+        PacketBody::new(reader)?.parse(Self::from_reader_internal)
     }
-    fn from_reader_internal<R: Read>(reader: &mut R, packet_length: VarInt) -> Result<Self, Error> {
+    fn from_reader_internal<R: Read>(reader: &mut R) -> Result<Self, Error> {
         let packet_id = VarInt::from_reader(reader)?;
         match packet_id.value() {
             0x00 => {
@@ -251,13 +208,7 @@ impl ServerboundPacket {
                 let message_id = VarInt::from_reader(reader)?;
                 let bool_result = boolean_from_reader(reader)?;
                 if bool_result {
-                    let dta_len =
-                        packet_length.value() as usize -
-                        packet_id.read_size().unwrap() as usize -
-                        message_id.read_size().unwrap() as usize -
-                        1;
-                    let mut data = vec![0; dta_len];
-                    reader.read_exact(&mut data).unwrap();
+                    let data = read_remaining(reader)?;
 
                     Ok(ServerboundPacket::LoginPluginResponse {
                         message_id,
@@ -305,38 +256,8 @@ impl ServerboundPacket {
     /// this method after recieving
     /// [crate::netty::login::ClientboundPacket::SetCompression]. Even if a
     /// packet isn't encrypted, the format is slightly different.
-    // TODO: test that this is compliant and works. This is pretty gross, could
-    // use some cleanup too.
     pub fn from_reader_com<R: Read>(reader: &mut R) -> Result<Self, Error> {
-        let remaining_len = VarInt::from_reader(reader)?;
-        let compressed_len = VarInt::from_reader(reader)?;
-        if compressed_len.value() == 0 {
-            // Packet is not compressed.
-            Self::from_reader_internal(
-                reader,
-                VarInt::from_value(
-                    remaining_len.value() -
-                    compressed_len.read_size().unwrap() as i32
-                )?
-            )
-        }
-        else {
-            // Packet is compressed. Grab all data...
-            let mut packet_data = vec![0x00; remaining_len.value() as usize - compressed_len.read_size().unwrap() as usize];
-            reader.read_exact(&mut packet_data)?;
-            // Add a decoding wrapper...
-            let mut decoded =
-                flate2::bufread::ZlibDecoder::new(packet_data.as_ref());
-            
-            // And interpret the packet. Also return.
-            Self::from_reader_internal(
-                &mut decoded,
-                VarInt::from_value(
-                    remaining_len.value() -
-                    compressed_len.read_size().unwrap() as i32
-                )?
-            )
-        }
+        PacketBody::new_com(reader)?.parse(Self::from_reader_internal)
     }
     /// Not done! Please wait for this to be finished or open a PR!
     #[cfg(feature = "encryption")]
@@ -353,7 +274,7 @@ impl ClientboundPacket {
         match self {
             Self::Disconnect { reason } => {
                 // Packet ID
-                bytes.append(&mut VarInt::from_value(0x00)?.to_bytes()?);
+                VarInt::new(0x00).encode_into(&mut bytes);
 
                 // Payload
                 // TODO: this may need cesu8 conversion?
@@ -364,17 +285,17 @@ impl ClientboundPacket {
                 should_authenticate
             } => {
                 // Packet ID
-                bytes.append(&mut VarInt::from_value(0x01)?.to_bytes()?);
+                VarInt::new(0x01).encode_into(&mut bytes);
 
                 // Payload
                 // Server ID
                 assert!(server_id.chars().count() <= 20);
                 bytes.append(&mut string_to_bytes_no_cesu8(server_id.clone())?);
                 // Public Key
-                bytes.append(&mut VarInt::from_value(public_key.len() as i32)?.to_bytes()?);
+                VarInt::new(public_key.len() as i32).encode_into(&mut bytes);
                 bytes.append(&mut public_key.clone());
                 // Verify Token
-                bytes.append(&mut VarInt::from_value(verify_token.len() as i32)?.to_bytes()?);
+                VarInt::new(verify_token.len() as i32).encode_into(&mut bytes);
                 bytes.append(&mut verify_token.clone());
                 // Should Authenticate
                 bytes.push(if *should_authenticate { 0x01 } else { 0x00 });
@@ -384,7 +305,7 @@ impl ClientboundPacket {
                 strict_error_handling
             } => {
                 // Packet ID
-                bytes.append(&mut VarInt::from_value(0x02)?.to_bytes()?);
+                VarInt::new(0x02).encode_into(&mut bytes);
 
                 // Payload
                 // UUID
@@ -394,7 +315,7 @@ impl ClientboundPacket {
                 bytes.append(&mut string_to_bytes_no_cesu8(username.clone())?);
 
                 // Properties len
-                bytes.append(&mut VarInt::from_value(properties.len() as i32)?.to_bytes()?);
+                VarInt::new(properties.len() as i32).encode_into(&mut bytes);
                 // Properties
                 for property in properties {
                     assert!(property.name.chars().count() <= 32767);
@@ -416,7 +337,7 @@ impl ClientboundPacket {
             }
             Self::SetCompression { threshold } => {
                 // Packet ID
-                bytes.append(&mut VarInt::from_value(0x03)?.to_bytes()?);
+                VarInt::new(0x03).encode_into(&mut bytes);
 
                 // Payload
                 bytes.append(&mut threshold.to_bytes()?);
@@ -425,7 +346,7 @@ impl ClientboundPacket {
                 message_id, channel, data
             } => {
                 // Packet ID
-                bytes.append(&mut VarInt::from_value(0x04)?.to_bytes()?);
+                VarInt::new(0x04).encode_into(&mut bytes);
 
                 // Payload
                 // Message ID
@@ -439,7 +360,7 @@ impl ClientboundPacket {
             }
             Self::CookieRequest { key } => {
                 // Packet ID
-                bytes.append(&mut VarInt::from_value(0x05)?.to_bytes()?);
+                VarInt::new(0x05).encode_into(&mut bytes);
 
                 // Payload
                 bytes.append(&mut key.to_bytes()?);
@@ -452,67 +373,24 @@ impl ClientboundPacket {
     /// client using this protocol version.
     pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
         let mut packet_bytes = self.to_most_bytes()?;
-        // Calculate packet length, prepend, and send it!
-        let packet_length = packet_bytes.len();
-        let mut result = VarInt::from_value(packet_length as i32)?.to_bytes()?;
-        result.append(&mut packet_bytes);
-
-        Ok(result)
+        // Prepend the packet length and send it!
+        Ok(with_length_prefix(|buf| buf.append(&mut packet_bytes)))
     }
     /// Converts this packet into bytes that can be sent over the network to a
     /// client using this protocol version, once compression has been enabled.
     /// Only use this method after sending
     /// [crate::netty::login::ClientboundPacket::SetCompression]. Even if a
     /// packet isn't encrypted, the format is slightly different.
-    // TODO: test that this is compliant and works
     pub fn to_bytes_com(&self, threshold: VarInt) -> Result<Vec<u8>, Error> {
-        // Get packet data.
-        let mut packet_bytes = self.to_most_bytes()?;
-        // Calculate packet length.
-        let packet_length = packet_bytes.len();
-
-        // If it's below the packet compression threshold,
-        if packet_length < threshold.value() as usize {
-            // Prepend length and send it off!
-            // We add 1 to `packet_length` to account for the compression length.
-            // (which is zero, but encodes as one byte)
-            let mut result = VarInt::from_value(packet_length as i32 + 1)?.to_bytes()?;
-            // Insert the compression length (0)
-            result.push(0x00);
-            // Add the rest of the packet
-            result.append(&mut packet_bytes);
-
-            Ok(result)
-        }
-        else {
-            // Otherwise, we need to compress the packet.
-            use std::io::prelude::*;
-            use flate2::Compression;
-            use flate2::write::ZlibEncoder;
-            // TODO: allow the user to select the compression type.
-            let mut encoder = ZlibEncoder::new(Vec::new(), Compression::fast());
-            // TODO: be more specific with the errors coming off of these `?`s.
-            encoder.write_all(&packet_bytes)?;
-            let mut compressed_data = encoder.finish()?;
-
-            // Put the length of the compressed section of the packet into this VarInt
-            let mut compressed_data_length = VarInt::from_value(compressed_data.len() as i32)?;
-            compressed_data_length.calculate_read_size();
-
-            // Prepend the value of (compressed data length + compressed data
-            // length length).
-            // Safe unwrap, since we just did `.calculate_read_size()`.
-            let mut result = VarInt::from_value(
-                compressed_data_length.value() +
-                compressed_data_length.read_size().unwrap() as i32
-            )?.to_bytes()?;
-            // Prepend compressed data length
-            result.append(&mut compressed_data_length.to_bytes()?);
-            // Add the rest of the packet
-            result.append(&mut compressed_data);
-
-            Ok(result)
-        }
+        self.to_bytes_com_with(threshold, &mut Compressor::new())
+    }
+    /// Equivalent to `to_bytes_com`, but reuses the given [Compressor]
+    /// instead of setting up a new one, which is faster when sending many
+    /// packets.
+    pub fn to_bytes_com_with(
+        &self, threshold: VarInt, compressor: &mut Compressor
+    ) -> Result<Vec<u8>, Error> {
+        compressor.frame(self.to_most_bytes()?, threshold)
     }
     /// Not done! Please wait for this to be finished or open a PR!
     #[cfg(feature = "encryption")]
@@ -525,11 +403,9 @@ impl ClientboundPacket {
         todo!()
     }
     pub fn from_reader<R: Read>(reader: &mut R) -> Result<Self, Error> {
-        let packet_length = VarInt::from_reader(reader)?;
-        
-        Self::from_reader_internal(reader, packet_length)
+        PacketBody::new(reader)?.parse(Self::from_reader_internal)
     }
-    fn from_reader_internal<R: Read>(reader: &mut R, packet_length: VarInt) -> Result<Self, Error> {
+    fn from_reader_internal<R: Read>(reader: &mut R) -> Result<Self, Error> {
         let packet_id = VarInt::from_reader(reader)?;
         match packet_id.value() {
             0x00 => {
@@ -586,17 +462,7 @@ impl ClientboundPacket {
             0x04 => {
                 let message_id = VarInt::from_reader(reader)?;
                 let channel = Identifier::from_reader(reader)?;
-                // These unwraps are safe: we just pulled this data and know it
-                // must have a read size value!
-                let data_len = 
-                    packet_length.value() as usize -
-                    packet_id.read_size().unwrap() as usize -
-                    message_id.read_size().unwrap() as usize -
-                    channel.to_bytes()?.len();
-                
-                let mut data = vec![0x00; data_len];
-
-                reader.read_exact(&mut data)?;
+                let data = read_remaining(reader)?;
 
                 Ok(Self::LoginPluginRequest { message_id, channel, data })
             }
@@ -618,39 +484,8 @@ impl ClientboundPacket {
     /// this method after sending
     /// [crate::netty::login::ClientboundPacket::SetCompression]. Even if a
     /// packet isn't encrypted, the format is slightly different.
-    // TODO: test that this is compliant and works. This is pretty gross, could
-    // use some cleanup too.
     pub fn from_reader_com<R: Read>(reader: &mut R) -> Result<Self, Error> {
-        let remaining_len = VarInt::from_reader(reader)?;
-        let compressed_len = VarInt::from_reader(reader)?;
-        if compressed_len.value() == 0 {
-            // Packet is not compressed. Return whatever standard packet parsing
-            // can gather.
-            Self::from_reader_internal(
-                reader,
-                VarInt::from_value(
-                    remaining_len.value() -
-                    compressed_len.read_size().unwrap() as i32
-                )?
-            )
-        }
-        else {
-            // Packet is compressed. Grab all data...
-            let mut packet_data = vec![0x00; remaining_len.value() as usize - compressed_len.read_size().unwrap() as usize];
-            reader.read_exact(&mut packet_data)?;
-            // Add a decoding wrapper...
-            let mut decoded =
-                flate2::bufread::ZlibDecoder::new(packet_data.as_ref());
-
-            // And interpret the packet. Also return it.
-            Self::from_reader_internal(
-                &mut decoded,
-                VarInt::from_value(
-                    remaining_len.value() -
-                    compressed_len.read_size().unwrap() as i32
-                )?
-            )
-        }
+        PacketBody::new_com(reader)?.parse(Self::from_reader_internal)
     }
     /// Not done! Please wait for this to be finished or open a PR!
     #[cfg(feature = "encryption")]
@@ -658,3 +493,100 @@ impl ClientboundPacket {
         todo!()
     }
 }
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+/// The result of a successful login, as sent by the server in
+/// [ClientboundPacket::LoginSuccess].
+pub struct LoginSuccess {
+    pub uuid: UUID,
+    pub username: String,
+    pub properties: Vec<Property>,
+    pub strict_error_handling: bool,
+    /// The compression threshold the server enabled during login, if any. All
+    /// packets after login must use the `_com` methods with this threshold.
+    pub compression_threshold: Option<VarInt>
+}
+
+/// Logs in to a server as a client, starting right after the handshake. Sends
+/// [ServerboundPacket::LoginStart], enables compression if the server asks for
+/// it, declines any plugin or cookie requests, and finishes by sending
+/// [ServerboundPacket::LoginAcknowledged]. After this returns, the connection
+/// is in the "configuration" stage.
+/// 
+/// Encryption isn't finished yet, so only offline mode is supported and
+/// `access_token` must be `None`. Passing an access token, or connecting to a
+/// server that sends [ClientboundPacket::EncryptionRequest], returns
+/// [Error::EncryptionUnsupported]. Once encryption is supported, the access
+/// token will be used to authenticate with Mojang.
+pub fn perform_login<S: Read + std::io::Write>(
+    stream: &mut S, username: &str, access_token: Option<&str>, uuid: UUID
+) -> Result<LoginSuccess, Error> {
+    // Logging in without authenticating would quietly fall back to offline
+    // mode, which isn't what someone passing a token wants.
+    if access_token.is_some() {
+        return Err(Error::EncryptionUnsupported);
+    }
+    let mut compression_threshold: Option<VarInt> = None;
+
+    send_packet(stream, ServerboundPacket::LoginStart { name: username.to_string(), uuid }, None)?;
+    loop {
+        let packet = match compression_threshold {
+            Some(_) => ClientboundPacket::from_reader_com(stream)?,
+            None => ClientboundPacket::from_reader(stream)?
+        };
+        match packet {
+            ClientboundPacket::Disconnect { reason } => {
+                return Err(Error::Disconnected(reason));
+            }
+            ClientboundPacket::EncryptionRequest { .. } => {
+                return Err(Error::EncryptionUnsupported);
+            }
+            ClientboundPacket::SetCompression { threshold } => {
+                // A negative threshold turns compression off.
+                compression_threshold = if threshold.value() < 0 {
+                    None
+                }
+                else {
+                    Some(threshold)
+                };
+            }
+            ClientboundPacket::LoginPluginRequest { message_id, .. } => {
+                send_packet(
+                    stream,
+                    ServerboundPacket::LoginPluginResponse { message_id, data: None },
+                    compression_threshold
+                )?;
+            }
+            ClientboundPacket::CookieRequest { key } => {
+                send_packet(
+                    stream,
+                    ServerboundPacket::CookieResponse { key, payload: None },
+                    compression_threshold
+                )?;
+            }
+            ClientboundPacket::LoginSuccess {
+                uuid, username, properties, strict_error_handling
+            } => {
+                send_packet(stream, ServerboundPacket::LoginAcknowledged, compression_threshold)?;
+
+                return Ok(LoginSuccess {
+                    uuid, username, properties, strict_error_handling,
+                    compression_threshold
+                });
+            }
+        }
+    }
+}
+
+fn send_packet<W: std::io::Write>(
+    writer: &mut W, packet: ServerboundPacket, compression_threshold: Option<VarInt>
+) -> Result<(), Error> {
+    let bytes = match compression_threshold {
+        Some(threshold) => packet.to_bytes_com(threshold)?,
+        None => packet.to_bytes()?
+    };
+    match writer.write_all(&bytes) {
+        Ok(_) => Ok(()),
+        Err(e) => Err(Error::WriterError(e))
+    }
+}
@@ -30,9 +30,19 @@ pub mod login;
 /// sofrtware during the "configuration" stage of a connection.
 pub mod configuration;
 
+/// Packets for communicating with traditional Minecraft software during the
+/// "play" stage of a connection.
+/// 
+/// Only a few packets are supported so far: enough to move around, respond to
+/// teleports, and spawn entities.
+pub mod play;
+
+use crate::{Identifier, VarInt};
+
 
 /// Represents all the packets that may be sent to the server at various stages
 /// of a client-server interaction.
+#[derive(Clone, PartialEq, Debug)]
 pub enum ServerboundPacket {
     /// Serverbound packets immediately following the start of a connection.
     /// Known as the "handshake" stage.
@@ -41,13 +51,25 @@ pub enum ServerboundPacket {
     Status(status::ServerboundPacket),
     /// Serverbound packets if a client requests to switch to the "login" stage.
     Login(login::ServerboundPacket),
+    /// Serverbound packets after logging in, before joining the game. Known as
+    /// the "configuration" stage.
+    Configuration(configuration::ServerboundPacket),
+    /// Serverbound packets once the client has joined the game. Known as the
+    /// "play" stage.
+    Play(play::ServerboundPacket),
 }
 
 /// Represents all the packets that may be sent to the client at various stages
 /// of a client-server interaction.
+#[derive(Clone, PartialEq, Debug)]
+// Packets from each stage differ wildly in size, and boxing them would make
+// matching on packets far more annoying.
+#[allow(clippy::large_enum_variant)]
 pub enum ClientboundPacket {
     Status(status::ClientboundPacket),
     Login(login::ClientboundPacket),
+    Configuration(configuration::ClientboundPacket),
+    Play(play::ClientboundPacket),
 }
 
 impl ClientboundPacket {
@@ -68,26 +90,583 @@ impl ClientboundPacket {
                     login::ClientboundPacket::from_reader(reader)?
                 ))
             }
-            _ => todo!()
+            ProtocolState::Configuration => {
+                Ok(ClientboundPacket::Configuration(
+                    configuration::ClientboundPacket::from_reader(reader)?
+                ))
+            }
+            ProtocolState::Play => {
+                Ok(ClientboundPacket::Play(
+                    play::ClientboundPacket::from_reader(reader)?
+                ))
+            }
         }
     }
     pub fn from_reader_com<R: std::io::Read>(
         reader: &mut R, protocol_state: ProtocolState
     ) -> Result<Self, crate::Error> {
         match protocol_state {
-            ProtocolState::Handshake | ProtocolState::Status => {
-                panic!("It's not possible for packets to be compressed during these stages of networking!");
+            ProtocolState::Handshake => {
+                Err(crate::Error::NoClientboundHandshake)
+            },
+            // Compression can't be enabled until the "login" stage.
+            ProtocolState::Status => {
+                Err(crate::Error::InvalidProtocolState(protocol_state))
             },
             ProtocolState::Login => {
                 Ok(ClientboundPacket::Login(
                     login::ClientboundPacket::from_reader_com(reader)?
                 ))
             }
-            _ => todo!()
+            ProtocolState::Configuration => {
+                Ok(ClientboundPacket::Configuration(
+                    configuration::ClientboundPacket::from_reader_com(reader)?
+                ))
+            }
+            ProtocolState::Play => {
+                Ok(ClientboundPacket::Play(
+                    play::ClientboundPacket::from_reader_com(reader)?
+                ))
+            }
         }
     }
 }
 
+/// Wraps a [std::io::Read] type with an internal buffer so that packets can be
+/// pulled out one at a time, regardless of how the bytes actually arrive.
+/// 
+/// A single read from a socket may contain several packets (for example, the
+/// status response and ping response are often sent back-to-back) or only part
+/// of one. Raw sockets hide this because the OS keeps the leftovers buffered,
+/// but other readers like a `&[u8]` holding a captured exchange do not. This
+/// type makes the framing explicit: it only ever hands out complete packets and
+/// keeps anything after them for the next call.
+pub struct BufferedConnection<R: std::io::Read> {
+    reader: R,
+    buffer: Vec<u8>
+}
+
+impl<R: std::io::Read> BufferedConnection<R> {
+    /// Creates a new, empty `BufferedConnection` around a [std::io::Read] type.
+    pub fn new(reader: R) -> Self {
+        BufferedConnection { reader, buffer: vec![] }
+    }
+    /// Returns the bytes of the next complete packet, including its length
+    /// prefix. Only reads from the underlying reader when the buffer doesn't
+    /// already hold a full packet.
+    pub fn next_frame(&mut self) -> Result<Vec<u8>, crate::Error> {
+        loop {
+            if let Some(frame_length) = frame_length(&self.buffer)? {
+                let rest = self.buffer.split_off(frame_length);
+                return Ok(std::mem::replace(&mut self.buffer, rest));
+            }
+            self.fill_buffer()?;
+        }
+    }
+    /// Reads the next complete packet sent to the client. Packets that arrived
+    /// alongside this one stay buffered for the next call.
+    pub fn next_clientbound(
+        &mut self, protocol_state: ProtocolState
+    ) -> Result<ClientboundPacket, crate::Error> {
+        let frame = self.next_frame()?;
+
+        ClientboundPacket::from_reader(&mut frame.as_slice(), protocol_state)
+    }
+    /// Returns any bytes that have been read from the underlying reader but not
+    /// yet handed out as part of a packet.
+    pub fn buffered(&self) -> &[u8] {
+        &self.buffer
+    }
+    /// Gets a reference to the underlying reader.
+    pub fn get_ref(&self) -> &R {
+        &self.reader
+    }
+    /// Gets a mutable reference to the underlying reader. Reading from it
+    /// directly will skip over any buffered data.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.reader
+    }
+    /// Unwraps this `BufferedConnection`, returning the underlying reader. Any
+    /// buffered data is lost.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+    fn fill_buffer(&mut self) -> Result<(), crate::Error> {
+        let mut chunk = [0; 4096];
+        loop {
+            match self.reader.read(&mut chunk) {
+                Ok(0) => {
+                    return Err(crate::Error::ConnectionClosed);
+                }
+                Ok(read) => {
+                    self.buffer.extend_from_slice(&chunk[..read]);
+                    return Ok(());
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(e) => {
+                    return Err(crate::Error::ReaderError(e));
+                }
+            }
+        }
+    }
+}
+
+/// Decodes packets sent to the client from bytes pushed in as they arrive,
+/// without ever blocking. This is the non-blocking counterpart to
+/// [BufferedConnection], meant for event loops that do their own I/O.
+/// 
+/// The decoder keeps track of the protocol state and compression threshold by
+/// watching the packets it decodes: a
+/// [login::ClientboundPacket::SetCompression] turns on compression, and a
+/// [login::ClientboundPacket::LoginSuccess] moves on to the "configuration"
+/// stage. Use [PacketDecoder::set_protocol_state] for any other changes.
+pub struct PacketDecoder {
+    buffer: Vec<u8>,
+    protocol_state: ProtocolState,
+    compression_threshold: Option<VarInt>
+}
+
+impl PacketDecoder {
+    /// Creates a new, empty `PacketDecoder` starting in the given protocol
+    /// state, with compression disabled.
+    pub fn new(protocol_state: ProtocolState) -> Self {
+        PacketDecoder { buffer: vec![], protocol_state, compression_threshold: None }
+    }
+    /// Adds newly received bytes to the end of the internal buffer.
+    pub fn push(&mut self, data: &[u8]) {
+        self.buffer.extend_from_slice(data);
+    }
+    /// Decodes the next packet if all of it has been pushed, or returns
+    /// `Ok(None)` if more data is needed first. Any bytes after the packet
+    /// stay buffered for the next call.
+    pub fn next_packet(&mut self) -> Result<Option<ClientboundPacket>, crate::Error> {
+        let Some(frame_length) = frame_length(&self.buffer)? else {
+            return Ok(None);
+        };
+        let rest = self.buffer.split_off(frame_length);
+        let frame = std::mem::replace(&mut self.buffer, rest);
+        // Packets during the "status" stage are never compressed.
+        let compressed = self.compression_threshold.is_some()
+            && self.protocol_state != ProtocolState::Status;
+        let packet = if compressed {
+            ClientboundPacket::from_reader_com(&mut frame.as_slice(), self.protocol_state)?
+        }
+        else {
+            ClientboundPacket::from_reader(&mut frame.as_slice(), self.protocol_state)?
+        };
+
+        match &packet {
+            ClientboundPacket::Login(login::ClientboundPacket::SetCompression { threshold }) => {
+                // A negative threshold disables compression.
+                self.compression_threshold =
+                    if threshold.value() < 0 { None } else { Some(*threshold) };
+            }
+            ClientboundPacket::Login(login::ClientboundPacket::LoginSuccess { .. }) => {
+                self.protocol_state = ProtocolState::Configuration;
+            }
+            _ => {}
+        }
+
+        Ok(Some(packet))
+    }
+    /// Returns the protocol state packets are currently decoded in.
+    pub fn protocol_state(&self) -> ProtocolState {
+        self.protocol_state
+    }
+    /// Changes the protocol state used to decode the following packets.
+    pub fn set_protocol_state(&mut self, protocol_state: ProtocolState) {
+        self.protocol_state = protocol_state;
+    }
+    /// Returns the current compression threshold, or `None` if compression is
+    /// disabled.
+    pub fn compression_threshold(&self) -> Option<VarInt> {
+        self.compression_threshold
+    }
+    /// Enables compression with the given threshold, or disables it with
+    /// `None`. Packets in the "status" stage are never compressed, so they
+    /// are decoded the same either way.
+    pub fn set_compression_threshold(&mut self, threshold: Option<VarInt>) {
+        self.compression_threshold = threshold;
+    }
+    /// Returns any bytes that have been pushed but not yet decoded as part of
+    /// a packet.
+    pub fn buffered(&self) -> &[u8] {
+        &self.buffer
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+/// The tags for one registry, as sent in the "Update Tags" packet during both
+/// the "configuration" and "play" stages. Each tag, like
+/// `minecraft:mineable/pickaxe`, names a list of entries in the registry by
+/// their numeric registry IDs.
+pub struct Tags {
+    /// The registry these tags belong to, like `minecraft:block`.
+    pub registry: Identifier,
+    /// Each tag's name and the IDs of the entries it contains.
+    pub tags: Vec<(Identifier, Vec<VarInt>)>
+}
+
+impl Tags {
+    /// Reads the tags for one registry from a Read type.
+    pub fn from_reader<R: std::io::Read>(reader: &mut R) -> Result<Tags, crate::Error> {
+        let registry = Identifier::from_reader(reader)?;
+        let tag_count = array_length(reader)?;
+        let mut tags = vec![];
+        for _ in 0..tag_count {
+            let name = Identifier::from_reader(reader)?;
+            let entry_count = array_length(reader)?;
+            let mut entries = vec![];
+            for _ in 0..entry_count {
+                entries.push(VarInt::from_reader(reader)?);
+            }
+            tags.push((name, entries));
+        }
+
+        Ok(Tags { registry, tags })
+    }
+    /// Converts the tags for this registry into a series of bytes.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, crate::Error> {
+        let mut bytes = self.registry.to_bytes()?;
+        VarInt::new(self.tags.len() as i32).encode_into(&mut bytes);
+        for (name, entries) in &self.tags {
+            bytes.append(&mut name.to_bytes()?);
+            VarInt::new(entries.len() as i32).encode_into(&mut bytes);
+            for entry in entries {
+                entry.encode_into(&mut bytes);
+            }
+        }
+
+        Ok(bytes)
+    }
+    /// Returns the IDs of the entries in the given tag, or `None` if this
+    /// registry has no such tag.
+    pub fn get(&self, tag: &Identifier) -> Option<&[VarInt]> {
+        self.tags.iter()
+            .find(|(name, _)| name == tag)
+            .map(|(_, entries)| entries.as_slice())
+    }
+}
+
+/// Reads the VarInt count in front of an array, rejecting negative counts.
+fn array_length<R: std::io::Read>(reader: &mut R) -> Result<i32, crate::Error> {
+    let length = VarInt::from_reader(reader)?;
+    if length.value() < 0 {
+        return Err(crate::Error::InvalidArrayLength(length));
+    }
+
+    Ok(length.value())
+}
+
+/// How long the Notchian server and client wait for a Keep Alive before
+/// giving up on a connection.
+pub const KEEP_ALIVE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Tracks Keep Alive packets to tell when the other side of a connection has
+/// stopped responding.
+/// 
+/// There is no stateful connection type in this crate yet, so this is kept
+/// separate: feed it each Keep Alive as it arrives and check
+/// [KeepAliveTracker::is_timed_out] periodically.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct KeepAliveTracker {
+    timeout: std::time::Duration,
+    last_activity: std::time::Instant,
+    last_received: Option<i64>,
+    last_sent: Option<i64>
+}
+
+impl KeepAliveTracker {
+    /// Creates a tracker using [KEEP_ALIVE_TIMEOUT], counting `now` as the
+    /// last time the other side was heard from.
+    pub fn new(now: std::time::Instant) -> Self {
+        Self::with_timeout(now, KEEP_ALIVE_TIMEOUT)
+    }
+    /// Creates a tracker that times out after `timeout` without a Keep Alive.
+    pub fn with_timeout(now: std::time::Instant, timeout: std::time::Duration) -> Self {
+        KeepAliveTracker { timeout, last_activity: now, last_received: None, last_sent: None }
+    }
+    /// Records a Keep Alive received from the server, and returns the
+    /// matching response that should be sent back. Keep Alives are only sent
+    /// during the "configuration" and "play" stages, which use different
+    /// packets, so the current stage is needed to pick the right one.
+    pub fn respond(
+        &mut self, id: i64, now: std::time::Instant, protocol_state: ProtocolState
+    ) -> Result<ServerboundPacket, crate::Error> {
+        self.received(id, now);
+        let response = match protocol_state {
+            ProtocolState::Configuration => {
+                ServerboundPacket::Configuration(configuration::ServerboundPacket::KeepAlive { id })
+            }
+            ProtocolState::Play => {
+                ServerboundPacket::Play(play::ServerboundPacket::KeepAlive { id })
+            }
+            _ => {
+                return Err(crate::Error::InvalidProtocolState(protocol_state));
+            }
+        };
+        self.sent(id);
+
+        Ok(response)
+    }
+    /// Records a Keep Alive received from the other side.
+    pub fn received(&mut self, id: i64, now: std::time::Instant) {
+        self.last_received = Some(id);
+        self.last_activity = now;
+    }
+    /// Records a Keep Alive sent to the other side.
+    pub fn sent(&mut self, id: i64) {
+        self.last_sent = Some(id);
+    }
+    /// Returns `true` if nothing has been received for longer than the
+    /// timeout.
+    pub fn is_timed_out(&self, now: std::time::Instant) -> bool {
+        now.saturating_duration_since(self.last_activity) > self.timeout
+    }
+    /// Returns the ID of the last Keep Alive received, if any.
+    pub fn last_received(&self) -> Option<i64> {
+        self.last_received
+    }
+    /// Returns the ID of the last Keep Alive sent, if any.
+    pub fn last_sent(&self) -> Option<i64> {
+        self.last_sent
+    }
+}
+
+/// The longest a packet can be, not counting its length prefix. The Notchian
+/// server and client never send anything longer, since the length prefix is
+/// limited to three bytes.
+pub const MAX_PACKET_LENGTH: i32 = (1 << 21) - 1;
+
+/// The longest a compressed packet can be once it's decompressed. The
+/// Notchian server and client reject anything that claims to be longer.
+pub const MAX_DATA_LENGTH: i32 = 1 << 23;
+
+/// Checks that a packet's declared length is neither negative nor longer than
+/// [MAX_PACKET_LENGTH], so it's safe to buffer that many bytes.
+fn checked_packet_length(packet_length: VarInt) -> Result<usize, crate::Error> {
+    if !(0..=MAX_PACKET_LENGTH).contains(&packet_length.value()) {
+        return Err(crate::Error::InvalidPacketLength(packet_length));
+    }
+
+    Ok(packet_length.value() as usize)
+}
+
+/// Reads the VarInt length prefix in front of a packet. Running out of data
+/// before or partway through it means the connection closed between packets.
+pub(crate) fn packet_length<R: std::io::Read>(reader: &mut R) -> Result<VarInt, crate::Error> {
+    match VarInt::from_reader(reader) {
+        Err(crate::Error::MissingData | crate::Error::VarIntTruncated) => {
+            Err(crate::Error::ConnectionClosed)
+        }
+        result => result
+    }
+}
+
+/// A reader over the body of a single packet: everything after the framing,
+/// decompressed if need be. Reads stop at the end of the packet, so the body
+/// can be handed to another parser without any risk of it reading into the
+/// next packet. Fields that run to the end of the packet can be read with
+/// [crate::generalized::read_remaining].
+pub struct PacketBody<'a, R: std::io::Read> {
+    inner: BodyReader<'a, R>
+}
+
+enum BodyReader<'a, R: std::io::Read> {
+    Plain(Frame<'a, R>),
+    Compressed(std::io::Take<flate2::read::ZlibDecoder<Frame<'a, R>>>)
+}
+
+/// The bytes of a single packet after its length prefix. Keeps track of
+/// whether the underlying reader ran out before the packet's declared length,
+/// which means the connection closed partway through it.
+struct Frame<'a, R: std::io::Read> {
+    inner: std::io::Take<&'a mut R>,
+    cut_off: bool
+}
+
+impl<R: std::io::Read> std::io::Read for Frame<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        if read == 0 && !buf.is_empty() && self.inner.limit() > 0 {
+            self.cut_off = true;
+        }
+
+        Ok(read)
+    }
+}
+
+impl<'a, R: std::io::Read> PacketBody<'a, R> {
+    /// Reads a packet's length prefix, returning a reader over the rest of
+    /// the packet.
+    pub fn new(reader: &'a mut R) -> Result<PacketBody<'a, R>, crate::Error> {
+        Ok(PacketBody { inner: BodyReader::Plain(Self::frame(reader)?) })
+    }
+    /// Reads a packet's length prefix and data length, returning a reader
+    /// over the rest of the packet after decompression. Only use this method
+    /// once compression has been enabled.
+    pub fn new_com(reader: &'a mut R) -> Result<PacketBody<'a, R>, crate::Error> {
+        let mut frame = Self::frame(reader)?;
+        let data_length = VarInt::from_reader(&mut frame)?;
+        if !(0..=MAX_DATA_LENGTH).contains(&data_length.value()) {
+            return Err(crate::Error::InvalidPacketLength(data_length));
+        }
+        let inner = if data_length.value() == 0 {
+            // Packet is not compressed.
+            BodyReader::Plain(frame)
+        }
+        else {
+            // Packet is compressed, so read it through a decoding wrapper.
+            let decoder = flate2::read::ZlibDecoder::new(frame);
+            BodyReader::Compressed(std::io::Read::take(decoder, data_length.value() as u64))
+        };
+
+        Ok(PacketBody { inner })
+    }
+    /// Skips any part of the packet that wasn't read, so the next packet
+    /// starts in the right place. Fails with [crate::Error::ConnectionClosed]
+    /// if the connection ended before the end of the packet.
+    pub fn finish(self) -> Result<(), crate::Error> {
+        let mut frame = match self.inner {
+            BodyReader::Plain(frame) => frame,
+            BodyReader::Compressed(decoder) => decoder.into_inner().into_inner()
+        };
+        std::io::copy(&mut frame, &mut std::io::sink())?;
+        if frame.cut_off {
+            return Err(crate::Error::ConnectionClosed);
+        }
+
+        Ok(())
+    }
+    /// Reads the packet with `parse`, then [PacketBody::finish]es it. If the
+    /// connection ended partway through the packet, this fails with
+    /// [crate::Error::ConnectionClosed] whatever `parse` returned. Otherwise,
+    /// errors from `parse` are passed through, even ones caused by a packet
+    /// that ended before all of its fields.
+    pub fn parse<T, F>(mut self, parse: F) -> Result<T, crate::Error>
+    where F: FnOnce(&mut Self) -> Result<T, crate::Error> {
+        let result = parse(&mut self);
+        if self.cut_off() {
+            return Err(crate::Error::ConnectionClosed);
+        }
+        let value = result?;
+        self.finish()?;
+
+        Ok(value)
+    }
+    fn cut_off(&self) -> bool {
+        match &self.inner {
+            BodyReader::Plain(frame) => frame.cut_off,
+            BodyReader::Compressed(decoder) => decoder.get_ref().get_ref().cut_off
+        }
+    }
+    fn frame(reader: &'a mut R) -> Result<Frame<'a, R>, crate::Error> {
+        let packet_length = checked_packet_length(packet_length(reader)?)?;
+
+        Ok(Frame { inner: std::io::Read::take(reader, packet_length as u64), cut_off: false })
+    }
+}
+
+impl<R: std::io::Read> std::io::Read for PacketBody<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match &mut self.inner {
+            BodyReader::Plain(frame) => frame.read(buf),
+            BodyReader::Compressed(decoder) => decoder.read(buf)
+        }
+    }
+}
+
+/// Reusable zlib state for writing compressed packets.
+/// 
+/// Setting up a zlib compressor allocates a few hundred kilobytes, so creating
+/// one for every packet is costly when sending many. The `to_bytes_com_with`
+/// methods take a `Compressor` that is reset and reused between packets
+/// instead. Their output is the same as `to_bytes_com`, which just uses a new
+/// `Compressor` every time.
+pub struct Compressor {
+    compress: flate2::Compress,
+    buffer: Vec<u8>
+}
+
+impl Compressor {
+    /// Creates a new `Compressor` using fast compression, like the Notchian
+    /// server.
+    pub fn new() -> Self {
+        Compressor {
+            compress: flate2::Compress::new(flate2::Compression::fast(), true),
+            buffer: vec![]
+        }
+    }
+    /// Frames the bytes of a packet (its ID and payload) for a connection with
+    /// compression enabled, compressing them if they reach the threshold.
+    pub(crate) fn frame(
+        &mut self, mut packet_bytes: Vec<u8>, threshold: VarInt
+    ) -> Result<Vec<u8>, crate::Error> {
+        let packet_length = packet_bytes.len();
+
+        // If it's below the packet compression threshold, send it as is with
+        // a data length of zero.
+        if packet_length < threshold.value() as usize {
+            return Ok(crate::generalized::with_length_prefix(|buf| {
+                buf.push(0x00);
+                buf.append(&mut packet_bytes);
+            }));
+        }
+
+        // Otherwise, we need to compress the packet.
+        self.compress.reset();
+        self.buffer.clear();
+        loop {
+            self.buffer.reserve(packet_length / 2 + 64);
+            let consumed = self.compress.total_in() as usize;
+            let status = self.compress.compress_vec(
+                &packet_bytes[consumed..], &mut self.buffer, flate2::FlushCompress::Finish
+            ).map_err(std::io::Error::from)?;
+            if status == flate2::Status::StreamEnd {
+                break;
+            }
+        }
+
+        // The data length is the size of the packet before compression.
+        let data_length = VarInt::new(packet_length as i32);
+        Ok(crate::generalized::with_length_prefix(|buf| {
+            buf.reserve(self.buffer.len() + data_length.encoded_len() as usize);
+            data_length.encode_into(buf);
+            buf.extend_from_slice(&self.buffer);
+        }))
+    }
+}
+
+impl Default for Compressor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns the length of the first packet in `data`, including its length
+/// prefix, or `None` if `data` doesn't hold a complete packet yet.
+fn frame_length(data: &[u8]) -> Result<Option<usize>, crate::Error> {
+    let mut cursor = data;
+    let packet_length = match VarInt::from_reader(&mut cursor) {
+        Ok(packet_length) => packet_length,
+        Err(crate::Error::MissingData | crate::Error::VarIntTruncated) => {
+            return Ok(None);
+        }
+        Err(e) => {
+            return Err(e);
+        }
+    };
+    let prefix_length = data.len() - cursor.len();
+    let frame_length = prefix_length + checked_packet_length(packet_length)?;
+
+    if data.len() < frame_length {
+        Ok(None)
+    }
+    else {
+        Ok(Some(frame_length))
+    }
+}
+
 #[derive(Clone, Copy, PartialEq, Eq, Debug, FromPrimitive, ToPrimitive)]
 #[repr(u8)]
 /// Indicates the current section of the network protocol to use.
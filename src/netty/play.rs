@@ -0,0 +1,301 @@
+use crate::{Angle, EntityPosition, Error, VarInt, UUID};
+use crate::enums::{EntityType, SpawnEntityData};
+use crate::generalized::{
+    double_from_reader, double_to_bytes, float_from_reader, float_to_bytes,
+    long_from_reader, long_to_bytes, short_from_reader, short_to_bytes,
+    unsigned_byte_from_reader, with_length_prefix
+};
+use super::{Compressor, PacketBody};
+use std::io::Read;
+
+/// Set in the movement flags byte when the player is standing on the ground.
+const ON_GROUND: u8 = 0x01;
+
+#[derive(Clone, PartialEq, Debug)]
+/// A packet sent from the client to the server during the "play" phase.
+///
+/// Only the packets needed to move around and stay connected are supported
+/// so far. Packet IDs are the ones for protocol 768 (1.21.2 and 1.21.3), which
+/// moved the movement packets up by two from 1.21's 0x1A to 0x1C.
+pub enum ServerboundPacket {
+    /// Confirms that the client moved to where a "Synchronize Player Position"
+    /// packet told it to.
+    ConfirmTeleportation {
+        /// The ID given in the "Synchronize Player Position" packet.
+        teleport_id: VarInt
+    },
+    /// Answers a Keep Alive sent by the server.
+    KeepAlive {
+        /// The ID from the server's Keep Alive.
+        id: i64
+    },
+    /// Updates the player's position.
+    SetPlayerPosition {
+        x: f64,
+        /// The position of the player's feet.
+        y: f64,
+        z: f64,
+        on_ground: bool
+    },
+    /// Updates the player's position and the direction they're looking.
+    SetPlayerPositionAndRotation {
+        x: f64,
+        /// The position of the player's feet.
+        y: f64,
+        z: f64,
+        /// Rotation around the vertical axis, in degrees.
+        yaw: f32,
+        /// Rotation up and down, in degrees. -90 is straight up.
+        pitch: f32,
+        on_ground: bool
+    },
+    /// Updates the direction the player is looking.
+    SetPlayerRotation {
+        /// Rotation around the vertical axis, in degrees.
+        yaw: f32,
+        /// Rotation up and down, in degrees. -90 is straight up.
+        pitch: f32,
+        on_ground: bool
+    }
+}
+
+impl ServerboundPacket {
+    /// Converts this packet into bytes that can be sent over the network to a
+    /// server using this protocol version.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        let mut packet_bytes = self.to_most_bytes()?;
+        // Prepend the packet length and send it!
+        Ok(with_length_prefix(|buf| buf.append(&mut packet_bytes)))
+    }
+    /// Converts the packet to bytes in the proper format for networking with
+    /// traditional Minecraft software *minus* the packet length being prepended.
+    fn to_most_bytes(&self) -> Result<Vec<u8>, Error> {
+        let mut bytes = vec![];
+        match self {
+            Self::ConfirmTeleportation { teleport_id } => {
+                // Packet ID
+                VarInt::new(0x00).encode_into(&mut bytes);
+                // Payload
+                bytes.append(&mut teleport_id.to_bytes()?);
+            }
+            Self::KeepAlive { id } => {
+                // Packet ID
+                VarInt::new(0x1A).encode_into(&mut bytes);
+                // Payload
+                bytes.append(&mut long_to_bytes(*id)?);
+            }
+            Self::SetPlayerPosition { x, y, z, on_ground } => {
+                // Packet ID
+                VarInt::new(0x1C).encode_into(&mut bytes);
+                // Payload
+                bytes.append(&mut double_to_bytes(*x)?);
+                bytes.append(&mut double_to_bytes(*y)?);
+                bytes.append(&mut double_to_bytes(*z)?);
+                bytes.push(movement_flags(*on_ground));
+            }
+            Self::SetPlayerPositionAndRotation { x, y, z, yaw, pitch, on_ground } => {
+                // Packet ID
+                VarInt::new(0x1D).encode_into(&mut bytes);
+                // Payload
+                bytes.append(&mut double_to_bytes(*x)?);
+                bytes.append(&mut double_to_bytes(*y)?);
+                bytes.append(&mut double_to_bytes(*z)?);
+                bytes.append(&mut float_to_bytes(*yaw)?);
+                bytes.append(&mut float_to_bytes(*pitch)?);
+                bytes.push(movement_flags(*on_ground));
+            }
+            Self::SetPlayerRotation { yaw, pitch, on_ground } => {
+                // Packet ID
+                VarInt::new(0x1E).encode_into(&mut bytes);
+                // Payload
+                bytes.append(&mut float_to_bytes(*yaw)?);
+                bytes.append(&mut float_to_bytes(*pitch)?);
+                bytes.push(movement_flags(*on_ground));
+            }
+        }
+
+        Ok(bytes)
+    }
+    /// Converts this packet into bytes that can be sent over the network to a
+    /// server using this protocol version, once compression has been enabled.
+    /// Only use this method after recieving
+    /// [crate::netty::login::ClientboundPacket::SetCompression].
+    pub fn to_bytes_com(&self, threshold: VarInt) -> Result<Vec<u8>, Error> {
+        self.to_bytes_com_with(threshold, &mut Compressor::new())
+    }
+    /// Equivalent to `to_bytes_com`, but reuses the given [Compressor]
+    /// instead of setting up a new one, which is faster when sending many
+    /// packets.
+    pub fn to_bytes_com_with(
+        &self, threshold: VarInt, compressor: &mut Compressor
+    ) -> Result<Vec<u8>, Error> {
+        compressor.frame(self.to_most_bytes()?, threshold)
+    }
+    pub fn from_reader<R: Read>(reader: &mut R) -> Result<Self, Error> {
+        PacketBody::new(reader)?.parse(Self::from_reader_internal)
+    }
+    fn from_reader_internal<R: Read>(reader: &mut R) -> Result<Self, Error> {
+        let packet_id = VarInt::from_reader(reader)?;
+        match packet_id.value() {
+            0x00 => {
+                let teleport_id = VarInt::from_reader(reader)?;
+
+                Ok(ServerboundPacket::ConfirmTeleportation { teleport_id })
+            }
+            0x1A => {
+                let id = long_from_reader(reader)?;
+
+                Ok(ServerboundPacket::KeepAlive { id })
+            }
+            0x1C => {
+                let x = double_from_reader(reader)?;
+                let y = double_from_reader(reader)?;
+                let z = double_from_reader(reader)?;
+                let on_ground = unsigned_byte_from_reader(reader)? & ON_GROUND != 0;
+
+                Ok(ServerboundPacket::SetPlayerPosition { x, y, z, on_ground })
+            }
+            0x1D => {
+                let x = double_from_reader(reader)?;
+                let y = double_from_reader(reader)?;
+                let z = double_from_reader(reader)?;
+                let yaw = float_from_reader(reader)?;
+                let pitch = float_from_reader(reader)?;
+                let on_ground = unsigned_byte_from_reader(reader)? & ON_GROUND != 0;
+
+                Ok(ServerboundPacket::SetPlayerPositionAndRotation { x, y, z, yaw, pitch, on_ground })
+            }
+            0x1E => {
+                let yaw = float_from_reader(reader)?;
+                let pitch = float_from_reader(reader)?;
+                let on_ground = unsigned_byte_from_reader(reader)? & ON_GROUND != 0;
+
+                Ok(ServerboundPacket::SetPlayerRotation { yaw, pitch, on_ground })
+            }
+            _ => { Err(Error::InvalidPacketId(packet_id)) }
+        }
+    }
+    /// Reads a packet from a [Read] type that is sent to a server using this
+    /// protocol version. Expects that compression has been enabled. Only use
+    /// this method after recieving
+    /// [crate::netty::login::ClientboundPacket::SetCompression].
+    pub fn from_reader_com<R: Read>(reader: &mut R) -> Result<Self, Error> {
+        PacketBody::new_com(reader)?.parse(Self::from_reader_internal)
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+/// A packet sent from the server to the client during the "play" phase.
+///
+/// Only spawning entities is supported so far.
+pub enum ClientboundPacket {
+    /// Adds a non-player entity to the world.
+    SpawnEntity {
+        entity_id: VarInt,
+        entity_uuid: UUID,
+        entity_type: EntityType,
+        position: EntityPosition,
+        pitch: Angle,
+        yaw: Angle,
+        /// Only used by living entities.
+        head_yaw: Angle,
+        data: SpawnEntityData,
+        /// In 8000ths of a block per tick, for each of x, y, and z.
+        velocity: (i16, i16, i16)
+    }
+}
+
+impl ClientboundPacket {
+    /// Converts this packet into bytes that can be sent over the network to a
+    /// client using this protocol version.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        let mut packet_bytes = self.to_most_bytes()?;
+        // Prepend the packet length and send it!
+        Ok(with_length_prefix(|buf| buf.append(&mut packet_bytes)))
+    }
+    /// Converts the packet to bytes in the proper format for networking with
+    /// traditional Minecraft software *minus* the packet length being prepended.
+    fn to_most_bytes(&self) -> Result<Vec<u8>, Error> {
+        let mut bytes = vec![];
+        match self {
+            Self::SpawnEntity {
+                entity_id, entity_uuid, entity_type, position,
+                pitch, yaw, head_yaw, data, velocity
+            } => {
+                // Packet ID
+                VarInt::new(0x01).encode_into(&mut bytes);
+                // Payload
+                bytes.append(&mut entity_id.to_bytes()?);
+                bytes.append(&mut entity_uuid.to_bytes()?);
+                VarInt::new(*entity_type as i32).encode_into(&mut bytes);
+                bytes.append(&mut position.to_bytes()?);
+                bytes.append(&mut pitch.to_bytes()?);
+                bytes.append(&mut yaw.to_bytes()?);
+                bytes.append(&mut head_yaw.to_bytes()?);
+                VarInt::new(data.to_data()).encode_into(&mut bytes);
+                bytes.append(&mut short_to_bytes(velocity.0)?);
+                bytes.append(&mut short_to_bytes(velocity.1)?);
+                bytes.append(&mut short_to_bytes(velocity.2)?);
+            }
+        }
+
+        Ok(bytes)
+    }
+    /// Converts this packet into bytes that can be sent over the network to a
+    /// client using this protocol version, once compression has been enabled.
+    /// Only use this method after sending
+    /// [crate::netty::login::ClientboundPacket::SetCompression].
+    pub fn to_bytes_com(&self, threshold: VarInt) -> Result<Vec<u8>, Error> {
+        self.to_bytes_com_with(threshold, &mut Compressor::new())
+    }
+    /// Equivalent to `to_bytes_com`, but reuses the given [Compressor]
+    /// instead of setting up a new one, which is faster when sending many
+    /// packets.
+    pub fn to_bytes_com_with(
+        &self, threshold: VarInt, compressor: &mut Compressor
+    ) -> Result<Vec<u8>, Error> {
+        compressor.frame(self.to_most_bytes()?, threshold)
+    }
+    pub fn from_reader<R: Read>(reader: &mut R) -> Result<Self, Error> {
+        PacketBody::new(reader)?.parse(Self::from_reader_internal)
+    }
+    fn from_reader_internal<R: Read>(reader: &mut R) -> Result<Self, Error> {
+        let packet_id = VarInt::from_reader(reader)?;
+        match packet_id.value() {
+            0x01 => {
+                let entity_id = VarInt::from_reader(reader)?;
+                let entity_uuid = UUID::from_reader(reader)?;
+                let entity_type = EntityType::try_from(VarInt::from_reader(reader)?.value() as u32)?;
+                let position = EntityPosition::from_reader(reader)?;
+                let pitch = Angle::from_reader(reader)?;
+                let yaw = Angle::from_reader(reader)?;
+                let head_yaw = Angle::from_reader(reader)?;
+                let data = SpawnEntityData::from_data(entity_type, VarInt::from_reader(reader)?.value())?;
+                let velocity = (
+                    short_from_reader(reader)?,
+                    short_from_reader(reader)?,
+                    short_from_reader(reader)?
+                );
+
+                Ok(ClientboundPacket::SpawnEntity {
+                    entity_id, entity_uuid, entity_type, position,
+                    pitch, yaw, head_yaw, data, velocity
+                })
+            }
+            _ => { Err(Error::InvalidPacketId(packet_id)) }
+        }
+    }
+    /// Reads a packet from a [Read] type that is sent to a client using this
+    /// protocol version. Expects that compression has been enabled. Only use
+    /// this method after sending
+    /// [crate::netty::login::ClientboundPacket::SetCompression].
+    pub fn from_reader_com<R: Read>(reader: &mut R) -> Result<Self, Error> {
+        PacketBody::new_com(reader)?.parse(Self::from_reader_internal)
+    }
+}
+
+/// Packs the movement flags sent at the end of each movement packet. The
+/// "pushing against a wall" flag is never set.
+fn movement_flags(on_ground: bool) -> u8 {
+    if on_ground { ON_GROUND } else { 0 }
+}
@@ -1,8 +1,10 @@
 use crate::{Chat, Error, VarInt, UUID};
 use crate::generalized::{
     long_from_reader, long_to_bytes, string_from_reader_no_cesu8,
-    string_to_bytes_no_cesu8, string_to_writer_no_cesu8
+    string_to_bytes_no_cesu8, string_to_writer_no_cesu8, with_length_prefix
 };
+use super::PacketBody;
+use serde::{Deserialize, Serialize};
 use std::io::Read;
 
 #[derive(Clone, PartialEq, Eq, Debug)]
@@ -19,6 +21,8 @@ pub enum ServerboundPacket {
 
 #[derive(Clone, PartialEq, Eq, Debug)]
 /// A packet sent from the server to the client during the "status" phase.
+// Boxing the status response would make matching on it far more annoying.
+#[allow(clippy::large_enum_variant)]
 pub enum ClientboundPacket {
     StatusResponse {
         response: StatusResponse
@@ -28,80 +32,109 @@ pub enum ClientboundPacket {
     }
 }
 
-#[derive(Debug, Clone, Eq, PartialEq)]
+#[derive(Debug, Clone, Eq, PartialEq, Default)]
 /// The information returned from a server when querying a server's status.
 pub struct StatusResponse {
-    pub version_name: String,
-    pub version_protocol: i64,
-    pub max_players: i64,
-    pub online_players: i64,
-    pub favicon_data: String,
-    pub sample_players: Vec<(String, UUID)>,
-    pub description: Chat
+    /// The server's game version.
+    pub version: Version,
+    /// How many players can and do play on the server.
+    pub players: Players,
+    /// The server's message of the day.
+    pub description: Chat,
+    /// The server's icon as base64 encoded PNG data, without the `data:image/png;base64,` prefix.
+    /// This is empty if the server doesn't have an icon.
+    pub favicon_data: String
 }
 
+#[derive(Debug, Clone, Eq, PartialEq, Default, Deserialize, Serialize)]
+/// The game version a server is running, as part of a [StatusResponse].
+pub struct Version {
+    /// The version's name, like `1.21.3`. Servers may put any text here.
+    pub name: String,
+    /// The protocol version the server uses.
+    pub protocol: i64
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Default, Deserialize, Serialize)]
+/// Player counts and a sample of online players, as part of a [StatusResponse].
+pub struct Players {
+    /// The maximum number of players that can join the server.
+    pub max: i64,
+    /// The number of players currently online.
+    pub online: i64,
+    /// Some of the players currently online. Servers may leave this out or fill it with any text.
+    #[serde(default)]
+    pub sample: Vec<SamplePlayer>
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize)]
+/// A single player listed in [Players::sample].
+pub struct SamplePlayer {
+    /// The player's username.
+    pub name: String,
+    /// The player's UUID. This is sent as a hyphenated string.
+    #[serde(with = "hyphenated_uuid")]
+    pub id: UUID
+}
+
+/// Reads and writes [UUID]s as hyphenated strings, like `069a79f4-44e9-4726-a5be-fca90e38aaf5`.
+mod hyphenated_uuid {
+    use crate::UUID;
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(uuid: &UUID, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&uuid.to_string())
+    }
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<UUID, D::Error> {
+        UUID::from_hyphenated(&String::deserialize(deserializer)?).map_err(de::Error::custom)
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+/// The exact JSON shape of a [StatusResponse].
+struct RawStatusResponse {
+    version: Version,
+    players: Players,
+    description: serde_json::Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    favicon: Option<String>
+}
+
+const FAVICON_PREFIX: &str = "data:image/png;base64,";
+
 impl StatusResponse {
-    // TODO: do this the proper way and not with this crud...
     pub fn from_reader<R: std::io::Read>(reader: &mut R) -> Result<StatusResponse, Error> {
-        let raw_data = string_from_reader_no_cesu8(reader)?;
-        let json_data: serde_json::Value = serde_json::from_str(&raw_data)?;
+        Self::from_string(&string_from_reader_no_cesu8(reader)?)
+    }
+    fn from_string(data: &str) -> Result<StatusResponse, Error> {
+        let raw: RawStatusResponse = serde_json::from_str(data)?;
 
         Ok(StatusResponse {
-            version_name: json_data["version"]["name"].to_string(),
-            version_protocol: json_data["version"]["protocol"].as_i64().ok_or(Error::InvalidJsonRoot)?,
-            max_players: json_data["players"]["max"].as_i64().ok_or(Error::InvalidJsonRoot)?,
-            online_players: json_data["players"]["online"].as_i64().ok_or(Error::InvalidJsonRoot)?,
-            description: Chat::from_string(serde_json::to_string(&json_data["description"])?)?,
+            version: raw.version,
+            players: raw.players,
+            description: Chat::from_string(raw.description.to_string())?,
             favicon_data:
-                json_data["favicon"]
-                    .as_str()
-                    .ok_or(Error::InvalidJsonRoot)?
+                raw.favicon
+                    .unwrap_or_default()
+                    .trim_start_matches(FAVICON_PREFIX)
                     .to_string()
-                    .trim_start_matches("data:image/png;base64,")
-                    .to_string(),
-            sample_players:
-                json_data["players"]["sample"]
-                    .as_array()
-                    .ok_or(Error::InvalidJsonRoot)
-                    .map(|dta| {
-                        let mut final_data = vec![];
-                        for pair in dta {
-                            final_data.push((pair["name"].to_string(), UUID::from_username(pair["id"].to_string()).unwrap()));
-                        }
-
-                        final_data
-                    })?
         })
     }
     fn to_string(&self) -> Result<String, Error> {
-        let mut string_data = String::new();
-        string_data += "{\"version\":{\"name\":\"";
-        string_data += &self.version_name;
-        string_data += "\",\"protocol\":";
-        string_data += &format!("{}", self.version_protocol);
-        string_data += "},\"players\":{\"max\":";
-        string_data += &format!("{}", self.max_players);
-        string_data += ",\"online\":";
-        string_data += &format!("{}", self.online_players);
-        string_data += "\"sample\":[";
-        let mut sample_index = false;
-        for player in self.sample_players.clone() {
-            if sample_index {
-                string_data += ",";
-            }
-            string_data += "{\"name\":\"";
-            string_data += &player.0;
-            string_data += "\",\"id\":\"";
-            string_data += &format!("{:x}", player.1.to_value()?);
-            string_data += "}";
-            sample_index = true;
-        }
-        string_data += "]},\"description\":";
-        string_data += ",\"favicon\":\"data:image/png;base64,";
-        string_data += &self.favicon_data;
-        string_data += "\"}";
-
-        Ok(string_data)
+        let raw = RawStatusResponse {
+            version: self.version.clone(),
+            players: self.players.clone(),
+            description: serde_json::from_str(&self.description.clone().to_string()?)?,
+            favicon:
+                if self.favicon_data.is_empty() {
+                    None
+                }
+                else {
+                    Some(format!("{}{}", FAVICON_PREFIX, self.favicon_data))
+                }
+        };
+
+        Ok(serde_json::to_string(&raw)?)
     }
 
     pub fn to_writer<W: std::io::Write>(&self, writer: &mut W) -> Result<(), Error> {
@@ -112,6 +145,26 @@ impl StatusResponse {
     pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
         string_to_bytes_no_cesu8(self.to_string()?)
     }
+    /// Shorthand for `self.version.name`.
+    pub fn version_name(&self) -> &str {
+        &self.version.name
+    }
+    /// Shorthand for `self.version.protocol`.
+    pub fn version_protocol(&self) -> i64 {
+        self.version.protocol
+    }
+    /// Shorthand for `self.players.max`.
+    pub fn max_players(&self) -> i64 {
+        self.players.max
+    }
+    /// Shorthand for `self.players.online`.
+    pub fn online_players(&self) -> i64 {
+        self.players.online
+    }
+    /// Returns the names and UUIDs of the sampled players, in the order the server listed them.
+    pub fn sample_players(&self) -> Vec<(String, UUID)> {
+        self.players.sample.iter().map(|player| (player.name.clone(), player.id)).collect()
+    }
 }
 
 impl ServerboundPacket {
@@ -120,24 +173,22 @@ impl ServerboundPacket {
         match self {
             Self::StatusRequest => {
                 // Packet ID
-                bytes.append(&mut VarInt::from_value(0x00)?.to_bytes()?);
+                VarInt::new(0x00).encode_into(&mut bytes);
             }
             Self::PingRequest { payload } => {
                 // Packet ID
-                bytes.append(&mut VarInt::from_value(0x01)?.to_bytes()?);
+                VarInt::new(0x01).encode_into(&mut bytes);
                 // Payload
                 bytes.append(&mut long_to_bytes(*payload)?);
             }
         }
-        // Calculate packet length, prepend, and send it!
-        let packet_length = bytes.len();
-        let mut result = VarInt::from_value(packet_length as i32)?.to_bytes()?;
-        result.append(&mut bytes);
-
-        Ok(result)
+        // Prepend the packet length and send it!
+        Ok(with_length_prefix(|buf| buf.append(&mut bytes)))
     }
     pub fn from_reader<R: Read>(reader: &mut R) -> Result<Self, Error> {
-        let _packet_length = VarInt::from_reader(reader)?;
+        PacketBody::new(reader)?.parse(Self::from_reader_internal)
+    }
+    fn from_reader_internal<R: Read>(reader: &mut R) -> Result<Self, Error> {
         let packet_id = VarInt::from_reader(reader)?;
         match packet_id.value() {
             0x00 => Ok(ServerboundPacket::StatusRequest),
@@ -157,26 +208,24 @@ impl ClientboundPacket {
         match self {
             Self::StatusResponse { response } => {
                 // Packet ID
-                bytes.append(&mut VarInt::from_value(0x00)?.to_bytes()?);
+                VarInt::new(0x00).encode_into(&mut bytes);
                 // Payload
                 bytes.append(&mut response.to_bytes()?);
             }
             Self::PingResponse { payload } => {
                 // Packet ID
-                bytes.append(&mut VarInt::from_value(0x01)?.to_bytes()?);
+                VarInt::new(0x01).encode_into(&mut bytes);
                 // Payload
                 bytes.append(&mut long_to_bytes(*payload)?);
             }
         }
-        // Calculate packet length, prepend, and send it!
-        let packet_length = bytes.len();
-        let mut result = VarInt::from_value(packet_length as i32)?.to_bytes()?;
-        result.append(&mut bytes);
-
-        Ok(result)
+        // Prepend the packet length and send it!
+        Ok(with_length_prefix(|buf| buf.append(&mut bytes)))
     }
     pub fn from_reader<R: Read>(reader: &mut R) -> Result<Self, Error> {
-        let _packet_length = VarInt::from_reader(reader)?;
+        PacketBody::new(reader)?.parse(Self::from_reader_internal)
+    }
+    fn from_reader_internal<R: Read>(reader: &mut R) -> Result<Self, Error> {
         let packet_id = VarInt::from_reader(reader)?;
         match packet_id.value() {
             0x00 => {
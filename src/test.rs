@@ -81,17 +81,46 @@ fn position_standard_values() -> Result<(), super::Error> {
     // Check that encoding works properly
     assert_eq!(zeroed.to_bytes()?, [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]);
     assert_eq!(max_value.to_bytes()?, [0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF]);
-    assert_eq!(min_value.to_bytes()?, [0x00, 0x00, 0x06, 0x00, 0x00, 0x01, 0x80, 0x0E]);
+    // Only the lowest 26/12 bits of each coordinate fit, and those are all zero for the minimums.
+    assert_eq!(min_value.to_bytes()?, [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]);
     return Ok(());
 }
 
 #[test]
+fn position_to_writer_matches_to_bytes() -> Result<(), super::Error> {
+    use super::Position;
+
+    let positions = [
+        Position::from_values(0, 0, 0),
+        Position::from_values(-1, -1, -1),
+        Position::from_values(10, 5, -30),
+        Position::from_values(-30_000_000, 319, 30_000_000),
+        Position::from_values(33_554_431, 2047, -33_554_432),
+        Position::from_values(i32::MIN, i16::MIN, i32::MAX)
+    ];
+    for position in positions {
+        let mut written = vec![];
+        position.to_writer(&mut written)?;
+        assert_eq!(written, position.to_bytes()?);
+    }
+    // -1 on every axis sets every bit.
+    assert_eq!(Position::from_values(-1, -1, -1).to_bytes()?, [0xFF; 8]);
+    // 10, 5, -30 as the Notchian server encodes it.
+    assert_eq!(
+        Position::from_values(10, 5, -30).to_bytes()?,
+        [0x00, 0x00, 0x02, 0xBF, 0xFF, 0xFE, 0x20, 0x05]
+    );
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "mojang-api")]
 fn username_api() -> Result<(), super::Error> {
     use super::UUID;
     // Create a UUID from a username
     let uuid = UUID::from_username(String::from("thisjaiden"))?;
     // Test username -> UUID
-    assert_eq!(uuid.clone().to_value()?, 0x09773765901b4da1a1243467f482b8b3);
+    assert_eq!(uuid.value(), 0x09773765901b4da1a1243467f482b8b3);
     // Test UUID -> username
     assert_eq!(uuid.to_username()?, String::from("thisjaiden"));
     return Ok(());
@@ -143,3 +172,2835 @@ fn nbt_mojang_bigtest() -> Result<(), super::Error> {
 
     return Ok(());
 }
+
+#[test]
+fn buffered_connection_back_to_back() -> Result<(), super::Error> {
+    use super::netty::{self, status, BufferedConnection, ProtocolState};
+
+    let mut captured = status::ClientboundPacket::PingResponse { payload: 1 }.to_bytes()?;
+    captured.append(&mut status::ClientboundPacket::PingResponse { payload: 2 }.to_bytes()?);
+
+    let mut connection = BufferedConnection::new(captured.as_slice());
+    for expected in [1, 2] {
+        match connection.next_clientbound(ProtocolState::Status)? {
+            netty::ClientboundPacket::Status(
+                status::ClientboundPacket::PingResponse { payload }
+            ) => assert_eq!(payload, expected),
+            _ => panic!("expected a ping response")
+        }
+    }
+    assert!(connection.buffered().is_empty());
+    assert!(connection.next_frame().is_err());
+    Ok(())
+}
+
+#[test]
+fn buffered_connection_partial_reads() -> Result<(), super::Error> {
+    use super::netty::{status, BufferedConnection};
+
+    // Hands out a single byte per read, like a very slow socket.
+    struct Trickle<'a>(&'a [u8]);
+    impl std::io::Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            if self.0.is_empty() || buf.is_empty() {
+                return Ok(0);
+            }
+            buf[0] = self.0[0];
+            self.0 = &self.0[1..];
+            Ok(1)
+        }
+    }
+
+    let first = status::ClientboundPacket::PingResponse { payload: 12345 }.to_bytes()?;
+    let second = status::ClientboundPacket::PingResponse { payload: -1 }.to_bytes()?;
+    let captured = [first.clone(), second.clone()].concat();
+
+    let mut connection = BufferedConnection::new(Trickle(&captured));
+    assert_eq!(connection.next_frame()?, first);
+    assert_eq!(connection.next_frame()?, second);
+    Ok(())
+}
+
+#[test]
+fn packet_decoder_partial_data() -> Result<(), super::Error> {
+    use super::netty::{self, login, status, PacketDecoder, ProtocolState};
+    use super::{VarInt, UUID};
+
+    let first = status::ClientboundPacket::PingResponse { payload: 12345 }.to_bytes()?;
+    let second = status::ClientboundPacket::PingResponse { payload: -1 }.to_bytes()?;
+
+    let mut decoder = PacketDecoder::new(ProtocolState::Status);
+    assert!(decoder.next_packet()?.is_none());
+    decoder.push(&first[..3]);
+    assert!(decoder.next_packet()?.is_none());
+    decoder.push(&[&first[3..], &second[..1]].concat());
+    assert!(matches!(
+        decoder.next_packet()?,
+        Some(netty::ClientboundPacket::Status(status::ClientboundPacket::PingResponse { payload: 12345 }))
+    ));
+    assert!(decoder.next_packet()?.is_none());
+    decoder.push(&second[1..]);
+    assert!(matches!(
+        decoder.next_packet()?,
+        Some(netty::ClientboundPacket::Status(status::ClientboundPacket::PingResponse { payload: -1 }))
+    ));
+    assert!(decoder.buffered().is_empty());
+
+    let mut decoder = PacketDecoder::new(ProtocolState::Login);
+    decoder.push(&login::ClientboundPacket::SetCompression { threshold: VarInt::from_value(256)? }.to_bytes()?);
+    assert!(decoder.next_packet()?.is_some());
+    assert_eq!(decoder.compression_threshold(), Some(VarInt::from_value(256)?));
+    assert_eq!(decoder.protocol_state(), ProtocolState::Login);
+
+    let mut decoder = PacketDecoder::new(ProtocolState::Login);
+    decoder.push(&login::ClientboundPacket::LoginSuccess {
+        uuid: UUID::from_value(1)?,
+        username: String::from("thisjaiden"),
+        properties: vec![],
+        strict_error_handling: false
+    }.to_bytes()?);
+    assert!(decoder.next_packet()?.is_some());
+    assert_eq!(decoder.protocol_state(), ProtocolState::Configuration);
+    assert_eq!(decoder.compression_threshold(), None);
+    Ok(())
+}
+
+#[test]
+fn nbt_named_tag_constructors() -> Result<(), super::Error> {
+    use super::nbt::{NamedTag, Tag};
+
+    let built = NamedTag::compound("root", vec![
+        NamedTag::new("health", Tag::Float(20.0)),
+        NamedTag::new(String::from("name"), Tag::String("Steve".to_string()))
+    ]);
+    let expected = NamedTag {
+        name: "root".to_string(),
+        tag: Tag::Compound(vec![
+            NamedTag { name: "health".to_string(), tag: Tag::Float(20.0) },
+            NamedTag { name: "name".to_string(), tag: Tag::String("Steve".to_string()) }
+        ])
+    };
+
+    assert_eq!(built, expected);
+    Ok(())
+}
+
+#[test]
+fn string_from_reader_counted_matches_bytes() -> Result<(), super::Error> {
+    use super::generalized::{string_from_reader_counted, string_from_reader_counted_no_cesu8, string_to_bytes};
+
+    let long_text = "golden apple ".repeat(20);
+    for text in ["", "minecraft:stone", long_text.as_str()] {
+        let bytes = string_to_bytes(text.to_string())?;
+        let mut trailing = bytes.clone();
+        trailing.push(0xFF);
+
+        let mut reader = trailing.as_slice();
+        assert_eq!(string_from_reader_counted(&mut reader)?, (text.to_string(), bytes.len()));
+        assert_eq!(reader, &[0xFF]);
+        assert_eq!(
+            string_from_reader_counted_no_cesu8(&mut bytes.as_slice())?,
+            (text.to_string(), bytes.len())
+        );
+    }
+
+    // Length prefixes padded out with extra bytes still count every byte.
+    let mut reader = &[0x80, 0x00, 0xFF][..];
+    assert_eq!(string_from_reader_counted(&mut reader)?, (String::new(), 2));
+    assert_eq!(reader, &[0xFF]);
+    let padded = [0x83, 0x80, 0x00, b'a', b'b', b'c'];
+    assert_eq!(string_from_reader_counted(&mut &padded[..])?, (String::from("abc"), 6));
+    assert_eq!(string_from_reader_counted_no_cesu8(&mut &padded[..])?, (String::from("abc"), 6));
+    Ok(())
+}
+
+#[test]
+fn particle_read_data() -> Result<(), super::Error> {
+    use super::enums::{Item, Particle, ParticleData, SkulkVibrationDestination};
+    use super::{Slot, VarInt};
+
+    let mut data = vec![];
+    data.append(&mut VarInt::from_value(42)?.to_bytes()?);
+    assert_eq!(
+        Particle::Block.read_data(&mut data.as_slice())?,
+        ParticleData::BlockState(VarInt::from_value(42)?)
+    );
+
+    let mut data = vec![];
+    data.extend_from_slice(&0xFF0000i32.to_be_bytes());
+    data.extend_from_slice(&1.5f32.to_be_bytes());
+    assert_eq!(
+        Particle::Dust.read_data(&mut data.as_slice())?,
+        ParticleData::Dust { color: 0xFF0000, scale: 1.5 }
+    );
+
+    let slot = Slot { item_count: 3, item: Some(Item::Stone) };
+    assert_eq!(
+        Particle::Item.read_data(&mut slot.to_bytes()?.as_slice())?,
+        ParticleData::Item(slot)
+    );
+
+    let mut data = vec![];
+    data.append(&mut VarInt::from_value(1)?.to_bytes()?);
+    data.append(&mut VarInt::from_value(7)?.to_bytes()?);
+    data.extend_from_slice(&1.62f32.to_be_bytes());
+    data.append(&mut VarInt::from_value(20)?.to_bytes()?);
+    assert_eq!(
+        Particle::Vibration.read_data(&mut data.as_slice())?,
+        ParticleData::Vibration {
+            destination: SkulkVibrationDestination::EntityID(VarInt::from_value(7)?),
+            entity_eye_height: Some(1.62),
+            ticks: VarInt::from_value(20)?
+        }
+    );
+
+    // Particles without extra data shouldn't consume anything.
+    let mut reader: &[u8] = &[0xFF];
+    assert_eq!(Particle::Flame.read_data(&mut reader)?, ParticleData::None);
+    assert_eq!(reader, &[0xFF]);
+    Ok(())
+}
+
+#[test]
+fn nbt_tag_iteration() -> Result<(), super::Error> {
+    use super::nbt::{NamedTag, Tag};
+
+    let list = Tag::List(vec![Tag::Int(1), Tag::Int(2), Tag::Int(3)]);
+    let compound = Tag::Compound(vec![
+        NamedTag::new("a", Tag::Byte(1)),
+        NamedTag::new("b", Tag::Byte(2))
+    ]);
+
+    assert_eq!(list.iter_list().count(), 3);
+    assert_eq!(list[1], Tag::Int(2));
+    let names: Vec<&str> = compound.iter_compound().map(|child| child.name.as_str()).collect();
+    assert_eq!(names, ["a", "b"]);
+
+    // Wrong variants iterate as empty.
+    assert_eq!(list.iter_compound().count(), 0);
+    assert_eq!(compound.iter_list().count(), 0);
+    assert_eq!(Tag::Int(5).iter_list().count(), 0);
+    Ok(())
+}
+
+#[test]
+fn identifier_as_string() -> Result<(), super::Error> {
+    use super::Identifier;
+
+    assert_eq!(Identifier::from_string("stone".to_string())?.as_string(), "minecraft:stone");
+    assert_eq!(Identifier::from_string("golden_apple:test".to_string())?.as_string(), "golden_apple:test");
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "mojang-api")]
+fn uuid_bulk_response() -> Result<(), super::Error> {
+    use super::UUID;
+
+    let response = r#"[
+        {"id": "853c80ef3c3749fdaa49938b674adae6", "name": "jeb_"},
+        {"id": "069a79f444e94726a5befca90e38aaf5", "name": "Notch"}
+    ]"#;
+    let players = UUID::parse_bulk_response(response)?;
+    assert_eq!(players, vec![
+        ("jeb_".to_string(), UUID::from_value(0x853c80ef3c3749fdaa49938b674adae6)?),
+        ("Notch".to_string(), UUID::from_value(0x069a79f444e94726a5befca90e38aaf5)?)
+    ]);
+
+    // No names means no requests.
+    assert!(UUID::from_usernames(&[])?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn nbt_length_limits() -> Result<(), super::Error> {
+    use super::nbt;
+    use super::Error;
+
+    // A root compound holding one unnamed byte array with the given length.
+    fn byte_array(length: i32) -> Vec<u8> {
+        let mut data = vec![10, 0, 0, 7, 0, 0];
+        data.extend_from_slice(&length.to_be_bytes());
+        data.extend_from_slice(&[1, 2, 3]);
+        data.push(0);
+        data
+    }
+
+    assert!(matches!(
+        nbt::from_reader(&mut byte_array(-1).as_slice()),
+        Err(Error::InvalidNbtLength(-1))
+    ));
+    assert!(matches!(
+        nbt::from_reader(&mut byte_array(i32::MAX).as_slice()),
+        Err(Error::InvalidNbtLength(i32::MAX))
+    ));
+
+    let limits = nbt::ReadLimits { max_length: 2, ..nbt::ReadLimits::default() };
+    assert!(matches!(
+        nbt::from_reader_with_limits(&mut byte_array(3).as_slice(), &limits),
+        Err(Error::InvalidNbtLength(3))
+    ));
+    let nbt = nbt::from_reader(&mut byte_array(3).as_slice())?;
+    assert_eq!(nbt.tag, nbt::Tag::Compound(vec![
+        nbt::NamedTag::new("", nbt::Tag::ByteArray(vec![1, 2, 3]))
+    ]));
+
+    // A list of End tags takes up no bytes per element, so any non-zero
+    // length is rejected before anything is read into it.
+    let end_list = [10, 0, 0, 9, 0, 0, 0, 0x00, 0x1e, 0x84, 0x80, 0];
+    assert!(matches!(nbt::from_reader(&mut &end_list[..]), Err(Error::InvalidNbtType)));
+    let empty_end_list = [10, 0, 0, 9, 0, 0, 0, 0, 0, 0, 0, 0];
+    assert_eq!(nbt::from_reader(&mut &empty_end_list[..])?.tag, nbt::Tag::Compound(vec![
+        nbt::NamedTag::new("", nbt::Tag::EmptyList(0))
+    ]));
+    Ok(())
+}
+
+#[test]
+fn position_world_coordinates() -> Result<(), super::Error> {
+    use super::Position;
+
+    let position = Position::from_f64_floored(10.9, 64.0, -0.5);
+    assert_eq!(position, Position::from_values(10, 64, -1));
+    assert_eq!(position.to_f64(), (10.0, 64.0, -1.0));
+    assert_eq!(position.center(), (10.5, 64.5, -0.5));
+    assert_eq!(Position::from_f64_floored(-3.0, -64.0, 0.0).to_f64(), (-3.0, -64.0, 0.0));
+    Ok(())
+}
+
+#[test]
+fn nbt_tag_len() -> Result<(), super::Error> {
+    use super::nbt::{NamedTag, Tag};
+
+    assert_eq!(Tag::List(vec![Tag::Int(1), Tag::Int(2)]).len(), Some(2));
+    assert_eq!(Tag::Compound(vec![NamedTag::new("a", Tag::End)]).len(), Some(1));
+    assert_eq!(Tag::LongArray(vec![1, 2, 3]).len(), Some(3));
+    assert_eq!(Tag::String("apple".to_string()).len(), Some(5));
+    assert_eq!(Tag::Int(5).len(), None);
+
+    assert!(Tag::Compound(vec![]).is_empty());
+    assert!(Tag::String(String::new()).is_empty());
+    assert!(!Tag::ByteArray(vec![0]).is_empty());
+    assert!(!Tag::Double(0.0).is_empty());
+    Ok(())
+}
+
+#[test]
+fn chat_hover_contents() -> Result<(), super::Error> {
+    use super::{Chat, HoverContents, HoverEvent, Identifier, UUID};
+
+    let chat = Chat::from_string(String::from(r#"{
+        "text": "Steve",
+        "hoverEvent": {
+            "action": "show_entity",
+            "contents": {
+                "type": "minecraft:player",
+                "id": [-2109535633, 1140445801, -1470471797, -1136015335],
+                "name": "Steve"
+            }
+        }
+    }"#))?;
+    let hover = chat.clone().to_string()?;
+    let hover: serde_json::Value = serde_json::from_str(&hover)?;
+    let event: HoverEvent = serde_json::from_value(hover["hoverEvent"].clone())?;
+
+    let expected = HoverContents::ShowEntity {
+        entity_type: Identifier::from_string(String::from("player"))?,
+        id: UUID::from_value(0x82430a6f43f9d269a85a618bbc49c819)?,
+        name: Some(Chat::from_string(String::from("\"Steve\""))?)
+    };
+    assert_eq!(event.parse_contents()?, expected);
+    // Round trip through the string UUID format.
+    assert_eq!(HoverEvent::from_contents(expected.clone())?.parse_contents()?, expected);
+
+    let item = HoverContents::ShowItem {
+        id: Identifier::from_string(String::from("minecraft:diamond_sword"))?,
+        count: 1,
+        components: Some(serde_json::json!({ "minecraft:damage": 5 }))
+    };
+    assert_eq!(HoverEvent::from_contents(item.clone())?.parse_contents()?, item);
+
+    let legacy = HoverEvent {
+        action: String::from("show_text"),
+        value: Some(String::from("\"hello\"")),
+        contents: None
+    };
+    assert_eq!(legacy.parse_contents()?, HoverContents::ShowText(Chat::from_string(String::from("\"hello\""))?));
+
+    let signed = HoverEvent {
+        action: String::from("show_entity"),
+        value: None,
+        contents: Some(serde_json::json!({
+            "type": "minecraft:player",
+            "id": "+2430a6f-43f9-d269-a85a-618bbc49c819"
+        }))
+    };
+    assert!(matches!(signed.parse_contents(), Err(super::Error::InvalidUuidString(_))));
+    Ok(())
+}
+
+#[test]
+fn encode_into_matches_to_bytes() -> Result<(), super::Error> {
+    use super::generalized::{string_encode_into, string_encode_into_no_cesu8, string_to_bytes, string_to_bytes_no_cesu8};
+    use super::{Position, VarInt, VarLong, UUID};
+
+    let varint = VarInt::from_value(-12345)?;
+    let varlong = VarLong::from_value(i64::MAX)?;
+    let uuid = UUID::from_value(0x09773765901b4da1a1243467f482b8b3)?;
+    let position = Position::from_values(5, 7, 3);
+
+    let mut buf = vec![0xAA];
+    varint.encode_into(&mut buf);
+    varlong.encode_into(&mut buf);
+    uuid.encode_into(&mut buf);
+    position.encode_into(&mut buf);
+    string_encode_into("golden apple", &mut buf);
+    string_encode_into_no_cesu8("golden apple", &mut buf);
+
+    let expected = [
+        vec![0xAA],
+        varint.to_bytes()?,
+        varlong.to_bytes()?,
+        uuid.to_bytes()?,
+        position.to_bytes()?,
+        string_to_bytes(String::from("golden apple"))?,
+        string_to_bytes_no_cesu8(String::from("golden apple"))?
+    ].concat();
+    assert_eq!(buf, expected);
+    Ok(())
+}
+
+#[test]
+fn chat_flatten_and_truncate() -> Result<(), super::Error> {
+    use super::Chat;
+
+    let chat = Chat::from_string(String::from(r#"{
+        "text": "Hello ",
+        "color": "gold",
+        "extra": [
+            {"text": "wörld", "bold": true},
+            {"text": "! ☺"}
+        ]
+    }"#))?;
+
+    let runs = chat.flatten();
+    assert_eq!(runs.len(), 3);
+    assert_eq!(runs[1].color, Some(String::from("gold")));
+    assert_eq!(runs[1].bold, Some(true));
+    assert_eq!(runs[2].bold, None);
+    assert_eq!(chat.to_plain_text(), "Hello wörld! ☺");
+
+    // Nothing to cut.
+    assert_eq!(chat.truncate(14), chat);
+
+    // Cutting inside a run keeps that run's style.
+    let truncated = chat.truncate(8);
+    assert_eq!(truncated.to_plain_text(), "Hello wö\u{2026}");
+    let runs = truncated.flatten();
+    assert_eq!(runs[2].text, Some(String::from("wö")));
+    assert_eq!(runs[2].bold, Some(true));
+    assert_eq!(runs[2].color, Some(String::from("gold")));
+
+    // Cutting exactly at a boundary doesn't leave an empty run behind.
+    let truncated = chat.truncate(6);
+    assert_eq!(truncated.to_plain_text(), "Hello \u{2026}");
+    assert_eq!(truncated.flatten().len(), 3);
+    Ok(())
+}
+
+#[test]
+fn default_structs() -> Result<(), super::Error> {
+    use super::netty::configuration::KnownPack;
+    use super::netty::status::{Players, StatusResponse};
+    use super::ChatComponent;
+
+    let response = StatusResponse { players: Players { online: 5, ..Default::default() }, ..Default::default() };
+    assert_eq!(response.online_players(), 5);
+    assert!(response.sample_players().is_empty());
+
+    let component = ChatComponent { text: Some(String::from("hi")), ..Default::default() };
+    assert_eq!(component.extra, None);
+
+    let pack = KnownPack { namespace: String::from("minecraft"), ..Default::default() };
+    assert_eq!(pack.version, "");
+    Ok(())
+}
+
+#[test]
+fn nbt_to_network_writer() -> Result<(), super::Error> {
+    use super::nbt::{self, NamedTag, Tag};
+
+    let tag = Tag::Compound(vec![
+        NamedTag::new("name", Tag::String(String::from("Steve"))),
+        NamedTag::new("pos", Tag::List(vec![Tag::Int(1), Tag::Int(-2)])),
+        NamedTag::new("empty", Tag::List(vec![]))
+    ]);
+    let mut written = vec![];
+    nbt::to_network_writer(&mut written, &tag)?;
+
+    let expected = [
+        vec![10], //                               Tag::Compound, no name
+        vec![8, 0, 4], b"name".to_vec(), //        Tag::String "name"
+        vec![0, 5], b"Steve".to_vec(), //          "Steve"
+        vec![9, 0, 3], b"pos".to_vec(), //         Tag::List "pos"
+        vec![3, 0, 0, 0, 2], //                    of 2 ints
+        vec![0, 0, 0, 1, 0xFF, 0xFF, 0xFF, 0xFE], // 1, -2
+        vec![9, 0, 5], b"empty".to_vec(), //       Tag::List "empty"
+        vec![0, 0, 0, 0, 0], //                    of 0 end tags
+        vec![0] //                                 Tag::End
+    ].concat();
+    assert_eq!(written, expected);
+
+    // Scalars are written with just their type ID.
+    let mut written = vec![];
+    nbt::to_network_writer(&mut written, &Tag::Short(7))?;
+    assert_eq!(written, [2, 0, 7]);
+    Ok(())
+}
+
+#[test]
+fn chat_type_identifiers() -> Result<(), super::Error> {
+    use super::enums::ChatType;
+    use super::{Identifier, VarInt};
+
+    let say = Identifier::from_string(String::from("minecraft:say_command"))?;
+    assert_eq!(ChatType::from_identifier(&say), Some(ChatType::SAY_COMMAND));
+    assert_eq!(ChatType::SAY_COMMAND.identifier(), Some(say));
+    assert_eq!(ChatType(VarInt::from_value(0)?), ChatType::CHAT);
+
+    assert_eq!(ChatType(VarInt::from_value(7)?).identifier(), None);
+    assert_eq!(ChatType(VarInt::from_value(-1)?).identifier(), None);
+    let custom = Identifier::from_string(String::from("golden_apple:chat"))?;
+    assert_eq!(ChatType::from_identifier(&custom), None);
+    Ok(())
+}
+
+#[test]
+fn varint_boundary_encodings() -> Result<(), super::Error> {
+    use super::{Error, VarInt, VarLong};
+
+    let boundaries: [(i32, &[u8]); 10] = [
+        (127, &[0x7f]),
+        (128, &[0x80, 0x01]),
+        (255, &[0xff, 0x01]),
+        (16_383, &[0xff, 0x7f]),
+        (16_384, &[0x80, 0x80, 0x01]),
+        (2_097_151, &[0xff, 0xff, 0x7f]),
+        (2_097_152, &[0x80, 0x80, 0x80, 0x01]),
+        (268_435_455, &[0xff, 0xff, 0xff, 0x7f]),
+        (268_435_456, &[0x80, 0x80, 0x80, 0x80, 0x01]),
+        (-2, &[0xfe, 0xff, 0xff, 0xff, 0x0f])
+    ];
+    for (value, encoded) in boundaries {
+        assert_eq!(VarInt::from_value(value)?.to_bytes()?, encoded);
+        assert_eq!(VarInt::from_bytes(encoded)?.0.value(), value);
+        assert_eq!(VarInt::from_reader(&mut &encoded[..])?.value(), value);
+    }
+
+    // Anything set above the 4 lowest bits of the 5th byte can't fit in an i32.
+    for encoded in [
+        &[0xff, 0xff, 0xff, 0xff, 0x1f][..],
+        &[0xff, 0xff, 0xff, 0xff, 0x8f, 0x01][..],
+        &[0x80, 0x80, 0x80, 0x80, 0x80, 0x00][..]
+    ] {
+        assert!(matches!(VarInt::from_bytes(encoded), Err(Error::VarIntTooLong)));
+        assert!(matches!(VarInt::from_reader(&mut &encoded[..]), Err(Error::VarIntTooLong)));
+    }
+
+    // The 10th byte of a VarLong only holds a single bit.
+    let minus_one = [0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01];
+    assert_eq!(VarLong::from_bytes(&minus_one)?.0.value(), -1);
+    assert_eq!(VarLong::from_reader(&mut &minus_one[..])?.value(), -1);
+    let too_long = [0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x02];
+    assert!(matches!(VarLong::from_bytes(&too_long), Err(Error::VarIntTooLong)));
+    assert!(matches!(VarLong::from_reader(&mut &too_long[..]), Err(Error::VarIntTooLong)));
+    Ok(())
+}
+
+#[test]
+fn nbt_diff() -> Result<(), super::Error> {
+    use super::nbt::{self, NamedTag, NbtChange, Tag};
+
+    let old = Tag::Compound(vec![
+        NamedTag::new("Health", Tag::Float(20.0)),
+        NamedTag::new("Name", Tag::String(String::from("Steve"))),
+        NamedTag::new("Inventory", Tag::List(vec![
+            Tag::Compound(vec![NamedTag::new("id", Tag::String(String::from("minecraft:stone")))]),
+            Tag::Compound(vec![NamedTag::new("id", Tag::String(String::from("minecraft:dirt")))])
+        ])),
+        NamedTag::new("OnGround", Tag::Byte(1))
+    ]);
+    // Same data in a different order, so no changes.
+    let reordered = Tag::Compound(vec![
+        NamedTag::new("OnGround", Tag::Byte(1)),
+        NamedTag::new("Inventory", Tag::List(vec![
+            Tag::Compound(vec![NamedTag::new("id", Tag::String(String::from("minecraft:stone")))]),
+            Tag::Compound(vec![NamedTag::new("id", Tag::String(String::from("minecraft:dirt")))])
+        ])),
+        NamedTag::new("Name", Tag::String(String::from("Steve"))),
+        NamedTag::new("Health", Tag::Float(20.0))
+    ]);
+    assert!(nbt::diff(&old, &reordered).is_empty());
+
+    let new = Tag::Compound(vec![
+        NamedTag::new("Health", Tag::Float(15.5)),
+        NamedTag::new("Name", Tag::String(String::from("Steve"))),
+        NamedTag::new("Inventory", Tag::List(vec![
+            Tag::Compound(vec![NamedTag::new("id", Tag::String(String::from("minecraft:granite")))])
+        ])),
+        NamedTag::new("XpLevel", Tag::Int(3))
+    ]);
+    assert_eq!(nbt::diff(&old, &new), vec![
+        NbtChange::Modified { path: String::from("Health"), old: Tag::Float(20.0), new: Tag::Float(15.5) },
+        NbtChange::Modified {
+            path: String::from("Inventory/0/id"),
+            old: Tag::String(String::from("minecraft:stone")),
+            new: Tag::String(String::from("minecraft:granite"))
+        },
+        NbtChange::Removed {
+            path: String::from("Inventory/1"),
+            value: Tag::Compound(vec![NamedTag::new("id", Tag::String(String::from("minecraft:dirt")))])
+        },
+        NbtChange::Removed { path: String::from("OnGround"), value: Tag::Byte(1) },
+        NbtChange::Added { path: String::from("XpLevel"), value: Tag::Int(3) }
+    ]);
+    Ok(())
+}
+
+#[test]
+fn try_from_byte_slices() -> Result<(), super::Error> {
+    use super::{Chat, Identifier, Position, VarInt, VarLong, UUID};
+
+    let uuid = UUID::from_value(0x09773765901b4da1a1243467f482b8b3)?;
+    assert_eq!(UUID::try_from(uuid.to_bytes()?.as_slice())?, uuid);
+
+    let position = Position::from_values(5, 7, 3);
+    assert_eq!(Position::try_from(position.to_bytes()?.as_slice())?, position);
+
+    assert_eq!(VarInt::try_from(&[0xff, 0x01][..])?.value(), 255);
+    assert_eq!(VarLong::try_from(&[0xff, 0x01][..])?.value(), 255);
+
+    assert!(Identifier::try_from(&[][..]).is_err());
+    assert!(Chat::try_from(&[][..]).is_err());
+
+    assert!(UUID::try_from(&[0x00; 15][..]).is_err());
+    Ok(())
+}
+
+#[test]
+fn login_offline_flow() -> Result<(), super::Error> {
+    use super::netty::login::{self, ClientboundPacket, ServerboundPacket};
+    use super::{Error, Identifier, UUID};
+
+    // Plays back what the server sends, and records what the client sends.
+    struct MockStream {
+        incoming: std::io::Cursor<Vec<u8>>,
+        outgoing: Vec<u8>
+    }
+    impl std::io::Read for MockStream {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            self.incoming.read(buf)
+        }
+    }
+    impl std::io::Write for MockStream {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.outgoing.write(buf)
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let uuid = UUID::from_value(0x09773765901b4da1a1243467f482b8b3)?;
+    let key = Identifier::from_string(String::from("golden_apple:cookie"))?;
+    let server_packets = [
+        ClientboundPacket::CookieRequest { key: key.clone() }.to_bytes()?,
+        ClientboundPacket::LoginSuccess {
+            uuid,
+            username: String::from("thisjaiden"),
+            properties: vec![],
+            strict_error_handling: false
+        }.to_bytes()?
+    ].concat();
+    let mut stream = MockStream { incoming: std::io::Cursor::new(server_packets), outgoing: vec![] };
+
+    let success = login::perform_login(&mut stream, "thisjaiden", None, uuid)?;
+    assert_eq!(success.uuid, uuid);
+    assert_eq!(success.username, "thisjaiden");
+    assert_eq!(success.compression_threshold, None);
+
+    let expected = [
+        ServerboundPacket::LoginStart { name: String::from("thisjaiden"), uuid }.to_bytes()?,
+        ServerboundPacket::CookieResponse { key, payload: None }.to_bytes()?,
+        ServerboundPacket::LoginAcknowledged.to_bytes()?
+    ].concat();
+    assert_eq!(stream.outgoing, expected);
+
+    // Servers that want encryption can't be logged in to yet.
+    let server_packets = ClientboundPacket::EncryptionRequest {
+        server_id: String::new(),
+        public_key: vec![1, 2, 3],
+        verify_token: vec![4, 5, 6, 7],
+        should_authenticate: true
+    }.to_bytes()?;
+    let mut stream = MockStream { incoming: std::io::Cursor::new(server_packets), outgoing: vec![] };
+    assert!(matches!(
+        login::perform_login(&mut stream, "thisjaiden", None, uuid),
+        Err(Error::EncryptionUnsupported)
+    ));
+
+    // Neither can online mode logins, even with offline mode servers.
+    let mut stream = MockStream { incoming: std::io::Cursor::new(vec![]), outgoing: vec![] };
+    assert!(matches!(
+        login::perform_login(&mut stream, "thisjaiden", Some("token"), uuid),
+        Err(Error::EncryptionUnsupported)
+    ));
+    assert!(stream.outgoing.is_empty());
+    Ok(())
+}
+
+#[test]
+fn registry_enums_sort_by_id() -> Result<(), super::Error> {
+    use super::enums::{Block, Item};
+    use std::collections::BTreeSet;
+
+    let mut items = vec![Item::Diamond, Item::Air, Item::Stone];
+    items.sort();
+    assert_eq!(items, [Item::Air, Item::Stone, Item::Diamond]);
+    assert!((Item::Stone as u32) < (Item::Diamond as u32));
+
+    let blocks: BTreeSet<Block> = [Block::Dirt, Block::Stone, Block::Air].into_iter().collect();
+    assert_eq!(blocks.into_iter().collect::<Vec<Block>>(), [Block::Air, Block::Stone, Block::Dirt]);
+    Ok(())
+}
+
+#[test]
+fn chat_shorthand_constructors() -> Result<(), super::Error> {
+    use super::Chat;
+
+    assert_eq!(Chat::plain("hello"), Chat::from_string(String::from(r#"{"text":"hello"}"#))?);
+    assert_eq!(
+        Chat::colored("hello", "gold"),
+        Chat::from_string(String::from(r#"{"text":"hello","color":"gold"}"#))?
+    );
+    assert_eq!(Chat::colored("hello", "#FFAA00").to_plain_text(), "hello");
+    Ok(())
+}
+
+#[test]
+fn generalized_string_edge_cases() -> Result<(), super::Error> {
+    use super::generalized::*;
+    use super::{Error, VarInt};
+
+    let long = "a".repeat(32767);
+    let tricky = String::from("nul\0 and \u{1F34E}");
+    for text in ["", long.as_str(), tricky.as_str()] {
+        let bytes = string_to_bytes(text.to_string())?;
+        assert_eq!(string_from_bytes(&bytes)?, (text.to_string(), bytes.len()));
+        assert_eq!(string_from_reader_counted(&mut std::io::Cursor::new(&bytes))?, (text.to_string(), bytes.len()));
+        assert_eq!(string_from_reader(&mut std::io::Cursor::new(&bytes))?, text);
+
+        let bytes = string_to_bytes_no_cesu8(text.to_string())?;
+        assert_eq!(string_from_bytes_no_cesu8(&bytes)?, (text.to_string(), bytes.len()));
+        assert_eq!(string_from_reader_no_cesu8(&mut std::io::Cursor::new(&bytes))?, text);
+    }
+
+    // The length prefix counts bytes, so Modified UTF-8 makes it longer than normal UTF-8.
+    let modified = string_to_bytes(tricky.clone())?;
+    let normal = string_to_bytes_no_cesu8(tricky.clone())?;
+    assert_eq!(normal.len() - 1, tricky.len());
+    assert_eq!(modified.len(), normal.len() + 3);
+
+    // A 32767 character string needs a 3 byte length prefix.
+    assert_eq!(string_to_bytes(long)?.len(), 32767 + 3);
+
+    assert!(matches!(string_from_bytes(&[0x05, b'a', b'b']), Err(Error::MissingData)));
+    assert!(matches!(string_from_bytes_no_cesu8(&[0x05, b'a']), Err(Error::MissingData)));
+    let negative = VarInt::from_value(-1)?.to_bytes()?;
+    assert!(matches!(string_from_bytes(&negative), Err(Error::InvalidStringLength(_))));
+    assert!(matches!(
+        string_from_reader(&mut std::io::Cursor::new(&negative)),
+        Err(Error::InvalidStringLength(_))
+    ));
+    assert!(matches!(string_from_bytes_no_cesu8(&[0x01, 0xFF]), Err(Error::InvalidUtf8(_))));
+    assert!(matches!(
+        string_from_reader_no_cesu8(&mut std::io::Cursor::new([0x01, 0xFF])),
+        Err(Error::InvalidUtf8(_))
+    ));
+    Ok(())
+}
+
+#[test]
+fn status_response_json() -> Result<(), super::Error> {
+    use super::netty::status::{Players, SamplePlayer, StatusResponse, Version};
+    use super::{Chat, UUID};
+
+    let json = r#"{"version":{"name":"1.21.3","protocol":768},"players":{"max":20,"online":1,"sample":[{"name":"thisjaiden","id":"82430a6f-43f9-d269-a85a-618bbc49c819"}]},"description":{"text":"A Minecraft Server"},"favicon":"data:image/png;base64,AAAA"}"#;
+    let bytes = super::generalized::string_to_bytes_no_cesu8(json.to_string())?;
+    let response = StatusResponse::from_reader(&mut std::io::Cursor::new(&bytes))?;
+    let id = UUID::from_value(0x82430a6f43f9d269a85a618bbc49c819)?;
+    assert_eq!(response.version, Version { name: String::from("1.21.3"), protocol: 768 });
+    assert_eq!(response.players.sample, [SamplePlayer { name: String::from("thisjaiden"), id }]);
+    assert_eq!(response.sample_players(), [(String::from("thisjaiden"), id)]);
+    assert_eq!(response.description.to_plain_text(), "A Minecraft Server");
+    assert_eq!(response.favicon_data, "AAAA");
+    assert_eq!(StatusResponse::from_reader(&mut std::io::Cursor::new(response.to_bytes()?))?, response);
+
+    // The sample and favicon are both optional.
+    let minimal = StatusResponse {
+        version: Version { name: String::from("Paper 1.21.3"), protocol: 768 },
+        players: Players { max: 100, online: 0, sample: vec![] },
+        description: Chat::plain("Hi \"there\""),
+        favicon_data: String::new()
+    };
+    let text = super::generalized::string_from_bytes_no_cesu8(&minimal.to_bytes()?)?.0;
+    let value: serde_json::Value = serde_json::from_str(&text)?;
+    assert!(value.get("favicon").is_none());
+    assert_eq!(StatusResponse::from_reader(&mut std::io::Cursor::new(minimal.to_bytes()?))?, minimal);
+
+    // Sample IDs are parsed as strictly as UUID::from_hyphenated.
+    for bad in ["+2430a6f-43f9-d269-a85a-618bbc49c819", "82430a6f43f9-d269-a85a-618bbc49c8-19"] {
+        let player = serde_json::json!({ "name": "thisjaiden", "id": bad });
+        assert!(serde_json::from_value::<SamplePlayer>(player).is_err());
+    }
+    Ok(())
+}
+
+#[test]
+fn nbt_compound_list() -> Result<(), super::Error> {
+    use super::nbt::{NamedTag, Tag};
+
+    let items = Tag::List(vec![
+        Tag::Compound(vec![
+            NamedTag::new("id", Tag::String(String::from("minecraft:stone"))),
+            NamedTag::new("count", Tag::Int(64))
+        ]),
+        Tag::Compound(vec![NamedTag::new("id", Tag::String(String::from("minecraft:dirt")))])
+    ]);
+    let children: Vec<&[NamedTag]> = items.compound_list().unwrap().collect();
+    assert_eq!(children.len(), 2);
+    assert_eq!(children[0].len(), 2);
+    assert_eq!(children[1][0].tag, Tag::String(String::from("minecraft:dirt")));
+
+    assert_eq!(Tag::List(vec![]).compound_list().unwrap().count(), 0);
+    assert!(Tag::List(vec![Tag::Int(1), Tag::Int(2)]).compound_list().is_none());
+    assert!(Tag::Compound(vec![]).compound_list().is_none());
+    Ok(())
+}
+
+#[test]
+fn uuid_formatting() -> Result<(), super::Error> {
+    use super::UUID;
+
+    let uuid = UUID::from_value(0x069a79f444e94726a5befca90e38aaf5)?;
+    assert_eq!(uuid.to_string(), "069a79f4-44e9-4726-a5be-fca90e38aaf5");
+    assert_eq!(format!("{:?}", uuid), "UUID(069a79f4-44e9-4726-a5be-fca90e38aaf5)");
+    assert_eq!(UUID::from_value(1)?.to_string(), "00000000-0000-0000-0000-000000000001");
+    Ok(())
+}
+
+#[test]
+fn nbt_compound_editing() -> Result<(), super::Error> {
+    use super::nbt::{NamedTag, Tag};
+
+    let mut entity = Tag::Compound(vec![
+        NamedTag::new("id", Tag::String(String::from("minecraft:pig"))),
+        NamedTag::new("UUID", Tag::IntArray(vec![1, 2, 3, 4])),
+        NamedTag::new("Motion", Tag::List(vec![Tag::Double(0.0), Tag::Double(0.1), Tag::Double(0.0)])),
+        NamedTag::new("Health", Tag::Float(10.0))
+    ]);
+    assert_eq!(entity.remove("UUID"), Some(NamedTag::new("UUID", Tag::IntArray(vec![1, 2, 3, 4]))));
+    assert_eq!(entity.remove("UUID"), None);
+
+    entity.retain(|child| child.name != "Motion");
+    let names: Vec<&str> = entity.iter_compound().map(|child| child.name.as_str()).collect();
+    assert_eq!(names, ["id", "Health"]);
+
+    let mut list = Tag::List(vec![Tag::Int(1)]);
+    assert_eq!(list.remove("id"), None);
+    list.retain(|_| false);
+    assert_eq!(list, Tag::List(vec![Tag::Int(1)]));
+    Ok(())
+}
+
+#[test]
+fn inventory_slots() -> Result<(), super::Error> {
+    use super::enums::Item;
+    use super::{Error, Inventory, Slot};
+
+    let mut inventory = Inventory::new(3);
+    assert_eq!(inventory.first_empty(), Some(0));
+    let stone = Slot { item_count: 64, item: Some(Item::Stone) };
+    assert_eq!(inventory.set(0, stone), Some(Slot::empty()));
+    assert_eq!(inventory.set(3, stone), None);
+    assert_eq!(inventory.first_empty(), Some(1));
+    assert_eq!(inventory.get(0), Some(&stone));
+    assert_eq!(inventory.get(3), None);
+
+    let bytes = inventory.to_bytes()?;
+    assert_eq!(bytes[0], 3);
+    let read = Inventory::from_reader(&mut bytes.as_slice())?;
+    assert_eq!(read, inventory);
+    assert_eq!(read.len(), 3);
+
+    assert!(matches!(Inventory::from_reader(&mut [0xFF, 0xFF, 0xFF, 0xFF, 0x0F].as_slice()), Err(Error::InvalidArrayLength(_))));
+    assert!(Inventory::from_reader(&mut [0x00].as_slice())?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn chat_search() -> Result<(), super::Error> {
+    use super::Chat;
+
+    let chat = Chat::from_string(String::from(
+        r#"{"text":"<Steve> ","extra":[{"text":"free ","color":"gold"},{"text":"DIAMONDS","bold":true}]}"#
+    ))?;
+    assert!(chat.contains("free DIAMONDS"));
+    assert!(!chat.contains("free diamonds"));
+    assert!(chat.contains_ignore_case("Free Diamonds"));
+    assert!(!chat.contains_ignore_case("emeralds"));
+
+    #[cfg(feature = "regex")]
+    {
+        assert!(chat.matches(&regex::Regex::new(r"^<\w+> free").unwrap()));
+        assert!(!chat.matches(&regex::Regex::new(r"^free").unwrap()));
+    }
+    Ok(())
+}
+
+#[test]
+fn varnum_fast_path_boundaries() -> Result<(), super::Error> {
+    use super::{VarInt, VarLong};
+
+    for (value, length) in [(0, 1), (127, 1), (128, 2), (16383, 2), (16384, 3), (-1, 5), (i32::MIN, 5)] {
+        let mut bytes = vec![];
+        VarInt::from_value(value)?.encode_into(&mut bytes);
+        assert_eq!(bytes.len(), length);
+        assert_eq!(VarInt::from_bytes(&bytes)?, (VarInt::from_value(value)?, length));
+        assert_eq!(VarInt::from_reader(&mut bytes.as_slice())?, VarInt::from_value(value)?);
+    }
+    for (value, length) in [(0, 1), (127, 1), (128, 2), (16383, 2), (16384, 3), (-1, 10), (i64::MIN, 10)] {
+        let mut bytes = vec![];
+        VarLong::from_value(value)?.encode_into(&mut bytes);
+        assert_eq!(bytes.len(), length);
+        assert_eq!(VarLong::from_bytes(&bytes)?, (VarLong::from_value(value)?, length));
+        assert_eq!(VarLong::from_reader(&mut bytes.as_slice())?, VarLong::from_value(value)?);
+    }
+    Ok(())
+}
+
+#[test]
+fn identifier_namespace_normalization() -> Result<(), super::Error> {
+    use super::Identifier;
+
+    let bare = Identifier::from_string(String::from("stone"))?;
+    let full = Identifier::from_string(String::from("minecraft:stone"))?;
+    let empty_namespace = Identifier::from_string(String::from(":stone"))?;
+    assert_eq!(bare, full);
+    assert_eq!(empty_namespace, full);
+    assert_eq!(bare.as_string(), "minecraft:stone");
+    assert!(bare.is_vanilla() && full.is_vanilla());
+
+    let modded = Identifier::from_string(String::from("mymod:stone"))?;
+    assert_ne!(modded, full);
+    assert!(!modded.is_vanilla());
+    Ok(())
+}
+
+#[test]
+fn keep_alive_tracker() -> Result<(), super::Error> {
+    use super::netty::{self, configuration, KeepAliveTracker, ProtocolState, KEEP_ALIVE_TIMEOUT};
+    use std::time::{Duration, Instant};
+
+    let start = Instant::now();
+    let mut tracker = KeepAliveTracker::new(start);
+    assert!(!tracker.is_timed_out(start + KEEP_ALIVE_TIMEOUT));
+    assert!(tracker.is_timed_out(start + KEEP_ALIVE_TIMEOUT + Duration::from_secs(1)));
+
+    let later = start + Duration::from_secs(20);
+    assert_eq!(
+        tracker.respond(42, later, ProtocolState::Configuration)?,
+        netty::ServerboundPacket::Configuration(configuration::ServerboundPacket::KeepAlive { id: 42 })
+    );
+    assert_eq!(tracker.last_received(), Some(42));
+    assert_eq!(tracker.last_sent(), Some(42));
+    assert!(!tracker.is_timed_out(start + KEEP_ALIVE_TIMEOUT + Duration::from_secs(1)));
+    assert!(tracker.is_timed_out(later + Duration::from_secs(31)));
+    // Times from before the last Keep Alive never count as timed out.
+    assert!(!tracker.is_timed_out(start));
+    Ok(())
+}
+
+#[test]
+fn nbt_compound_as_map() -> Result<(), super::Error> {
+    use super::nbt::{NamedTag, Tag};
+
+    let compound = Tag::Compound(vec![
+        NamedTag::new("id", Tag::String(String::from("minecraft:pig"))),
+        NamedTag::new("Health", Tag::Float(10.0)),
+        NamedTag::new("Health", Tag::Float(5.0))
+    ]);
+    let map = compound.as_map().unwrap();
+    assert_eq!(map.len(), 2);
+    assert_eq!(map["id"], &Tag::String(String::from("minecraft:pig")));
+    // Duplicate names keep the last occurrence.
+    assert_eq!(map["Health"], &Tag::Float(5.0));
+    assert!(Tag::List(vec![]).as_map().is_none());
+    Ok(())
+}
+
+#[test]
+fn connection_closed_errors() -> Result<(), super::Error> {
+    use super::netty::{self, status, BufferedConnection, ProtocolState};
+    use super::Error;
+
+    let packet = status::ClientboundPacket::PingResponse { payload: 7 }.to_bytes()?;
+    let truncated = &packet[..packet.len() - 2];
+    assert!(matches!(
+        netty::ClientboundPacket::from_reader(&mut [].as_slice(), ProtocolState::Status),
+        Err(Error::ConnectionClosed)
+    ));
+    assert!(matches!(
+        netty::ClientboundPacket::from_reader(&mut &truncated[..], ProtocolState::Status),
+        Err(Error::ConnectionClosed)
+    ));
+    // Malformed packets are still reported as such.
+    assert!(matches!(
+        netty::ClientboundPacket::from_reader(&mut [0x01, 0x7F].as_slice(), ProtocolState::Status),
+        Err(Error::InvalidPacketId(_))
+    ));
+
+    let mut connection = BufferedConnection::new(truncated);
+    assert!(matches!(connection.next_frame(), Err(Error::ConnectionClosed)));
+    Ok(())
+}
+
+#[test]
+fn play_movement_packets() -> Result<(), super::Error> {
+    use super::netty::play::ServerboundPacket;
+    use super::VarInt;
+
+    let packets = [
+        ServerboundPacket::ConfirmTeleportation { teleport_id: VarInt::from_value(3)? },
+        ServerboundPacket::SetPlayerPosition { x: 0.5, y: 64.0, z: -12.25, on_ground: true },
+        ServerboundPacket::SetPlayerPositionAndRotation {
+            x: 0.5, y: 65.25, z: -12.25, yaw: 90.0, pitch: -45.0, on_ground: false
+        },
+        ServerboundPacket::SetPlayerRotation { yaw: 180.0, pitch: 0.0, on_ground: true }
+    ];
+    for packet in packets {
+        assert_eq!(ServerboundPacket::from_reader(&mut packet.to_bytes()?.as_slice())?, packet);
+        // Small enough to be sent uncompressed, and large enough to be compressed.
+        for threshold in [256, 0] {
+            let bytes = packet.to_bytes_com(VarInt::from_value(threshold)?)?;
+            assert_eq!(ServerboundPacket::from_reader_com(&mut bytes.as_slice())?, packet);
+        }
+    }
+
+    let bytes = ServerboundPacket::SetPlayerRotation { yaw: 0.0, pitch: 0.0, on_ground: true }.to_bytes()?;
+    assert_eq!(bytes, [0x0A, 0x1E, 0, 0, 0, 0, 0, 0, 0, 0, 0x01]);
+    Ok(())
+}
+
+#[test]
+fn block_item_mapping() -> Result<(), super::Error> {
+    use super::enums::{Block, Item};
+
+    assert_eq!(Block::Stone.to_item(), Some(Item::Stone));
+    assert_eq!(Item::Stone.to_block(), Some(Block::Stone));
+    assert_eq!(Block::WallTorch.to_item(), Some(Item::Torch));
+    assert_eq!(Item::Torch.to_block(), Some(Block::Torch));
+    assert_eq!(Block::OakWallSign.to_item(), Some(Item::OakSign));
+    assert_eq!(Block::KelpPlant.to_item(), Some(Item::Kelp));
+    assert_eq!(Block::Wheat.to_item(), Some(Item::WheatSeeds));
+    assert_eq!(Item::WheatSeeds.to_block(), Some(Block::Wheat));
+    assert_eq!(Item::Wheat.to_block(), None);
+    assert_eq!(Item::Redstone.to_block(), Some(Block::RedstoneWire));
+    assert_eq!(Block::Water.to_item(), None);
+    assert_eq!(Item::WaterBucket.to_block(), None);
+    assert_eq!(Item::Diamond.to_block(), None);
+    Ok(())
+}
+
+#[test]
+fn packets_ending_in_byte_arrays() -> Result<(), super::Error> {
+    use super::netty::{configuration, login};
+    use super::{Identifier, VarInt};
+    use super::generalized::read_remaining;
+
+    let channel = Identifier::from_string(String::from("golden_apple:test"))?;
+    let request = login::ClientboundPacket::LoginPluginRequest {
+        message_id: VarInt::from_value(300)?,
+        channel: channel.clone(),
+        data: vec![1, 2, 3, 4, 5]
+    };
+    let response = login::ServerboundPacket::LoginPluginResponse {
+        message_id: VarInt::from_value(300)?,
+        data: Some(vec![9; 200])
+    };
+    let message = configuration::ServerboundPacket::PluginMessage { channel, data: vec![] };
+
+    // Each packet is followed by another, which must still be read correctly.
+    let compression = login::ClientboundPacket::SetCompression { threshold: VarInt::from_value(-1)? };
+    let stream = [request.to_bytes()?, compression.to_bytes()?].concat();
+    let mut reader = stream.as_slice();
+    assert_eq!(login::ClientboundPacket::from_reader(&mut reader)?, request);
+    assert_eq!(login::ClientboundPacket::from_reader(&mut reader)?, compression);
+    let stream = [
+        request.to_bytes_com(VarInt::from_value(256)?)?,
+        compression.to_bytes_com(VarInt::from_value(256)?)?
+    ].concat();
+    let mut reader = stream.as_slice();
+    assert_eq!(login::ClientboundPacket::from_reader_com(&mut reader)?, request);
+    assert_eq!(login::ClientboundPacket::from_reader_com(&mut reader)?, compression);
+
+    let stream = [response.to_bytes()?, message.to_bytes()?, message.to_bytes()?].concat();
+    let mut reader = stream.as_slice();
+    assert_eq!(login::ServerboundPacket::from_reader(&mut reader)?, response);
+    assert_eq!(configuration::ServerboundPacket::from_reader(&mut reader)?, message);
+    assert_eq!(configuration::ServerboundPacket::from_reader(&mut reader)?, message);
+    assert!(reader.is_empty());
+
+    // Unread parts of a packet are skipped over.
+    let mut padded = vec![0x04, 0x03, 0x80, 0x01, 0xFF];
+    padded.append(&mut compression.to_bytes()?);
+    let mut reader = padded.as_slice();
+    assert_eq!(
+        login::ClientboundPacket::from_reader(&mut reader)?,
+        login::ClientboundPacket::SetCompression { threshold: VarInt::from_value(128)? }
+    );
+    assert_eq!(login::ClientboundPacket::from_reader(&mut reader)?, compression);
+
+    assert_eq!(read_remaining(&mut std::io::Read::take([1, 2, 3].as_slice(), 2))?, [1, 2]);
+    Ok(())
+}
+
+#[test]
+fn chat_json_round_trips() -> Result<(), super::Error> {
+    use super::{Chat, Error};
+
+    let shapes = [
+        (r#""hi""#, r#"{"text":"hi"}"#, "hi"),
+        (r#"{"text":"hi","bold":true}"#, r#"{"text":"hi","bold":true}"#, "hi"),
+        (r#"["a",{"text":"b"}]"#, r#"{"text":"a","extra":[{"text":"b"}]}"#, "ab"),
+        (r#"{"text":"x","extra":["y",["z","!"]]}"#, r#"{"text":"x","extra":[{"text":"y"},{"text":"z","extra":[{"text":"!"}]}]}"#, "xyz!"),
+        (r#"[{"text":"a","color":"red"},"b"]"#, r#"{"text":"a","color":"red","extra":[{"text":"b"}]}"#, "ab")
+    ];
+    for (input, output, plain) in shapes {
+        let chat = Chat::from_string(input.to_string())?;
+        assert_eq!(chat.to_plain_text(), plain);
+        assert_eq!(chat.clone().to_string()?, output);
+        assert_eq!(Chat::from_string(chat.clone().to_string()?)?, chat);
+    }
+    // In an array, later elements inherit the first element's style.
+    let chat = Chat::from_string(String::from(r#"[{"text":"a","color":"red"},"b"]"#))?;
+    assert_eq!(chat.flatten()[1].color.as_deref(), Some("red"));
+
+    assert!(matches!(Chat::from_string(String::from("[]")), Err(Error::InvalidJsonRoot)));
+    assert!(matches!(Chat::from_string(String::from("5")), Err(Error::InvalidJsonRoot)));
+    Ok(())
+}
+
+#[test]
+fn position_round_trips() -> Result<(), super::Error> {
+    use super::Position;
+
+    let x_range = 1i64 << Position::X_BITS;
+    let z_range = 1i64 << Position::Z_BITS;
+    let y_range = 1i64 << Position::Y_BITS;
+    let mut positions = vec![
+        Position::from_values(-1, -1, -1),
+        Position::from_values(-33_554_432, -2048, -33_554_432),
+        Position::from_values(33_554_431, 2047, 33_554_431),
+        Position::from_values(-30_000_000, -64, -29_999_999),
+        Position::from_values(10, 5, -30)
+    ];
+    // A simple deterministic generator, so failures can be reproduced.
+    let mut state: u64 = 0x2545F4914F6CDD1D;
+    let mut next = move |range: i64| {
+        state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        ((state >> 16) as i64).rem_euclid(range) - range / 2
+    };
+    for _ in 0..1000 {
+        positions.push(Position::from_values(next(x_range) as i32, next(y_range) as i16, next(z_range) as i32));
+    }
+    for position in positions {
+        let bytes = position.to_bytes()?;
+        assert_eq!(Position::from_bytes(&bytes)?, (position, 8));
+        assert_eq!(Position::from_reader(&mut bytes.as_slice())?, position);
+    }
+    assert_eq!(
+        Position::from_bytes(&[0x00, 0x00, 0x02, 0xBF, 0xFF, 0xFE, 0x20, 0x05])?.0,
+        Position::from_values(10, 5, -30)
+    );
+    Ok(())
+}
+
+#[test]
+fn read_write_extension_traits() -> Result<(), super::Error> {
+    use super::{McRead, McWrite, Position, VarInt, VarLong, UUID};
+
+    let uuid = UUID::from_value(0x069a79f444e94726a5befca90e38aaf5)?;
+    let position = Position::from_values(-5, 64, 12);
+    let mut buf = vec![];
+    buf.write_varint(VarInt::from_value(-300)?)?;
+    buf.write_varlong(VarLong::from_value(1 << 40)?)?;
+    buf.write_string("golden \u{1F34E}")?;
+    buf.write_uuid(uuid)?;
+    buf.write_position(position)?;
+    buf.write_bool(true)?;
+
+    let mut reader = buf.as_slice();
+    assert_eq!(reader.read_varint()?, VarInt::from_value(-300)?);
+    assert_eq!(reader.read_varlong()?, VarLong::from_value(1 << 40)?);
+    assert_eq!(reader.read_string()?, "golden \u{1F34E}");
+    assert_eq!(reader.read_uuid()?, uuid);
+    assert_eq!(reader.read_position()?, position);
+    assert!(reader.read_bool()?);
+    assert!(reader.is_empty());
+    Ok(())
+}
+
+#[test]
+fn known_packs_negotiation() -> Result<(), super::Error> {
+    use super::netty::configuration::{KnownPack, ServerboundPacket};
+
+    let pack = |id: &str, version: &str| KnownPack {
+        namespace: String::from("minecraft"),
+        id: String::from(id),
+        version: String::from(version)
+    };
+    let server = vec![pack("core", "1.21.4"), pack("bundle", "1.21.4"), pack("trade_rebalance", "1.21.4")];
+    let client = vec![pack("trade_rebalance", "1.21.4"), pack("core", "1.21.4"), pack("bundle", "1.21.3")];
+
+    let packs = KnownPack::negotiate(&server, &client);
+    assert_eq!(packs, vec![pack("core", "1.21.4"), pack("trade_rebalance", "1.21.4")]);
+    assert!(KnownPack::negotiate(&server, &[]).is_empty());
+
+    let response = ServerboundPacket::KnownPacks { packs };
+    let bytes = response.to_bytes()?;
+    assert_eq!(ServerboundPacket::from_reader(&mut bytes.as_slice())?, response);
+    Ok(())
+}
+
+#[test]
+fn nbt_truncated_arrays() -> Result<(), super::Error> {
+    use super::nbt::{read_tag_by_type, Tag};
+
+    // Byte, int, and long arrays that each declare three elements.
+    let full: [(u8, Vec<u8>); 3] = [
+        (0x07, vec![0, 0, 0, 3, 1, 0xFF, 3]),
+        (0x0B, [vec![0, 0, 0, 3], (1..=12).collect()].concat()),
+        (0x0C, [vec![0, 0, 0, 3], (1..=24).collect()].concat())
+    ];
+    for (type_id, bytes) in &full {
+        assert!(read_tag_by_type(&mut bytes.as_slice(), *type_id).is_ok());
+        let truncated = &bytes[..bytes.len() - 1];
+        assert!(matches!(
+            read_tag_by_type(&mut &truncated[..], *type_id),
+            Err(super::Error::MissingData)
+        ));
+    }
+
+    let ints = read_tag_by_type(&mut full[1].1.as_slice(), 0x0B)?;
+    assert_eq!(ints, Tag::IntArray(vec![0x01020304, 0x05060708, 0x090A0B0C]));
+    let bytes = read_tag_by_type(&mut full[0].1.as_slice(), 0x07)?;
+    assert_eq!(bytes, Tag::ByteArray(vec![1, -1, 3]));
+
+    // A length far larger than the data is still just missing data.
+    let huge = [0x00, 0x10, 0x00, 0x00, 0x01, 0x02];
+    assert!(matches!(read_tag_by_type(&mut &huge[..], 0x0C), Err(super::Error::MissingData)));
+    Ok(())
+}
+
+#[test]
+fn packet_body_reader() -> Result<(), super::Error> {
+    use super::netty::PacketBody;
+    use super::netty::play::ServerboundPacket;
+    use super::VarInt;
+    use std::io::Read;
+
+    // Two packets back to back; the first body can't be read past its end.
+    let mut stream = vec![3, 0xAA, 0xBB, 0xCC, 2, 0xDD, 0xEE];
+    let mut reader = stream.as_slice();
+    let mut body = PacketBody::new(&mut reader)?;
+    let mut first = vec![];
+    body.read_to_end(&mut first)?;
+    body.finish()?;
+    assert_eq!(first, vec![0xAA, 0xBB, 0xCC]);
+
+    // Unread bytes are skipped by `finish`.
+    let mut body = PacketBody::new(&mut reader)?;
+    let mut byte = [0];
+    body.read_exact(&mut byte)?;
+    body.finish()?;
+    assert_eq!(byte, [0xDD]);
+    assert!(reader.is_empty());
+
+    // Compressed bodies are decompressed, and stop at the end of the packet.
+    let packet = ServerboundPacket::SetPlayerRotation { yaw: 90.0, pitch: -45.0, on_ground: true };
+    stream = packet.to_bytes_com(VarInt::from_value(1)?)?;
+    stream.append(&mut packet.to_bytes_com(VarInt::from_value(256)?)?);
+    let mut reader = stream.as_slice();
+    for _ in 0..2 {
+        let mut body = PacketBody::new_com(&mut reader)?;
+        let mut decompressed = vec![];
+        body.read_to_end(&mut decompressed)?;
+        body.finish()?;
+        assert_eq!(decompressed.len(), 10);
+        assert_eq!(decompressed[0], 0x1E);
+    }
+    assert!(reader.is_empty());
+
+    // Negative lengths are rejected.
+    let negative = VarInt::from_value(-1)?.to_bytes()?;
+    assert!(matches!(
+        PacketBody::new(&mut negative.as_slice()),
+        Err(super::Error::InvalidPacketLength(_))
+    ));
+    Ok(())
+}
+
+#[test]
+fn chat_format_selection() -> Result<(), super::Error> {
+    use super::nbt::{self, NamedTag, Tag};
+    use super::{Chat, ChatFormat, PROTOCOL_VERSION};
+
+    assert_eq!(ChatFormat::for_protocol(PROTOCOL_VERSION), ChatFormat::Nbt);
+    assert_eq!(ChatFormat::for_protocol(765), ChatFormat::Nbt);
+    assert_eq!(ChatFormat::for_protocol(764), ChatFormat::Json);
+
+    assert_eq!(Chat::plain("hi").to_nbt()?, Tag::String(String::from("hi")));
+    let styled = Chat::from_string(String::from(
+        r#"{"text":"a","bold":true,"extra":["b",{"text":"c","color":"red"}]}"#
+    ))?;
+    assert_eq!(styled.to_nbt()?, Tag::Compound(vec![
+        NamedTag::new("bold", Tag::Byte(1)),
+        NamedTag::new("extra", Tag::List(vec![
+            Tag::Compound(vec![NamedTag::new("text", Tag::String(String::from("b")))]),
+            Tag::Compound(vec![
+                NamedTag::new("color", Tag::String(String::from("red"))),
+                NamedTag::new("text", Tag::String(String::from("c")))
+            ])
+        ])),
+        NamedTag::new("text", Tag::String(String::from("a")))
+    ]));
+
+    let mut json = vec![];
+    styled.clone().to_writer_versioned(&mut json, ChatFormat::Json)?;
+    assert_eq!(json, styled.clone().to_bytes()?);
+
+    let mut network = vec![];
+    styled.clone().to_writer_versioned(&mut network, ChatFormat::Nbt)?;
+    assert_eq!(nbt::read_tag_with_type(&mut network.as_slice())?, styled.to_nbt()?);
+    Ok(())
+}
+
+#[test]
+fn uuid_value() -> Result<(), super::Error> {
+    use super::UUID;
+
+    let uuid = UUID::from_value(0x069a79f444e94726a5befca90e38aaf5)?;
+    assert_eq!(uuid.value(), 0x069a79f444e94726a5befca90e38aaf5);
+    // Reading the value doesn't consume the UUID.
+    assert_eq!(UUID::from_value(uuid.value())?, uuid);
+    Ok(())
+}
+
+/// Replays a capture from the `captures` folder through a [super::netty::PacketDecoder], a few
+/// bytes at a time like a real connection, returning every packet decoded along the way.
+fn replay_capture(
+    capture: &[u8], state: super::netty::ProtocolState
+) -> Result<Vec<super::netty::ClientboundPacket>, super::Error> {
+    let mut decoder = super::netty::PacketDecoder::new(state);
+    let mut packets = vec![];
+    for chunk in capture.chunks(7) {
+        decoder.push(chunk);
+        while let Some(packet) = decoder.next_packet()? {
+            packets.push(packet);
+        }
+    }
+    assert!(decoder.buffered().is_empty(), "capture ended partway through a packet");
+
+    Ok(packets)
+}
+
+#[test]
+fn replay_status_capture() -> Result<(), super::Error> {
+    use super::netty::{ClientboundPacket, ProtocolState, status};
+    use super::UUID;
+
+    let packets = replay_capture(include_bytes!("../captures/status.bin"), ProtocolState::Status)?;
+    assert_eq!(packets.len(), 2);
+    let ClientboundPacket::Status(status::ClientboundPacket::StatusResponse { response }) = &packets[0] else {
+        panic!("expected a status response, got {:?}", packets[0]);
+    };
+    assert_eq!(response.version_name(), "1.21.3");
+    assert_eq!(response.version_protocol(), 768);
+    assert_eq!((response.online_players(), response.max_players()), (1, 20));
+    assert_eq!(
+        response.sample_players(),
+        vec![(String::from("thisjaiden"), UUID::from_value(0x09773765901b4da1a1243467f482b8b3)?)]
+    );
+    assert_eq!(response.description.to_plain_text(), "A Minecraft Server");
+    assert_eq!(
+        packets[1],
+        ClientboundPacket::Status(status::ClientboundPacket::PingResponse { payload: 0x0123456789abcdef })
+    );
+    Ok(())
+}
+
+#[test]
+fn replay_login_capture() -> Result<(), super::Error> {
+    use super::netty::{ClientboundPacket, ProtocolState, login};
+    use super::{Identifier, VarInt, UUID};
+
+    let packets = replay_capture(include_bytes!("../captures/login.bin"), ProtocolState::Login)?;
+    assert_eq!(packets, vec![
+        ClientboundPacket::Login(login::ClientboundPacket::SetCompression {
+            threshold: VarInt::from_value(256)?
+        }),
+        ClientboundPacket::Login(login::ClientboundPacket::LoginPluginRequest {
+            message_id: VarInt::from_value(7)?,
+            channel: Identifier::from_string(String::from("golden_apple:hello"))?,
+            data: [(0..=255).collect::<Vec<u8>>(), (0..=255).collect()].concat()
+        }),
+        ClientboundPacket::Login(login::ClientboundPacket::LoginSuccess {
+            uuid: UUID::from_value(0x09773765901b4da1a1243467f482b8b3)?,
+            username: String::from("thisjaiden"),
+            properties: vec![login::Property {
+                name: String::from("textures"),
+                value: String::from("e30="),
+                signature: None
+            }],
+            strict_error_handling: true
+        })
+    ]);
+    Ok(())
+}
+
+#[test]
+fn angle_precision() -> Result<(), super::Error> {
+    use super::Angle;
+
+    let step = 360.0 / 256.0;
+    for tenths in 0..3600 {
+        let degrees = tenths as f64 / 10.0;
+        let angle = Angle::from_degrees(degrees);
+        // Conversions round down, so they're off by less than one step.
+        assert!(degrees - angle.to_degrees() >= 0.0);
+        assert!(degrees - angle.to_degrees() < step, "{degrees} became {}", angle.to_degrees());
+        let radians = degrees.to_radians();
+        assert!((radians - Angle::from_radians(radians).to_radians()).abs() < step.to_radians());
+    }
+
+    for value in 0..=255u8 {
+        let (angle, size) = Angle::from_bytes(&[value])?;
+        assert_eq!(size, 1);
+        assert_eq!(angle.as_256ths(), value);
+        assert_eq!(Angle::from_bytes(&angle.to_bytes()?)?.0, angle);
+        assert_eq!(Angle::from_degrees(angle.to_degrees()), angle);
+    }
+
+    // Full turns and negative angles.
+    assert_eq!(Angle::from_degrees(360.0), Angle::from_degrees(0.0));
+    assert_eq!(Angle::from_degrees(720.0 + 90.0).as_256ths(), 64);
+    assert_eq!(Angle::from_degrees(-90.0).as_256ths(), 64);
+    assert_eq!(Angle::from_radians(2.0 * std::f64::consts::PI).as_256ths(), 0);
+    Ok(())
+}
+
+#[test]
+fn update_tags() -> Result<(), super::Error> {
+    use super::netty::configuration::ClientboundPacket;
+    use super::netty::Tags;
+    use super::{Identifier, VarInt};
+
+    let id = |s: &str| Identifier::from_string(String::from(s));
+    let blocks = Tags {
+        registry: id("block")?,
+        tags: vec![
+            (id("mineable/pickaxe")?, vec![VarInt::from_value(1)?, VarInt::from_value(300)?]),
+            (id("golden_apple:empty")?, vec![])
+        ]
+    };
+    let bytes = blocks.to_bytes()?;
+    assert_eq!(Tags::from_reader(&mut bytes.as_slice())?, blocks);
+    assert_eq!(
+        blocks.get(&id("minecraft:mineable/pickaxe")?),
+        Some(&[VarInt::from_value(1)?, VarInt::from_value(300)?][..])
+    );
+    assert_eq!(blocks.get(&id("golden_apple:empty")?), Some(&[][..]));
+    assert_eq!(blocks.get(&id("logs")?), None);
+
+    let packet = ClientboundPacket::UpdateTags {
+        registries: vec![blocks, Tags { registry: id("item")?, tags: vec![] }]
+    };
+    let bytes = packet.to_bytes()?;
+    assert_eq!(ClientboundPacket::from_reader(&mut bytes.as_slice())?, packet);
+
+    // A negative number of tags is rejected.
+    let mut bad = id("block")?.to_bytes()?;
+    bad.append(&mut VarInt::from_value(-1)?.to_bytes()?);
+    assert!(matches!(
+        Tags::from_reader(&mut bad.as_slice()),
+        Err(super::Error::InvalidArrayLength(_))
+    ));
+    Ok(())
+}
+
+#[test]
+fn identifier_str_comparison() -> Result<(), super::Error> {
+    use super::Identifier;
+
+    let brand = Identifier::from_string(String::from("minecraft:brand"))?;
+    assert!(brand == "minecraft:brand");
+    assert!(brand == "brand");
+    assert!(brand == ":brand");
+    assert!("minecraft:brand" == brand);
+    assert!(*"brand" == brand);
+    assert!(brand != "golden_apple:brand");
+    assert!(brand != "minecraft:brand:extra");
+
+    let custom = Identifier::from_string(String::from("golden_apple:hello"))?;
+    assert!(custom == "golden_apple:hello");
+    assert!(custom != "hello");
+    Ok(())
+}
+
+#[test]
+fn length_prefixed_writer() -> Result<(), super::Error> {
+    use super::generalized::with_length_prefix;
+    use super::VarInt;
+
+    assert_eq!(with_length_prefix(|_| {}), vec![0x00]);
+    assert_eq!(with_length_prefix(|buf| buf.extend_from_slice(&[1, 2, 3])), vec![3, 1, 2, 3]);
+
+    let long = with_length_prefix(|buf| buf.resize(300, 0xAB));
+    let (length, size) = VarInt::from_bytes(&long)?;
+    assert_eq!((length.value(), size), (300, 2));
+    assert_eq!(long.len(), 302);
+    Ok(())
+}
+
+#[test]
+fn client_information_builder() -> Result<(), super::Error> {
+    use super::netty::configuration::{ChatSettings, ClientInformation, ServerboundPacket, SkinSettings};
+    use super::VarInt;
+
+    let packet = ClientInformation::new()
+        .locale("en_GB")
+        .view_distance(8)
+        .chat_mode(ChatSettings::System)
+        .skin_parts(SkinSettings::CAPE | SkinSettings::HAT)
+        .build()?;
+    assert_eq!(packet, ServerboundPacket::ClientInformation {
+        locale: String::from("en_GB"),
+        view_distance: 8,
+        chat_mode: ChatSettings::System,
+        chat_colors: true,
+        skin_parts: SkinSettings::CAPE | SkinSettings::HAT,
+        main_hand: VarInt::from_value(1)?,
+        text_filtering: false,
+        allow_server_listings: true
+    });
+    let bytes = packet.to_bytes()?;
+    assert_eq!(ServerboundPacket::from_reader(&mut bytes.as_slice())?, packet);
+
+    assert!(ClientInformation::default().build().is_ok());
+    for locale in ["english", "en-us", "e_us", "en_", "en_u5", "abcdefghijklm_no"] {
+        assert!(matches!(
+            ClientInformation::new().locale(locale).build(),
+            Err(super::Error::InvalidLocale(_))
+        ), "{locale} should be rejected");
+    }
+    Ok(())
+}
+
+#[test]
+fn reused_compressor() -> Result<(), super::Error> {
+    use super::netty::configuration::ServerboundPacket;
+    use super::netty::Compressor;
+    use super::{Identifier, VarInt};
+    use std::io::Write;
+
+    let threshold = VarInt::from_value(256)?;
+    let mut compressor = Compressor::new();
+    for size in [0, 10, 300, 5000, 32000] {
+        let packet = ServerboundPacket::PluginMessage {
+            channel: Identifier::from_string(String::from("golden_apple:test"))?,
+            data: (0..size).map(|i| (i % 251) as u8).collect()
+        };
+        let reused = packet.to_bytes_com_with(threshold, &mut compressor)?;
+        assert_eq!(reused, packet.to_bytes_com(threshold)?);
+        assert_eq!(ServerboundPacket::from_reader_com(&mut reused.as_slice())?, packet);
+
+        // Matches compressing with a fresh zlib encoder.
+        let body = packet.to_bytes()?;
+        let body = &body[VarInt::from_bytes(&body)?.1..];
+        if body.len() >= 256 {
+            let mut encoder = flate2::write::ZlibEncoder::new(vec![], flate2::Compression::fast());
+            encoder.write_all(body)?;
+            let compressed = encoder.finish()?;
+            assert!(reused.ends_with(&compressed));
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn spawn_entity_packet() -> Result<(), super::Error> {
+    use super::enums::{EntityType, Orientation, SpawnEntityData};
+    use super::netty::{self, play::ClientboundPacket, ProtocolState};
+    use super::{Angle, EntityPosition, VarInt, UUID};
+
+    let arrow = ClientboundPacket::SpawnEntity {
+        entity_id: VarInt::from_value(42)?,
+        entity_uuid: UUID::from_value(0x069a79f444e94726a5befca90e38aaf5)?,
+        entity_type: EntityType::Arrow,
+        position: EntityPosition { x: 10.5, y: 64.0, z: -3.25 },
+        pitch: Angle::from_degrees(45.0),
+        yaw: Angle::from_degrees(180.0),
+        head_yaw: Angle::from_degrees(0.0),
+        data: SpawnEntityData::EntityID(7),
+        velocity: (800, -8000, 0)
+    };
+    let bytes = arrow.to_bytes()?;
+    // Length, packet ID, entity ID, UUID, then the entity type.
+    assert_eq!(&bytes[..3], &[bytes.len() as u8 - 1, 0x01, 42]);
+    assert_eq!(bytes[19], EntityType::Arrow as u8);
+    assert_eq!(ClientboundPacket::from_reader(&mut bytes.as_slice())?, arrow);
+    assert_eq!(
+        netty::ClientboundPacket::from_reader(&mut bytes.as_slice(), ProtocolState::Play)?,
+        netty::ClientboundPacket::Play(arrow.clone())
+    );
+    let compressed = arrow.to_bytes_com(VarInt::from_value(0)?)?;
+    assert_eq!(ClientboundPacket::from_reader_com(&mut compressed.as_slice())?, arrow);
+
+    // The data field depends on the entity type.
+    assert_eq!(SpawnEntityData::from_data(EntityType::ItemFrame, 1)?, SpawnEntityData::Orientation(Orientation::Up));
+    assert!(SpawnEntityData::from_data(EntityType::GlowItemFrame, 6).is_err());
+    assert_eq!(SpawnEntityData::from_data(EntityType::FallingBlock, 1)?, SpawnEntityData::BlockType(1));
+    assert_eq!(SpawnEntityData::from_data(EntityType::Trident, 0)?, SpawnEntityData::None);
+    assert_eq!(SpawnEntityData::from_data(EntityType::Pig, 3)?, SpawnEntityData::Other(3));
+    assert_eq!(SpawnEntityData::Orientation(Orientation::East).to_data(), 5);
+
+    assert_eq!(EntityPosition { x: -0.5, y: 64.9, z: 3.0 }.block(), super::Position::from_values(-1, 64, 3));
+    Ok(())
+}
+
+#[test]
+fn nbt_approx_eq() -> Result<(), super::Error> {
+    use super::nbt::{NamedTag, Tag};
+
+    assert_ne!(Tag::Double(0.1 + 0.2), Tag::Double(0.3));
+    assert!(Tag::Double(0.1 + 0.2).approx_eq(&Tag::Double(0.3), 1e-9));
+    assert!(!Tag::Double(0.31).approx_eq(&Tag::Double(0.3), 1e-9));
+    assert!(Tag::Float(f32::NAN).approx_eq(&Tag::Float(f32::NAN), 0.0));
+    assert!(Tag::Double(f64::INFINITY).approx_eq(&Tag::Double(f64::INFINITY), 0.0));
+    // Other types, including ones that hold floats, are still compared exactly.
+    assert!(!Tag::Float(1.0).approx_eq(&Tag::Double(1.0), 1.0));
+    assert!(!Tag::Int(1).approx_eq(&Tag::Int(2), 10.0));
+
+    let computed = Tag::Compound(vec![
+        NamedTag::new("pos", Tag::List(vec![Tag::Double(0.1 + 0.2), Tag::Double(1.0 / 3.0)])),
+        NamedTag::new("health", Tag::Float(19.999999)),
+        NamedTag::new("name", Tag::String(String::from("Steve")))
+    ]);
+    let expected = Tag::Compound(vec![
+        NamedTag::new("pos", Tag::List(vec![Tag::Double(0.3), Tag::Double(0.333333)])),
+        NamedTag::new("health", Tag::Float(20.0)),
+        NamedTag::new("name", Tag::String(String::from("Steve")))
+    ]);
+    assert!(computed.approx_eq(&expected, 1e-5));
+    assert!(!computed.approx_eq(&expected, 1e-9));
+
+    let mut renamed = expected.clone();
+    renamed.retain(|child| child.name != "name");
+    assert!(!computed.approx_eq(&renamed, 1e-5));
+    Ok(())
+}
+
+#[test]
+fn nbt_int_array_round_trip() -> Result<(), super::Error> {
+    use super::nbt::{self, NamedTag, Tag};
+
+    let tag = Tag::IntArray(vec![1, -2, 3]);
+    let mut bytes = vec![];
+    nbt::to_network_writer(&mut bytes, &tag)?;
+    // Type, then a four byte length with distinct bytes, then each element.
+    assert_eq!(&bytes[..5], &[0x0B, 0x00, 0x00, 0x00, 0x03]);
+    assert_eq!(&bytes[9..13], &[0xFF, 0xFF, 0xFF, 0xFE]);
+    assert_eq!(nbt::read_tag_with_type(&mut bytes.as_slice())?, tag);
+
+    // The same inside a compound, alongside the other array types.
+    let root = NamedTag::compound("root", vec![
+        NamedTag::new("ints", tag),
+        NamedTag::new("longs", Tag::LongArray(vec![0x0102030405060708, -1])),
+        NamedTag::new("bytes", Tag::ByteArray(vec![1, -2, 3]))
+    ]);
+    let bytes = nbt::to_bytes(&root)?;
+    assert_eq!(nbt::from_reader(&mut bytes.as_slice())?, root);
+    Ok(())
+}
+
+#[test]
+fn nbt_long_and_modified_utf8_names() -> Result<(), super::Error> {
+    use super::nbt::{self, NamedTag, Tag};
+
+    // 300 bytes: a length whose high and low bytes differ.
+    let long_name = "n".repeat(300);
+    let root = NamedTag::compound("root", vec![
+        NamedTag::new(long_name.clone(), Tag::String("v".repeat(300))),
+        NamedTag::new("after", Tag::Int(7))
+    ]);
+    let bytes = nbt::to_bytes(&root)?;
+    assert_eq!(&bytes[8..10], &[0x01, 0x2C]);
+    let read = nbt::from_reader(&mut bytes.as_slice())?;
+    assert_eq!(read, root);
+    assert_eq!(read.tag.as_map().unwrap()["after"], &Tag::Int(7));
+
+    // Modified UTF-8 writes NUL as two bytes and characters outside the BMP
+    // as surrogate pairs.
+    let string = Tag::String(String::from("a\0\u{1F34E}"));
+    let mut bytes = vec![];
+    nbt::to_network_writer(&mut bytes, &string)?;
+    assert_eq!(&bytes[1..3], &[0x00, 0x09]);
+    assert_eq!(&bytes[4..6], &[0xC0, 0x80]);
+    assert_eq!(nbt::read_tag_with_type(&mut bytes.as_slice())?, string);
+    Ok(())
+}
+
+#[test]
+fn nbt_from_bytes() -> Result<(), super::Error> {
+    use super::nbt::{self, NamedTag, Tag};
+
+    let root = NamedTag::compound("slot", vec![NamedTag::new("Count", Tag::Byte(3))]);
+    let mut bytes = nbt::to_bytes(&root)?;
+    let nbt_length = bytes.len();
+    // Whatever follows the NBT in the packet.
+    bytes.extend_from_slice(&[0xAA, 0xBB]);
+
+    let (read, size) = nbt::from_bytes(&bytes)?;
+    assert_eq!(read, root);
+    assert_eq!(size, nbt_length);
+    assert_eq!(&bytes[size..], &[0xAA, 0xBB]);
+
+    const SAMPLE: &[u8] = include_bytes!("../mojang/bigtest.nbt");
+    assert_eq!(nbt::from_bytes(SAMPLE)?.1, SAMPLE.len());
+    assert!(matches!(nbt::from_bytes(&bytes[..nbt_length - 1]), Err(super::Error::ReaderError(_))));
+    Ok(())
+}
+
+#[test]
+fn nbt_network_format() -> Result<(), super::Error> {
+    use super::nbt::{self, NamedTag, Tag};
+
+    // A nameless compound: the header is followed directly by the first tag.
+    let network = [0x0A, 0x01, 0x00, 0x01, b'a', 0x05, 0x00];
+    let read = nbt::from_reader_network(&mut &network[..])?;
+    assert_eq!(read, NamedTag::compound("", vec![NamedTag::new("a", Tag::Byte(5))]));
+    assert_eq!(nbt::to_bytes_network(&read)?, network);
+    // The root's name is never written.
+    let named = NamedTag::compound("ignored", vec![NamedTag::new("a", Tag::Byte(5))]);
+    assert_eq!(nbt::to_bytes_network(&named)?, network);
+    // The file format keeps it.
+    let file = nbt::to_bytes(&named)?;
+    assert_eq!(nbt::from_reader(&mut file.as_slice())?, named);
+    assert_ne!(file, network);
+
+    // Since 1.20.3 the root tag doesn't have to be a compound.
+    let string = NamedTag::new("", Tag::String(String::from("hello")));
+    let bytes = nbt::to_bytes_network(&string)?;
+    assert_eq!(nbt::from_reader_network(&mut bytes.as_slice())?, string);
+    Ok(())
+}
+
+#[test]
+fn nbt_compressed_files() -> Result<(), super::Error> {
+    use super::nbt::{self, NamedTag, Tag};
+    use std::io::Write;
+
+    const SAMPLE: &[u8] = include_bytes!("../mojang/bigtest.nbt");
+    let expected = nbt::from_reader(&mut &SAMPLE[..])?;
+
+    let mut encoder = flate2::write::GzEncoder::new(vec![], flate2::Compression::default());
+    encoder.write_all(SAMPLE)?;
+    let gzip = encoder.finish()?;
+    assert_eq!(nbt::from_reader_gzip(&mut gzip.as_slice())?, expected);
+    assert_eq!(nbt::from_reader_auto(&mut gzip.as_slice())?, expected);
+
+    let player = NamedTag::compound("", vec![
+        NamedTag::new("Health", Tag::Float(20.0)),
+        NamedTag::new("Pos", Tag::LongArray(vec![1, 64, -3]))
+    ]);
+    let written = nbt::to_bytes_gzip(&player)?;
+    assert_eq!(&written[..2], &[0x1f, 0x8b]);
+    assert_eq!(nbt::from_reader_auto(&mut written.as_slice())?, player);
+
+    let mut encoder = flate2::write::ZlibEncoder::new(vec![], flate2::Compression::default());
+    encoder.write_all(SAMPLE)?;
+    let zlib = encoder.finish()?;
+    assert_eq!(nbt::from_reader_zlib(&mut zlib.as_slice())?, expected);
+    assert_eq!(nbt::from_reader_auto(&mut zlib.as_slice())?, expected);
+
+    assert_eq!(nbt::from_reader_auto(&mut &SAMPLE[..])?, expected);
+    assert!(nbt::from_reader_gzip(&mut &SAMPLE[..]).is_err());
+    Ok(())
+}
+
+#[test]
+fn nbt_snbt() -> Result<(), super::Error> {
+    use super::nbt::{NamedTag, Tag};
+
+    let tag = Tag::from_snbt("{Air:0s,Items:[{Slot:0b}]}")?;
+    assert_eq!(tag, Tag::Compound(vec![
+        NamedTag::new("Air", Tag::Short(0)),
+        NamedTag::new("Items", Tag::List(vec![
+            Tag::Compound(vec![NamedTag::new("Slot", Tag::Byte(0))])
+        ]))
+    ]));
+
+    assert_eq!(Tag::from_snbt("[B; 1b, -2b]")?, Tag::ByteArray(vec![1, -2]));
+    assert_eq!(Tag::from_snbt("[I;1,2,3]")?, Tag::IntArray(vec![1, 2, 3]));
+    assert_eq!(Tag::from_snbt("[L;1L,-9000000000L]")?, Tag::LongArray(vec![1, -9000000000]));
+    assert_eq!(Tag::from_snbt("42")?, Tag::Int(42));
+    assert_eq!(Tag::from_snbt("1.5")?, Tag::Double(1.5));
+    assert_eq!(Tag::from_snbt("1.5f")?, Tag::Float(1.5));
+    assert_eq!(Tag::from_snbt("7l")?, Tag::Long(7));
+    assert_eq!(Tag::from_snbt("true")?, Tag::Byte(1));
+    assert_eq!(Tag::from_snbt("minecraft")?, Tag::String(String::from("minecraft")));
+    assert_eq!(Tag::from_snbt("{}")?, Tag::Compound(vec![]));
+    assert_eq!(Tag::from_snbt("[]")?, Tag::EmptyList(0));
+    assert_eq!(Tag::from_snbt("[ ]")?, Tag::EmptyList(0));
+    assert_eq!(
+        Tag::from_snbt(r#"{"display name": 'it\'s "here"'}"#)?,
+        Tag::Compound(vec![NamedTag::new("display name", Tag::String(String::from("it's \"here\"")))])
+    );
+
+    assert!(matches!(Tag::from_snbt("[1, 2b]"), Err(super::Error::InvalidSnbt(4))));
+    assert!(matches!(Tag::from_snbt("{a:1"), Err(super::Error::InvalidSnbt(4))));
+    assert!(matches!(Tag::from_snbt("[B;300b]"), Err(super::Error::InvalidSnbt(3))));
+
+    // Nesting is limited to 512 levels, like binary NBT.
+    assert!(matches!(Tag::from_snbt(&"[".repeat(200_000)), Err(super::Error::NbtTooDeep)));
+    assert!(matches!(Tag::from_snbt(&"{a:".repeat(513)), Err(super::Error::NbtTooDeep)));
+    let deepest = format!("{}{}", "[".repeat(512), "]".repeat(512));
+    assert!(Tag::from_snbt(&deepest).is_ok());
+
+    let complex = Tag::Compound(vec![
+        NamedTag::new("name", Tag::String(String::from("a \"quoted\" \\ name"))),
+        NamedTag::new("key with spaces", Tag::Long(-5)),
+        NamedTag::new("numbers", Tag::List(vec![Tag::Float(0.25), Tag::Float(-1.0)])),
+        NamedTag::new("depth", Tag::Double(12.5)),
+        NamedTag::new("ints", Tag::IntArray(vec![]))
+    ]);
+    assert_eq!(Tag::from_snbt(&complex.to_snbt())?, complex);
+    assert_eq!(Tag::Compound(vec![NamedTag::new("Air", Tag::Short(300))]).to_snbt(), "{Air:300s}");
+
+    // Numbers SNBT can't represent don't survive the trip.
+    assert_eq!(Tag::Float(f32::NAN).to_snbt(), "NaNf");
+    assert_eq!(Tag::from_snbt(&Tag::Float(f32::NAN).to_snbt())?, Tag::String(String::from("NaNf")));
+    assert_eq!(Tag::from_snbt(&Tag::Double(f64::INFINITY).to_snbt())?, Tag::String(String::from("infd")));
+    assert_eq!(Tag::from_snbt(&Tag::Float(f32::NEG_INFINITY).to_snbt())?, Tag::String(String::from("-inff")));
+    assert_eq!(Tag::from_snbt(&Tag::EmptyList(0x03).to_snbt())?, Tag::EmptyList(0));
+    assert_eq!(Tag::from_snbt("-NaNd")?, Tag::String(String::from("-NaNd")));
+    assert_eq!(Tag::from_snbt("+inf")?, Tag::String(String::from("+inf")));
+    Ok(())
+}
+
+#[test]
+fn nbt_empty_list_type() -> Result<(), super::Error> {
+    use super::nbt::{self, NamedTag, Tag};
+
+    // A root compound holding an empty list of Ints called "a".
+    let bytes = [
+        0x0a, 0x00, 0x00,
+        0x09, 0x00, 0x01, b'a', 0x03, 0x00, 0x00, 0x00, 0x00,
+        0x00
+    ];
+    let root = nbt::from_reader(&mut &bytes[..])?;
+    assert_eq!(root, NamedTag::compound("", vec![NamedTag::new("a", Tag::EmptyList(0x03))]));
+    assert_eq!(root.tag.as_map().unwrap()["a"].len(), Some(0));
+    assert_eq!(nbt::to_bytes(&root)?, bytes);
+
+    let mut written = vec![];
+    root.tag.write_to(&mut written)?;
+    assert_eq!(written, bytes[3..]);
+
+    // Empty SNBT lists survive a trip through binary NBT unchanged.
+    let parsed = NamedTag::new("", Tag::from_snbt("{a:[]}")?);
+    assert_eq!(nbt::from_reader(&mut &nbt::to_bytes(&parsed)?[..])?, parsed);
+    Ok(())
+}
+
+#[test]
+fn nbt_accessors() -> Result<(), super::Error> {
+    use super::nbt::{self, Tag};
+
+    const SAMPLE: &[u8] = include_bytes!("../mojang/bigtest.nbt");
+    let root = nbt::from_reader(&mut &SAMPLE[..])?.tag;
+
+    assert_eq!(root.get("intTest").and_then(Tag::as_i32), Some(2147483647));
+    assert_eq!(root.get("byteTest").and_then(Tag::as_i8), Some(127));
+    assert_eq!(root.get("shortTest").and_then(Tag::as_i16), Some(32767));
+    assert_eq!(root.get("longTest").and_then(Tag::as_i64), Some(9223372036854775807));
+    assert_eq!(root.get("doubleTest").and_then(Tag::as_f64), Some(0.4931287132182315));
+    assert!(root.get("floatTest").and_then(Tag::as_f32).is_some());
+    assert_eq!(root.get("intTest").and_then(Tag::as_i64), None);
+    assert_eq!(root.get("missing"), None);
+
+    let egg = root.get("nested compound test")
+        .and_then(|nested| nested.get("egg"))
+        .and_then(|egg| egg.get("name"))
+        .and_then(Tag::as_str);
+    assert_eq!(egg, Some("Eggbert"));
+
+    let longs = root.get("listTest (long)").and_then(Tag::as_list).unwrap();
+    assert_eq!(longs.iter().filter_map(Tag::as_i64).collect::<Vec<_>>(), vec![11, 12, 13, 14, 15]);
+    assert_eq!(root.get("listTest (compound)").and_then(Tag::as_list).map(<[Tag]>::len), Some(2));
+    assert_eq!(root.as_compound().map(<[_]>::len), Some(11));
+    assert_eq!(Tag::EmptyList(0x03).as_list(), Some(&[][..]));
+    assert_eq!(Tag::IntArray(vec![1]).as_int_array(), Some(&[1][..]));
+    assert_eq!(root.get("intTest").and_then(Tag::as_compound), None);
+    Ok(())
+}
+
+#[test]
+fn nbt_path() -> Result<(), super::Error> {
+    use super::nbt::{NamedTag, Tag};
+
+    let player = Tag::Compound(vec![
+        NamedTag::new("Inventory", Tag::List(vec![
+            Tag::Compound(vec![
+                NamedTag::new("id", Tag::String(String::from("minecraft:stone"))),
+                NamedTag::new("count", Tag::Int(64))
+            ])
+        ])),
+        NamedTag::new("EnderItems", Tag::EmptyList(0x0a)),
+        NamedTag::new("Pos", Tag::List(vec![Tag::Double(0.5), Tag::Double(64.0), Tag::Double(-3.5)]))
+    ]);
+
+    assert_eq!(player.path("Inventory/0/id").and_then(Tag::as_str), Some("minecraft:stone"));
+    assert_eq!(player.path("Inventory/0/count").and_then(Tag::as_i32), Some(64));
+    assert_eq!(player.path("Pos/2").and_then(Tag::as_f64), Some(-3.5));
+    assert_eq!(player.path(""), Some(&player));
+    // Out of range indices, bad indices, missing names, and wrong types.
+    assert_eq!(player.path("Inventory/1/id"), None);
+    assert_eq!(player.path("Inventory/first"), None);
+    assert_eq!(player.path("EnderItems/0"), None);
+    assert_eq!(player.path("Health"), None);
+    assert_eq!(player.path("Pos/0/x"), None);
+    Ok(())
+}
+
+#[test]
+fn nbt_depth_limit() -> Result<(), super::Error> {
+    use super::{nbt, Error};
+
+    // A root compound holding unnamed compounds nested `depth` levels deep.
+    fn nested(depth: usize) -> Vec<u8> {
+        let mut data = vec![];
+        for _ in 0..depth {
+            data.extend_from_slice(&[0x0a, 0x00, 0x00]);
+        }
+        data.extend(std::iter::repeat_n(0x00, depth));
+        data
+    }
+
+    assert!(matches!(nbt::from_reader(&mut nested(100_000).as_slice()), Err(Error::NbtTooDeep)));
+    assert!(matches!(nbt::read_named_tag(&mut &nested(100_000)[3..]), Err(Error::NbtTooDeep)));
+    assert!(nbt::from_reader(&mut nested(512).as_slice()).is_ok());
+    assert!(matches!(nbt::from_reader(&mut nested(513).as_slice()), Err(Error::NbtTooDeep)));
+
+    assert!(nbt::from_reader_limited(&mut nested(8).as_slice(), 8).is_ok());
+    assert!(matches!(nbt::from_reader_limited(&mut nested(9).as_slice(), 8), Err(Error::NbtTooDeep)));
+    Ok(())
+}
+
+#[test]
+fn nbt_compound_builder() -> Result<(), super::Error> {
+    use super::nbt::{self, CompoundBuilder, NamedTag, Tag};
+
+    let item = CompoundBuilder::new()
+        .byte("Count", 1)
+        .string("id", "minecraft:stone")
+        .compound("tag", CompoundBuilder::new().short("Damage", 3).long("Seed", -1))
+        .build_named("");
+    assert_eq!(item, NamedTag::compound("", vec![
+        NamedTag::new("Count", Tag::Byte(1)),
+        NamedTag::new("id", Tag::String(String::from("minecraft:stone"))),
+        NamedTag::new("tag", Tag::Compound(vec![
+            NamedTag::new("Damage", Tag::Short(3)),
+            NamedTag::new("Seed", Tag::Long(-1))
+        ]))
+    ]));
+    let bytes = nbt::to_bytes(&item)?;
+    assert_eq!(nbt::from_bytes(&bytes)?, (item, bytes.len()));
+
+    let player = CompoundBuilder::new()
+        .int("DataVersion", 4189)
+        .float("Health", 20.0)
+        .double("fall_distance", 0.0)
+        .byte_array("Flags", vec![1, 0])
+        .int_array("UUID", vec![1, 2, 3, 4])
+        .long_array("Seeds", vec![5])
+        .list("Pos", vec![Tag::Double(0.5), Tag::Double(64.0), Tag::Double(-3.5)])
+        .tag("Inventory", Tag::EmptyList(0x0a))
+        .build();
+    let mut written = vec![];
+    nbt::to_network_writer(&mut written, &player)?;
+    assert_eq!(nbt::from_reader_network(&mut written.as_slice())?.tag, player);
+    assert_eq!(player.path("Pos/1").and_then(Tag::as_f64), Some(64.0));
+    assert_eq!(CompoundBuilder::new().build(), Tag::Compound(vec![]));
+    Ok(())
+}
+
+#[test]
+fn nbt_bit_eq() -> Result<(), super::Error> {
+    use super::nbt::{self, NamedTag, Tag};
+
+    let heightmap = Tag::Compound(vec![
+        NamedTag::new("scale", Tag::Double(f64::NAN)),
+        NamedTag::new("samples", Tag::List(vec![Tag::Float(1.5), Tag::Float(f32::NAN)]))
+    ]);
+    assert_ne!(heightmap, heightmap.clone());
+    assert!(heightmap.bit_eq(&heightmap.clone()));
+
+    let mut written = vec![];
+    nbt::to_network_writer(&mut written, &heightmap)?;
+    assert!(nbt::from_reader_network(&mut written.as_slice())?.tag.bit_eq(&heightmap));
+
+    assert!(!Tag::Double(0.0).bit_eq(&Tag::Double(-0.0)));
+    assert!(!Tag::Double(f64::NAN).bit_eq(&Tag::Double(-f64::NAN)));
+    assert!(!Tag::Float(1.0).bit_eq(&Tag::Double(1.0)));
+    assert!(!heightmap.bit_eq(&Tag::Compound(vec![])));
+    assert!(Tag::Int(3).bit_eq(&Tag::Int(3)));
+    Ok(())
+}
+
+#[test]
+fn nbt_display() -> Result<(), super::Error> {
+    use super::nbt::{self, NamedTag, Tag};
+
+    let root = NamedTag::compound("root", vec![
+        NamedTag::new("x", Tag::Byte(3)),
+        NamedTag::new("items", Tag::List(vec![
+            Tag::Compound(vec![NamedTag::new("id", Tag::String(String::from("minecraft:stone")))]),
+            Tag::Compound(vec![])
+        ])),
+        NamedTag::new("scale", Tag::Float(0.5)),
+        NamedTag::new("uuid", Tag::IntArray(vec![1, -2])),
+        NamedTag::new("empty", Tag::EmptyList(0x03))
+    ]);
+    let expected = concat!(
+        "Compound \"root\" {\n",
+        "    Byte \"x\": 3b,\n",
+        "    List \"items\": [\n",
+        "        Compound {\n",
+        "            String \"id\": \"minecraft:stone\"\n",
+        "        },\n",
+        "        Compound {}\n",
+        "    ],\n",
+        "    Float \"scale\": 0.5f,\n",
+        "    IntArray \"uuid\": [I; 1, -2],\n",
+        "    List \"empty\": []\n",
+        "}"
+    );
+    assert_eq!(root.to_string(), expected);
+    assert_eq!(Tag::Long(4).to_string(), "Long 4L");
+
+    const SAMPLE: &[u8] = include_bytes!("../mojang/bigtest.nbt");
+    let bigtest = nbt::from_reader(&mut &SAMPLE[..])?.to_string();
+    assert!(bigtest.starts_with("Compound \"Level\" {\n"));
+    assert!(bigtest.contains("\n    Long \"longTest\": 9223372036854775807L,\n"));
+    assert!(bigtest.contains("\n            String \"name\": \"Eggbert\",\n"));
+    Ok(())
+}
+
+#[test]
+fn nbt_to_writer() -> Result<(), super::Error> {
+    use super::nbt::{self, NamedTag, Tag};
+
+    const SAMPLE: &[u8] = include_bytes!("../mojang/bigtest.nbt");
+    let bigtest = nbt::from_reader(&mut &SAMPLE[..])?;
+    let mut written = vec![];
+    nbt::to_writer(&bigtest, &mut written)?;
+    assert_eq!(written, nbt::to_bytes(&bigtest)?);
+    assert_eq!(nbt::from_bytes(&written)?, (bigtest, written.len()));
+
+    let mut nothing = vec![];
+    assert!(matches!(
+        nbt::to_writer(&NamedTag::new("", Tag::Int(1)), &mut nothing),
+        Err(super::Error::InvalidRootTag)
+    ));
+    assert!(nothing.is_empty());
+    Ok(())
+}
+
+#[test]
+fn nbt_write_by_reference() -> Result<(), super::Error> {
+    use super::nbt::{self, CompoundBuilder};
+
+    let root = CompoundBuilder::new()
+        .string("id", "minecraft:stone")
+        .compound("tag", CompoundBuilder::new().int("Damage", 2))
+        .build_named("");
+    // The same tree can be written any number of times without cloning it.
+    let first = nbt::to_bytes(&root)?;
+    let second = nbt::to_bytes(&root)?;
+    assert_eq!(first, second);
+    assert_eq!(nbt::from_bytes(&first)?.0, root);
+
+    let mut streamed = vec![];
+    root.tag.write_to(&mut streamed)?;
+    assert_eq!(root.tag.write_to_bytes()?, streamed);
+    assert_eq!(&first[3..], streamed.as_slice());
+    Ok(())
+}
+
+#[test]
+fn nbt_header_errors() -> Result<(), super::Error> {
+    use super::{nbt, Error};
+
+    // Streams that end before the root compound's name is complete.
+    for truncated in [&[][..], &[0x0a], &[0x0a, 0x00], &[0x0a, 0x00, 0x02, b'h']] {
+        match nbt::from_reader(&mut &truncated[..]) {
+            Err(Error::ReaderError(e)) => assert_eq!(e.kind(), std::io::ErrorKind::UnexpectedEof),
+            other => panic!("expected an end of file error, got {:?}", other)
+        }
+    }
+    // A root tag that isn't a compound, and a child with an unknown type.
+    assert!(matches!(nbt::from_reader(&mut &[0x08, 0x00, 0x00][..]), Err(Error::InvalidNbtHeader)));
+    assert!(matches!(nbt::from_bytes(&[0x0a, 0x00, 0x00, 0x0d, 0x00, 0x00]), Err(Error::InvalidNbtType)));
+    assert!(matches!(
+        nbt::to_bytes(&nbt::NamedTag::new("", nbt::Tag::Byte(1))),
+        Err(Error::InvalidRootTag)
+    ));
+    Ok(())
+}
+
+#[test]
+fn nbt_nested_list_bytes() -> Result<(), super::Error> {
+    use super::nbt::{self, Tag};
+
+    // A compound holding a list of two shorts, followed by a byte, so that
+    // anything written after the list ends up in the wrong place.
+    let compound = [
+        0x09, 0x00, 0x01, b'l', 0x02, 0x00, 0x00, 0x00, 0x02, 0x00, 0x01, 0x00, 0x02,
+        0x01, 0x00, 0x01, b'b', 0x7f,
+        0x00
+    ];
+    let tag = nbt::read_tag_by_type(&mut &compound[..], 0x0a)?;
+    assert_eq!(tag.get("b").and_then(Tag::as_i8), Some(127));
+    assert_eq!(tag.write_to_bytes()?, compound);
+
+    let list = tag.get("l").unwrap();
+    assert_eq!(list.write_to_bytes()?, compound[4..13]);
+    assert_eq!(Tag::List(vec![]).write_to_bytes()?, [0x00, 0x00, 0x00, 0x00, 0x00]);
+    Ok(())
+}
+
+#[test]
+fn nbt_entries() -> Result<(), super::Error> {
+    use super::nbt::{CompoundBuilder, Tag};
+
+    let item = CompoundBuilder::new()
+        .byte("Count", 1)
+        .string("id", "minecraft:stone")
+        .byte("Slot", 4)
+        .build();
+    let bytes: Vec<&str> = item.entries().unwrap()
+        .filter(|entry| entry.tag.as_i8().is_some())
+        .map(|entry| entry.name.as_str())
+        .collect();
+    assert_eq!(bytes, ["Count", "Slot"]);
+    assert_eq!(item.entries().map(Iterator::count), Some(3));
+    assert_eq!(Tag::Compound(vec![]).entries().map(Iterator::count), Some(0));
+    assert!(Tag::List(vec![Tag::Int(1)]).entries().is_none());
+    Ok(())
+}
+
+#[test]
+fn nbt_modified_utf8_strings() -> Result<(), super::Error> {
+    use super::nbt::{self, Tag};
+
+    // U+1F600 as Java writes it: a surrogate pair, each half in three bytes.
+    let java = [0x08, 0x00, 0x07, b'>', 0xED, 0xA0, 0xBD, 0xED, 0xB8, 0x80];
+    assert_eq!(nbt::read_tag_with_type(&mut &java[..])?, Tag::String(String::from(">\u{1F600}")));
+    let mut written = vec![];
+    nbt::to_network_writer(&mut written, &Tag::String(String::from(">\u{1F600}")))?;
+    assert_eq!(written, java);
+
+    // A lone continuation byte, and the first half of a surrogate pair
+    // without the second.
+    for malformed in [&[0x08, 0x00, 0x01, 0x80][..], &[0x08, 0x00, 0x04, 0xED, 0xA0, 0xBD, b'a']] {
+        assert!(matches!(
+            nbt::read_tag_with_type(&mut &malformed[..]),
+            Err(super::Error::InvalidJavaUtf8(_))
+        ));
+    }
+    Ok(())
+}
+
+#[test]
+fn nbt_size_limit() -> Result<(), super::Error> {
+    use super::nbt::{self, CompoundBuilder, ReadLimits};
+    use super::Error;
+    use std::io::Read;
+
+    // A byte array claiming i32::MAX elements, followed by endless data.
+    let header = [0x0a, 0x00, 0x00, 0x07, 0x00, 0x00, 0x7f, 0xff, 0xff, 0xff];
+    let mut endless = header.chain(std::io::repeat(1));
+    let limits = ReadLimits { max_length: usize::MAX, max_bytes: 4096, ..ReadLimits::default() };
+    assert!(matches!(nbt::from_reader_with_limits(&mut endless, &limits), Err(Error::NbtTooLarge)));
+
+    // A list of lists, each claiming 2,000,000 End tags. These would take up
+    // memory without using up any of the budget, so they're rejected outright.
+    let mut end_lists = vec![0x0a, 0x00, 0x00, 0x09, 0x00, 0x00, 0x09, 0x00, 0x00, 0x00, 50];
+    for _ in 0..50 {
+        end_lists.extend_from_slice(&[0x00, 0x00, 0x1e, 0x84, 0x80]);
+    }
+    end_lists.push(0x00);
+    assert!(matches!(nbt::from_reader_with_limits(&mut end_lists.as_slice(), &limits), Err(Error::InvalidNbtType)));
+    // Every other tag uses up at least a byte, so a long list of them runs out.
+    let header = [0x0a, 0x00, 0x00, 0x09, 0x00, 0x00, 0x01, 0x00, 0x20, 0x00, 0x00];
+    let mut endless = header.chain(std::io::repeat(1));
+    assert!(matches!(nbt::from_reader_with_limits(&mut endless, &limits), Err(Error::NbtTooLarge)));
+
+    let root = CompoundBuilder::new().byte_array("data", vec![7; 100]).build_named("");
+    let bytes = nbt::to_bytes(&root)?;
+    let exact = ReadLimits { max_bytes: bytes.len(), ..ReadLimits::default() };
+    assert_eq!(nbt::from_reader_with_limits(&mut bytes.as_slice(), &exact)?, root);
+    let short = ReadLimits { max_bytes: bytes.len() - 1, ..ReadLimits::default() };
+    assert!(matches!(nbt::from_reader_with_limits(&mut bytes.as_slice(), &short), Err(Error::NbtTooLarge)));
+    // Data that ends early is still reported as missing, not too large.
+    assert!(matches!(nbt::from_reader_with_limits(&mut &bytes[..50], &exact), Err(Error::MissingData)));
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn nbt_serde() -> Result<(), super::Error> {
+    use super::nbt::{self, CompoundBuilder, NamedTag, Tag};
+
+    let root = CompoundBuilder::new()
+        .string("id", "minecraft:stone")
+        .byte_array("bytes", vec![1, 2])
+        .int_array("ints", vec![1, 2])
+        .long_array("longs", vec![1, 2])
+        .list("doubles", vec![Tag::Double(0.5)])
+        .tag("empty", Tag::EmptyList(0x03))
+        .compound("tag", CompoundBuilder::new().int("b", 1).int("a", 2))
+        .build_named("root");
+    let json = serde_json::to_value(&root)?;
+    assert_eq!(json["tag"]["Compound"]["ints"], serde_json::json!({ "IntArray": [1, 2] }));
+    assert_eq!(json["tag"]["Compound"]["longs"], serde_json::json!({ "LongArray": [1, 2] }));
+    assert_eq!(json["tag"]["Compound"]["tag"]["Compound"]["b"], serde_json::json!({ "Int": 1 }));
+
+    // Compounds keep their order, so the bytes come out the same.
+    let text = serde_json::to_string(&root)?;
+    let read: NamedTag = serde_json::from_str(&text)?;
+    assert_eq!(read, root);
+    assert_eq!(nbt::to_bytes(&read)?, nbt::to_bytes(&root)?);
+    Ok(())
+}
+
+#[test]
+fn varint_read_size() -> Result<(), super::Error> {
+    use super::{VarInt, VarLong};
+
+    let (one, used) = VarInt::from_bytes(&[0x05])?;
+    assert_eq!((one.read_size(), used), (Some(1), 1));
+    let (five, used) = VarInt::from_bytes(&[0xff, 0xff, 0xff, 0xff, 0x0f, 0xAA])?;
+    assert_eq!((five.value(), five.read_size(), used), (-1, Some(5), 5));
+    assert_eq!(VarInt::from_reader(&mut &[0x05][..])?.read_size(), Some(1));
+    assert_eq!(VarInt::from_reader(&mut &[0xff, 0xff, 0xff, 0xff, 0x0f][..])?.read_size(), Some(5));
+    assert_eq!(VarInt::from_reader(&mut &[0xdd, 0xc7, 0x01][..])?.read_size(), Some(3));
+
+    let mut calculated = VarInt::from_value(300)?;
+    assert_eq!(calculated.read_size(), None);
+    calculated.calculate_read_size();
+    assert_eq!(calculated.read_size(), Some(2));
+
+    assert_eq!(VarLong::from_bytes(&[0x01])?.0.read_size(), Some(1));
+    assert_eq!(VarLong::from_reader(&mut &[0x80, 0x01][..])?.read_size(), Some(2));
+    Ok(())
+}
+
+#[test]
+fn varlong_to_writer_extremes() -> Result<(), super::Error> {
+    use super::VarLong;
+
+    for value in [i64::MIN, i64::MAX, -1, 1 << 40, 0] {
+        let mut written = vec![];
+        VarLong::from_value(value)?.to_writer(&mut written)?;
+        assert_eq!(written, VarLong::from_value(value)?.to_bytes()?);
+        assert_eq!(VarLong::from_reader(&mut written.as_slice())?.value(), value);
+    }
+    let mut written = vec![];
+    VarLong::from_value(i64::MIN)?.to_writer(&mut written)?;
+    assert_eq!(written.len(), 10);
+    Ok(())
+}
+
+#[test]
+fn zigzag_varints() -> Result<(), super::Error> {
+    use super::{VarInt, VarLong};
+
+    assert_eq!(VarInt::from_value_zigzag(-1)?.to_bytes()?, vec![0x01]);
+    assert_eq!(VarInt::from_value_zigzag(1)?.to_bytes()?, vec![0x02]);
+    assert_eq!(VarInt::from_value_zigzag(-64)?.to_bytes()?, vec![0x7f]);
+    assert_eq!(VarLong::from_value_zigzag(-1)?.to_bytes()?, vec![0x01]);
+    // Two's complement -1 takes five bytes instead.
+    assert_eq!(VarInt::from_value(-1)?.to_bytes()?.len(), 5);
+
+    for value in [0, 1, -1, 300, -300, i32::MIN, i32::MAX] {
+        let bytes = VarInt::from_value_zigzag(value)?.to_bytes()?;
+        assert_eq!(VarInt::from_bytes(&bytes)?.0.zigzag_value(), value);
+    }
+    for value in [0, -1, i64::MIN, i64::MAX] {
+        let bytes = VarLong::from_value_zigzag(value)?.to_bytes()?;
+        assert_eq!(VarLong::from_bytes(&bytes)?.0.zigzag_value(), value);
+    }
+    Ok(())
+}
+
+#[test]
+fn varint_reader_eof() -> Result<(), super::Error> {
+    use super::{Error, VarInt, VarLong};
+
+    assert!(matches!(VarInt::from_reader(&mut &[][..]), Err(Error::MissingData)));
+    assert!(matches!(VarInt::from_reader(&mut &[0x80, 0x80][..]), Err(Error::VarIntTruncated)));
+    assert!(matches!(VarLong::from_reader(&mut &[][..]), Err(Error::MissingData)));
+    assert!(matches!(VarLong::from_reader(&mut &[0xff; 9][..]), Err(Error::VarIntTruncated)));
+    // The same bytes with the rest of the VarInt read fine.
+    assert_eq!(VarInt::from_reader(&mut &[0x80, 0x80, 0x01][..])?.value(), 16384);
+
+    // Errors from the reader itself are passed through.
+    struct Broken;
+    impl std::io::Read for Broken {
+        fn read(&mut self, _: &mut [u8]) -> std::io::Result<usize> {
+            Err(std::io::Error::other("broken"))
+        }
+    }
+    assert!(matches!(VarInt::from_reader(&mut Broken), Err(Error::ReaderError(_))));
+    Ok(())
+}
+
+#[test]
+fn varint_conversions_and_arithmetic() -> Result<(), super::Error> {
+    use super::{VarInt, VarLong};
+
+    let length = VarInt::from(300);
+    assert_eq!(length, VarInt::from_value(300)?);
+    assert_eq!(i32::from(length), 300);
+    assert_eq!(length.to_bytes()?, vec![0xac, 0x02]);
+    assert_eq!(length + VarInt::from(5), VarInt::from(305));
+    assert_eq!(length - VarInt::from(301), VarInt::from(-1));
+    let sum: i32 = (VarInt::from(1) + VarInt::from(2)).into();
+    assert_eq!(sum, 3);
+    // Results are new values, not read from bytes.
+    assert_eq!((VarInt::from_bytes(&[0x01])?.0 + VarInt::from(1)).read_size(), None);
+
+    let time = VarLong::from(1 << 40);
+    assert_eq!(i64::from(time), 1 << 40);
+    assert_eq!(time + VarLong::from(1), VarLong::from((1 << 40) + 1));
+    assert_eq!(time - time, VarLong::from(0));
+    Ok(())
+}
+
+#[test]
+fn infallible_varint_constructors() -> Result<(), super::Error> {
+    use super::{VarInt, VarLong};
+
+    assert_eq!(VarInt::new(-1), VarInt::from_value(-1)?);
+    assert_eq!(VarLong::new(i64::MIN), VarLong::from_value(i64::MIN)?);
+    let mut bytes = vec![0xAA];
+    VarInt::new(300).encode_into(&mut bytes);
+    VarLong::new(1).encode_into(&mut bytes);
+    assert_eq!(bytes, [0xAA, 0xac, 0x02, 0x01]);
+    Ok(())
+}
+
+#[test]
+fn varint_decoder() -> Result<(), super::Error> {
+    use super::{Error, VarInt, VarIntDecoder};
+
+    let mut decoder = VarIntDecoder::new();
+    assert_eq!(decoder.push(0xdd)?, None);
+    assert!(decoder.in_progress());
+    assert_eq!(decoder.push(0xc7)?, None);
+    let varint = decoder.push(0x01)?.unwrap();
+    assert_eq!((varint.value(), varint.read_size()), (25565, Some(3)));
+    assert!(!decoder.in_progress());
+
+    // The decoder starts over after each value.
+    assert_eq!(decoder.push(0x05)?, Some(VarInt::new(5)));
+    let mut last = None;
+    for byte in [0xff, 0xff, 0xff, 0xff, 0x0f] {
+        last = decoder.push(byte)?;
+    }
+    assert_eq!(last, Some(VarInt::new(-1)));
+
+    for byte in [0xff, 0xff, 0xff, 0xff] {
+        assert_eq!(decoder.push(byte)?, None);
+    }
+    assert!(matches!(decoder.push(0x1f), Err(Error::VarIntTooLong)));
+    assert!(!decoder.in_progress());
+    assert_eq!(decoder.push(0x7f)?, Some(VarInt::new(127)));
+    Ok(())
+}
+
+#[test]
+fn varint_encoded_len() -> Result<(), super::Error> {
+    use super::{VarInt, VarLong};
+
+    for value in [0, 1, 127, 128, 16383, 16384, 2097151, 2097152, 268435455, 268435456, i32::MAX, -1, i32::MIN] {
+        let varint = VarInt::new(value);
+        assert_eq!(varint.encoded_len() as usize, varint.to_bytes()?.len(), "{}", value);
+    }
+    for value in [0, 127, 128, 1 << 35, 1 << 62, i64::MAX, -1, i64::MIN] {
+        let varlong = VarLong::new(value);
+        assert_eq!(varlong.encoded_len() as usize, varlong.to_bytes()?.len(), "{}", value);
+    }
+    const SMALL: u8 = VarInt::new(0).encoded_len();
+    assert_eq!(SMALL, 1);
+
+    let mut calculated = VarInt::new(-1);
+    calculated.calculate_read_size();
+    assert_eq!(calculated.read_size(), Some(5));
+    Ok(())
+}
+
+#[test]
+fn position_offsets_and_neighbors() -> Result<(), super::Error> {
+    use super::enums::Orientation;
+    use super::Position;
+
+    let position = Position::from_values(10, 64, -30);
+    assert_eq!(position.offset(1, -2, 3), Position::from_values(11, 62, -27));
+    assert_eq!(position + (-11, 0, 30), Position::from_values(-1, 64, 0));
+    assert_eq!(position.neighbor(Orientation::Down), Position::from_values(10, 63, -30));
+    assert_eq!(position.neighbor(Orientation::Up), Position::from_values(10, 65, -30));
+    assert_eq!(position.neighbor(Orientation::North), Position::from_values(10, 64, -31));
+    assert_eq!(position.neighbor(Orientation::South), Position::from_values(10, 64, -29));
+    assert_eq!(position.neighbor(Orientation::West), Position::from_values(9, 64, -30));
+    assert_eq!(position.neighbor(Orientation::East), Position::from_values(11, 64, -30));
+    assert_eq!(
+        Position::from_values(i32::MAX, 0, 0).neighbor(Orientation::East),
+        Position::from_values(i32::MIN, 0, 0)
+    );
+    Ok(())
+}
+
+#[test]
+fn position_chunk_coordinates() -> Result<(), super::Error> {
+    use super::Position;
+
+    let negative = Position::from_values(-1, -64, -1);
+    assert_eq!((negative.chunk_x(), negative.chunk_z()), (-1, -1));
+    assert_eq!(negative.chunk_relative(), (15, -64, 15));
+
+    let position = Position::from_values(-17, 70, 33);
+    assert_eq!((position.chunk_x(), position.chunk_z()), (-2, 2));
+    assert_eq!(position.chunk_relative(), (15, 70, 1));
+
+    let origin = Position::from_values(0, 0, 15);
+    assert_eq!((origin.chunk_x(), origin.chunk_z()), (0, 0));
+    assert_eq!(origin.chunk_relative(), (0, 0, 15));
+    assert_eq!(Position::from_values(-16, 0, 16).chunk_x(), -1);
+    assert_eq!(Position::from_values(-16, 0, 16).chunk_z(), 1);
+    Ok(())
+}
+
+#[test]
+fn position_distances() -> Result<(), super::Error> {
+    use super::Position;
+
+    let a = Position::from_values(1, 2, 3);
+    let b = Position::from_values(4, -2, 3);
+    assert_eq!(a.distance_squared(b), 25);
+    assert_eq!(b.distance_squared(a), 25);
+    assert_eq!(a.manhattan(b), 7);
+    assert!(a.within(b, 5));
+    assert!(!a.within(b, 4));
+    assert!(a.within(a, 0));
+
+    // Opposite corners of the world border would overflow an i32.
+    let west = Position::from_values(-30_000_000, -64, -30_000_000);
+    let east = Position::from_values(30_000_000, 319, 30_000_000);
+    assert_eq!(west.distance_squared(east), 2 * 60_000_000i64.pow(2) + 383 * 383);
+    assert_eq!(west.manhattan(east), 120_000_383);
+    assert!(west.within(east, 85_000_000));
+    assert!(!west.within(east, 84_000_000));
+    Ok(())
+}
+
+#[test]
+fn uuid_hyphenated_strings() -> Result<(), super::Error> {
+    use super::{Error, UUID};
+
+    let notch = UUID::from_value(0x069a79f444e94726a5befca90e38aaf5)?;
+    assert_eq!(UUID::from_hyphenated("069a79f4-44e9-4726-a5be-fca90e38aaf5")?, notch);
+    assert_eq!(UUID::from_hyphenated("069a79f444e94726a5befca90e38aaf5")?, notch);
+    assert_eq!(UUID::from_hyphenated("069A79F4-44E9-4726-A5BE-FCA90E38AAF5")?, notch);
+    assert_eq!("069a79f4-44e9-4726-a5be-fca90e38aaf5".parse::<UUID>()?, notch);
+    assert_eq!(notch.to_hyphenated(), "069a79f4-44e9-4726-a5be-fca90e38aaf5");
+    assert_eq!(UUID::from_value(1)?.to_hyphenated(), "00000000-0000-0000-0000-000000000001");
+
+    for invalid in [
+        "",
+        "069a79f4-44e9-4726-a5be-fca90e38aaf",
+        "069a79f444e9-4726-a5be-fca90e38aaf5-",
+        "069a79f4-44e9-4726-a5be-fca90e38aag5",
+        "+69a79f444e94726a5befca90e38aaf5",
+        "069a79f4-44e9-4726-a5be-fca9-e38aaf5"
+    ] {
+        assert!(matches!(UUID::from_hyphenated(invalid), Err(Error::InvalidUuidString(_))), "{}", invalid);
+    }
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "mojang-api")]
+fn uuid_empty_profile_response() -> Result<(), super::Error> {
+    use super::{Error, UUID};
+
+    assert!(matches!(UUID::parse_profile_response(""), Err(Error::PlayerNotFound)));
+    assert!(matches!(UUID::parse_profile_response("\n"), Err(Error::PlayerNotFound)));
+    let profile = UUID::parse_profile_response(r#"{"id": "069a79f444e94726a5befca90e38aaf5", "name": "Notch"}"#)?;
+    assert_eq!(profile["name"], "Notch");
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "mojang-api")]
+fn uuid_cache() -> Result<(), super::Error> {
+    use super::{Error, UUID, UuidCache};
+    use std::time::{Duration, Instant};
+
+    let notch = UUID::from_value(0x069a79f444e94726a5befca90e38aaf5)?;
+    let mut cache = UuidCache::new(Duration::from_secs(60));
+    let start = Instant::now();
+    let mut fetches = 0;
+
+    assert_eq!(cache.uuid_or_else("Notch", start, |_| { fetches += 1; Ok(notch) })?, notch);
+    // Cached, and matched regardless of case.
+    assert_eq!(cache.uuid_or_else("notch", start + Duration::from_secs(30), |_| { fetches += 1; Ok(notch) })?, notch);
+    assert_eq!(fetches, 1);
+    // Expired.
+    assert_eq!(cache.uuid_or_else("NOTCH", start + Duration::from_secs(60), |_| { fetches += 1; Ok(notch) })?, notch);
+    assert_eq!(fetches, 2);
+
+    assert_eq!(cache.name_or_else(notch, start, |_| Ok("Notch".to_string()))?, "Notch");
+    assert_eq!(cache.name_or_else(notch, start, |_| Err(Error::PlayerNotFound))?, "Notch");
+
+    // Failed lookups aren't cached.
+    assert!(cache.uuid_or_else("jeb_", start, |_| Err(Error::PlayerNotFound)).is_err());
+    assert!(cache.uuid_or_else("jeb_", start, |_| Ok(notch)).is_ok());
+
+    cache.clear();
+    assert!(cache.name_or_else(notch, start, |_| Err(Error::PlayerNotFound)).is_err());
+    Ok(())
+}
+
+#[test]
+fn uuid_hash_and_ord() -> Result<(), super::Error> {
+    use super::UUID;
+    use std::collections::HashSet;
+
+    let notch = UUID::from_value(0x069a79f444e94726a5befca90e38aaf5)?;
+    let jeb = UUID::from_value(0x853c80ef3c3749fdaa49938b674adae6)?;
+    let nil = UUID::from_value(0)?;
+
+    let mut players = HashSet::new();
+    assert!(players.insert(jeb));
+    assert!(players.insert(notch));
+    assert!(players.insert(nil));
+    assert!(!players.insert(UUID::from_value(0x069a79f444e94726a5befca90e38aaf5)?));
+    assert_eq!(players.len(), 3);
+    assert!(players.contains(&notch));
+
+    let mut sorted: Vec<UUID> = players.into_iter().collect();
+    sorted.sort();
+    assert_eq!(sorted, vec![nil, notch, jeb]);
+    Ok(())
+}
+
+#[test]
+fn uuid_version_and_variant() -> Result<(), super::Error> {
+    use super::UUID;
+
+    let notch: UUID = "069a79f4-44e9-4726-a5be-fca90e38aaf5".parse()?;
+    assert_eq!(notch.version(), 4);
+    assert_eq!(notch.variant(), 0b10);
+    // The offline mode UUID for "Notch".
+    let offline: UUID = "b50ad385-829d-3141-a216-7e7d7539ba7f".parse()?;
+    assert_eq!(offline.version(), 3);
+    assert_eq!(offline.variant(), 0b10);
+
+    let nil = UUID::from_value(0)?;
+    assert_eq!(nil.version(), 0);
+    assert_eq!(nil.variant(), 0b0);
+    assert_eq!("00000000-0000-0000-c000-000000000000".parse::<UUID>()?.variant(), 0b110);
+    assert_eq!("ffffffff-ffff-ffff-ffff-ffffffffffff".parse::<UUID>()?.variant(), 0b111);
+    assert_eq!("ffffffff-ffff-ffff-ffff-ffffffffffff".parse::<UUID>()?.version(), 15);
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "random")]
+fn uuid_new_v4() -> Result<(), super::Error> {
+    use super::UUID;
+
+    let uuids: Vec<UUID> = (0..32).map(|_| UUID::new_v4()).collect();
+    for uuid in &uuids {
+        assert_eq!(uuid.version(), 4);
+        assert_eq!(uuid.variant(), 0b10);
+    }
+    let unique: std::collections::HashSet<&UUID> = uuids.iter().collect();
+    assert_eq!(unique.len(), uuids.len());
+    Ok(())
+}
+
+#[test]
+fn packet_decoder_after_login_success() -> Result<(), super::Error> {
+    use super::netty::{self, configuration, login, status, PacketDecoder, ProtocolState, Tags};
+    use super::{Error, Identifier, VarInt, UUID};
+
+    let mut decoder = PacketDecoder::new(ProtocolState::Login);
+    decoder.push(&login::ClientboundPacket::LoginSuccess {
+        uuid: UUID::from_value(1)?,
+        username: String::from("thisjaiden"),
+        properties: vec![],
+        strict_error_handling: false
+    }.to_bytes()?);
+    let update_tags = configuration::ClientboundPacket::UpdateTags {
+        registries: vec![Tags { registry: Identifier::from_string("minecraft:block".to_string())?, tags: vec![] }]
+    };
+    decoder.push(&update_tags.to_bytes()?);
+    // A Known Packs packet, which can't be read yet.
+    decoder.push(&[0x02, 0x0E, 0x00]);
+
+    assert!(decoder.next_packet()?.is_some());
+    assert_eq!(decoder.protocol_state(), ProtocolState::Configuration);
+    assert_eq!(decoder.next_packet()?, Some(netty::ClientboundPacket::Configuration(update_tags)));
+    assert!(matches!(decoder.next_packet(), Err(Error::UnsupportedPacket(_))));
+    assert!(decoder.buffered().is_empty());
+
+    // Compression doesn't apply to the "status" stage.
+    let mut decoder = PacketDecoder::new(ProtocolState::Status);
+    decoder.set_compression_threshold(Some(VarInt::new(256)));
+    decoder.push(&status::ClientboundPacket::PingResponse { payload: 7 }.to_bytes()?);
+    assert_eq!(
+        decoder.next_packet()?,
+        Some(netty::ClientboundPacket::Status(status::ClientboundPacket::PingResponse { payload: 7 }))
+    );
+    assert!(matches!(
+        netty::ClientboundPacket::from_reader_com(&mut [0x02, 0x00, 0x01].as_slice(), ProtocolState::Status),
+        Err(Error::InvalidProtocolState(ProtocolState::Status))
+    ));
+    Ok(())
+}
+
+#[test]
+fn truncated_varints_inside_packets() -> Result<(), super::Error> {
+    use super::netty::{self, ProtocolState};
+    use super::Error;
+
+    // A whole Login Disconnect packet, whose string length VarInt is cut off
+    // by the end of the packet.
+    let packet = [0x02, 0x00, 0x80];
+    let result = netty::ClientboundPacket::from_reader(&mut packet.as_slice(), ProtocolState::Login);
+    assert!(matches!(result, Err(Error::VarIntTruncated)), "{:?}", result);
+    // Running out partway through the length prefix is still a closed connection.
+    assert!(matches!(
+        netty::ClientboundPacket::from_reader(&mut [0x80].as_slice(), ProtocolState::Login),
+        Err(Error::ConnectionClosed)
+    ));
+    Ok(())
+}
+
+#[test]
+fn truncated_packet_bodies() -> Result<(), super::Error> {
+    use super::netty::{self, ProtocolState};
+    use super::Error;
+
+    // A complete two byte Ping Response frame, far too short for its payload.
+    let result = netty::ClientboundPacket::from_reader(&mut [0x02, 0x01, 0x00].as_slice(), ProtocolState::Status);
+    assert!(matches!(&result, Err(Error::ReaderError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof), "{:?}", result);
+    // The same for a compressed Login Disconnect whose string is longer than the packet.
+    let result = netty::ClientboundPacket::from_reader_com(&mut [0x04, 0x00, 0x00, 0x05, b'a'].as_slice(), ProtocolState::Login);
+    assert!(!matches!(result, Err(Error::ConnectionClosed) | Ok(_)), "{:?}", result);
+
+    // When the frame itself is cut off, the connection closed, even if the
+    // fields that were read parsed fine.
+    assert!(matches!(
+        netty::ClientboundPacket::from_reader(&mut [0x0A, 0x01, 0, 0, 0, 0, 0, 0, 0, 7].as_slice(), ProtocolState::Status),
+        Err(Error::ConnectionClosed)
+    ));
+    assert!(matches!(
+        netty::ClientboundPacket::from_reader(&mut [0x09, 0x01, 0, 0, 0].as_slice(), ProtocolState::Status),
+        Err(Error::ConnectionClosed)
+    ));
+    Ok(())
+}
+
+#[test]
+fn play_movement_packet_ids() -> Result<(), super::Error> {
+    use super::netty::play::ServerboundPacket;
+    use super::{VarInt, PROTOCOL_VERSION};
+
+    // These IDs are only right for protocol 768.
+    assert_eq!(PROTOCOL_VERSION, 768);
+    let packets = [
+        (ServerboundPacket::ConfirmTeleportation { teleport_id: VarInt::new(0) }, 0x00),
+        (ServerboundPacket::SetPlayerPosition { x: 0.0, y: 0.0, z: 0.0, on_ground: false }, 0x1C),
+        (ServerboundPacket::SetPlayerPositionAndRotation {
+            x: 0.0, y: 0.0, z: 0.0, yaw: 0.0, pitch: 0.0, on_ground: false
+        }, 0x1D),
+        (ServerboundPacket::SetPlayerRotation { yaw: 0.0, pitch: 0.0, on_ground: false }, 0x1E)
+    ];
+    for (packet, id) in packets {
+        // Every packet here is short enough for a one byte length prefix.
+        assert_eq!(packet.to_bytes()?[1], id);
+    }
+    Ok(())
+}
+
+#[test]
+fn oversized_packet_lengths() -> Result<(), super::Error> {
+    use super::netty::{self, BufferedConnection, PacketDecoder, ProtocolState, MAX_PACKET_LENGTH};
+    use super::{Error, VarInt};
+
+    let mut too_long = vec![];
+    VarInt::new(MAX_PACKET_LENGTH + 1).encode_into(&mut too_long);
+    let mut decoder = PacketDecoder::new(ProtocolState::Status);
+    decoder.push(&too_long);
+    assert!(matches!(decoder.next_packet(), Err(Error::InvalidPacketLength(_))));
+    let mut connection = BufferedConnection::new(too_long.as_slice());
+    assert!(matches!(connection.next_frame(), Err(Error::InvalidPacketLength(_))));
+    assert!(matches!(
+        netty::ClientboundPacket::from_reader(&mut too_long.as_slice(), ProtocolState::Status),
+        Err(Error::InvalidPacketLength(_))
+    ));
+
+    // The longest allowed packet just waits for more data.
+    let mut longest = vec![];
+    VarInt::new(MAX_PACKET_LENGTH).encode_into(&mut longest);
+    let mut decoder = PacketDecoder::new(ProtocolState::Status);
+    decoder.push(&longest);
+    assert!(decoder.next_packet()?.is_none());
+    Ok(())
+}
+
+#[test]
+fn compressed_packet_data_length() -> Result<(), super::Error> {
+    use super::netty::login::ServerboundPacket;
+    use super::VarInt;
+
+    let packet = ServerboundPacket::LoginPluginResponse {
+        message_id: VarInt::new(1),
+        data: Some(vec![7; 600])
+    };
+    let uncompressed = packet.to_bytes()?;
+    let (uncompressed_length, prefix) = VarInt::from_bytes(&uncompressed)?;
+
+    let bytes = packet.to_bytes_com(VarInt::new(256))?;
+    let (packet_length, packet_prefix) = VarInt::from_bytes(&bytes)?;
+    let (data_length, _) = VarInt::from_bytes(&bytes[packet_prefix..])?;
+    // The data length is the size of the packet before compression, and the
+    // packet length covers the data length and the compressed data.
+    assert_eq!(data_length.value() as usize, uncompressed.len() - prefix);
+    assert_eq!(data_length, uncompressed_length);
+    assert_eq!(packet_length.value() as usize, bytes.len() - packet_prefix);
+
+    // Below the threshold, the data length is zero.
+    let bytes = packet.to_bytes_com(VarInt::new(1000))?;
+    assert_eq!(bytes[VarInt::from_bytes(&bytes)?.1], 0x00);
+    assert_eq!(VarInt::from_bytes(&bytes)?.0.value() as usize, uncompressed.len() - prefix + 1);
+    Ok(())
+}
+
+#[test]
+fn oversized_data_lengths() -> Result<(), super::Error> {
+    use super::netty::{login, MAX_DATA_LENGTH};
+    use super::{Error, VarInt};
+    use std::io::Write;
+
+    // A few kilobytes of zlib data that inflate to more than the limit.
+    let mut encoder = flate2::write::ZlibEncoder::new(vec![], flate2::Compression::best());
+    encoder.write_all(&vec![0; MAX_DATA_LENGTH as usize + 1])?;
+    let compressed = encoder.finish()?;
+    let mut body = vec![];
+    VarInt::new(MAX_DATA_LENGTH + 1).encode_into(&mut body);
+    body.extend_from_slice(&compressed);
+    let mut frame = vec![];
+    VarInt::new(body.len() as i32).encode_into(&mut frame);
+    frame.extend_from_slice(&body);
+    assert!(matches!(
+        login::ClientboundPacket::from_reader_com(&mut frame.as_slice()),
+        Err(Error::InvalidPacketLength(length)) if length.value() == MAX_DATA_LENGTH + 1
+    ));
+    Ok(())
+}
+
+#[test]
+fn keep_alive_tracker_play() -> Result<(), super::Error> {
+    use super::netty::{self, play, KeepAliveTracker, ProtocolState};
+    use super::Error;
+    use std::time::Instant;
+
+    let start = Instant::now();
+    let mut tracker = KeepAliveTracker::new(start);
+    let response = tracker.respond(-7, start, ProtocolState::Play)?;
+    assert_eq!(response, netty::ServerboundPacket::Play(play::ServerboundPacket::KeepAlive { id: -7 }));
+    assert_eq!(tracker.last_sent(), Some(-7));
+
+    let netty::ServerboundPacket::Play(packet) = response else {
+        panic!("expected a play packet");
+    };
+    let bytes = packet.to_bytes()?;
+    assert_eq!(bytes, [0x09, 0x1A, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xF9]);
+    assert_eq!(play::ServerboundPacket::from_reader(&mut bytes.as_slice())?, packet);
+
+    // There's nothing to answer with outside of configuration and play.
+    assert!(matches!(
+        tracker.respond(8, start, ProtocolState::Login),
+        Err(Error::InvalidProtocolState(ProtocolState::Login))
+    ));
+    assert_eq!(tracker.last_received(), Some(8));
+    assert_eq!(tracker.last_sent(), Some(-7));
+    Ok(())
+}
+
+#[test]
+fn string_huge_length_prefix() -> Result<(), super::Error> {
+    use super::generalized::{
+        string_from_reader, string_from_reader_counted, string_from_reader_counted_no_cesu8,
+        string_from_reader_no_cesu8
+    };
+    use super::{Error, VarInt};
+
+    // Claims to be about 2 GB long, but only holds a few bytes. Reading it
+    // must fail without allocating the whole claimed length first.
+    let mut bytes = vec![];
+    VarInt::new(i32::MAX).encode_into(&mut bytes);
+    bytes.extend_from_slice(b"short");
+    let eof = |result: Result<_, Error>| matches!(
+        result, Err(Error::ReaderError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof
+    );
+    assert!(eof(string_from_reader(&mut bytes.as_slice())));
+    assert!(eof(string_from_reader_no_cesu8(&mut bytes.as_slice())));
+    assert!(eof(string_from_reader_counted(&mut bytes.as_slice()).map(|(text, _)| text)));
+    assert!(eof(string_from_reader_counted_no_cesu8(&mut bytes.as_slice()).map(|(text, _)| text)));
+
+    // Strings longer than the first allocation still read in full.
+    let long = "a".repeat(10_000);
+    let mut bytes = vec![];
+    VarInt::new(long.len() as i32).encode_into(&mut bytes);
+    bytes.extend_from_slice(long.as_bytes());
+    assert_eq!(string_from_reader(&mut bytes.as_slice())?, long);
+    Ok(())
+}